@@ -0,0 +1,24 @@
+// Money-movement reporting. Every path that moves lamports (fees, refunds,
+// slashing, treasury withdrawals, rent reclaimed on close) reports it here,
+// so finance tooling can sum LamportsMoved events instead of inferring
+// transfers from request events.
+use anchor_lang::prelude::*;
+
+use crate::events::LamportsMoved;
+use crate::types::{Handle, MoveReason};
+
+pub fn emit_lamports_moved(
+    reason: MoveReason,
+    from: Pubkey,
+    to: Pubkey,
+    amount: u64,
+    related_handle: Option<Handle>,
+) {
+    emit!(LamportsMoved {
+        reason,
+        from,
+        to,
+        amount,
+        related_handle,
+    });
+}
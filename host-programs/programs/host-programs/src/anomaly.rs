@@ -0,0 +1,58 @@
+// Derivation self-checks. A derived handle that equals one of its operands,
+// or that is already recorded with a different op or parents, points at a
+// hash-domain bug rather than a real sha256 collision.
+use anchor_lang::prelude::*;
+
+use crate::errors::ExecutorError;
+use crate::events::{AnomalyKind, DerivationAnomaly};
+use crate::opcode::OpCode;
+use crate::state::{Config, HandleRecord};
+use crate::types::Handle;
+
+/// Checks `result` of `op` against its `operands` and, when the caller
+/// passed it, the registry record already stored for `result`. Anomalies fail the
+/// request unless the policy allows soft failure (`anomaly_soft_fail`
+/// under the permissionless profile), in which case they are only
+/// reported through `DerivationAnomaly`.
+pub fn check_derivation(
+    config: &Config,
+    caller: Pubkey,
+    result: &Handle,
+    op: Option<OpCode>,
+    operands: &[Handle],
+    result_record: Option<&HandleRecord>,
+) -> Result<()> {
+    if operands.contains(result) {
+        report_anomaly(config, caller, result, op, AnomalyKind::DerivedEqualsOperand)?;
+    }
+    if let Some(record) = result_record {
+        require!(record.handle == *result, ExecutorError::ResultRecordMismatch);
+        if record.op_code != op || record.parents() != operands {
+            report_anomaly(config, caller, result, op, AnomalyKind::ProvenanceMismatch)?;
+        }
+    }
+    Ok(())
+}
+
+fn report_anomaly(
+    config: &Config,
+    caller: Pubkey,
+    result: &Handle,
+    op: Option<OpCode>,
+    kind: AnomalyKind,
+) -> Result<()> {
+    if config.policy().fail_on_anomaly {
+        let error = match kind {
+            AnomalyKind::DerivedEqualsOperand => ExecutorError::DerivedEqualsOperand,
+            AnomalyKind::ProvenanceMismatch => ExecutorError::ProvenanceMismatch,
+        };
+        return Err(error.into());
+    }
+    emit!(DerivationAnomaly {
+        caller,
+        result_handle: *result,
+        op_code: op,
+        kind,
+    });
+    Ok(())
+}
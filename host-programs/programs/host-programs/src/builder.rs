@@ -0,0 +1,258 @@
+// Fixed-arity request builders for Rust clients. Each constructor takes
+// exactly the operands its op family consumes, so a wrong operand count is
+// a compile error instead of a rejected transaction.
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+
+use crate::handle::*;
+use crate::request::OpRequest;
+use crate::types::*;
+
+/// `request_unary_op` with its single operand.
+///
+/// ```
+/// use anchor_lang::Discriminator;
+/// use host_programs::builder::UnaryRequest;
+/// use host_programs::instruction::RequestUnaryOp;
+/// use host_programs::types::Fhe16UnaryOp;
+///
+/// let data = UnaryRequest::new(Fhe16UnaryOp::Abs, [9; 32]).with_nonce(5).instruction_data();
+/// let (discriminator, args) = data.split_at(RequestUnaryOp::DISCRIMINATOR.len());
+/// assert_eq!(discriminator, RequestUnaryOp::DISCRIMINATOR);
+/// assert_eq!(args[0], Fhe16UnaryOp::Abs as u8);
+/// assert_eq!(&args[1..33], &[9; 32]);
+/// assert_eq!(args[33..], [[1].as_slice(), &5u64.to_le_bytes(), &[0]].concat());
+/// ```
+///
+/// A second operand does not compile:
+///
+/// ```compile_fail
+/// # use host_programs::builder::UnaryRequest;
+/// # use host_programs::types::Fhe16UnaryOp;
+/// UnaryRequest::new(Fhe16UnaryOp::Abs, [1; 32], [2; 32]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct UnaryRequest {
+    pub op: Fhe16UnaryOp,
+    pub input: Handle,
+    pub caller_nonce: Option<u64>,
+    pub dry_run: bool,
+}
+
+impl UnaryRequest {
+    pub fn new(op: Fhe16UnaryOp, input: Handle) -> Self {
+        Self {
+            op,
+            input,
+            caller_nonce: None,
+            dry_run: false,
+        }
+    }
+
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.caller_nonce = Some(nonce);
+        self
+    }
+
+    /// See `finish_dry_run`: simulate-only, the transaction always fails.
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Instruction data for `request_unary_op`.
+    pub fn instruction_data(&self) -> Vec<u8> {
+        crate::instruction::RequestUnaryOp {
+            op: self.op,
+            input_handle: self.input,
+            caller_nonce: self.caller_nonce,
+            dry_run: self.dry_run,
+        }
+        .data()
+    }
+
+    /// Handle the instruction returns under the config's `salt`.
+    pub fn result_handle(&self, program_id: &Pubkey, salt: &DomainSalt) -> Handle {
+        derive_unary_handle(self.op, &self.input, program_id, salt)
+    }
+}
+
+impl From<UnaryRequest> for OpRequest {
+    fn from(request: UnaryRequest) -> Self {
+        OpRequest::Unary {
+            op: request.op,
+            input: request.input,
+        }
+    }
+}
+
+/// `request_binary_op`, operands in `lhs`, `rhs` order.
+///
+/// ```
+/// use anchor_lang::Discriminator;
+/// use host_programs::builder::BinaryRequest;
+/// use host_programs::instruction::RequestBinaryOp;
+/// use host_programs::types::Fhe16BinaryOp;
+///
+/// let request = BinaryRequest::new(Fhe16BinaryOp::Sub, [1; 32], [2; 32]).dry_run();
+/// let data = request.instruction_data();
+/// let (discriminator, args) = data.split_at(RequestBinaryOp::DISCRIMINATOR.len());
+/// assert_eq!(discriminator, RequestBinaryOp::DISCRIMINATOR);
+/// assert_eq!(args[0], Fhe16BinaryOp::Sub as u8);
+/// assert_eq!((&args[1..33], &args[33..65]), (&[1; 32][..], &[2; 32][..]));
+/// // no nonce, dry run
+/// assert_eq!(args[65..], [0, 1]);
+/// ```
+///
+/// A missing operand does not compile:
+///
+/// ```compile_fail
+/// # use host_programs::builder::BinaryRequest;
+/// # use host_programs::types::Fhe16BinaryOp;
+/// BinaryRequest::new(Fhe16BinaryOp::Add, [1; 32]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct BinaryRequest {
+    pub op: Fhe16BinaryOp,
+    pub lhs: Handle,
+    pub rhs: Handle,
+    pub caller_nonce: Option<u64>,
+    pub dry_run: bool,
+}
+
+impl BinaryRequest {
+    pub fn new(op: Fhe16BinaryOp, lhs: Handle, rhs: Handle) -> Self {
+        Self {
+            op,
+            lhs,
+            rhs,
+            caller_nonce: None,
+            dry_run: false,
+        }
+    }
+
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.caller_nonce = Some(nonce);
+        self
+    }
+
+    /// See `finish_dry_run`: simulate-only, the transaction always fails.
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Instruction data for `request_binary_op`.
+    pub fn instruction_data(&self) -> Vec<u8> {
+        crate::instruction::RequestBinaryOp {
+            op: self.op,
+            lhs_handle: self.lhs,
+            rhs_handle: self.rhs,
+            caller_nonce: self.caller_nonce,
+            dry_run: self.dry_run,
+        }
+        .data()
+    }
+
+    /// Handle the instruction returns under the config's `salt`.
+    pub fn result_handle(&self, program_id: &Pubkey, salt: &DomainSalt) -> Handle {
+        derive_binary_handle(self.op, &self.lhs, &self.rhs, program_id, salt)
+    }
+}
+
+impl From<BinaryRequest> for OpRequest {
+    fn from(request: BinaryRequest) -> Self {
+        OpRequest::Binary {
+            op: request.op,
+            lhs: request.lhs,
+            rhs: request.rhs,
+        }
+    }
+}
+
+/// `request_ternary_op`, operands in `a`, `b`, `c` order.
+///
+/// ```
+/// use anchor_lang::Discriminator;
+/// use host_programs::builder::TernaryRequest;
+/// use host_programs::instruction::RequestTernaryOp;
+/// use host_programs::types::Fhe16TernaryOp;
+///
+/// let request = TernaryRequest::new(Fhe16TernaryOp::Select, [1; 32], [2; 32], [3; 32]);
+/// let data = request.instruction_data();
+/// let (discriminator, args) = data.split_at(RequestTernaryOp::DISCRIMINATOR.len());
+/// assert_eq!(discriminator, RequestTernaryOp::DISCRIMINATOR);
+/// assert_eq!(args[0], Fhe16TernaryOp::Select as u8);
+/// assert_eq!(args[1..97], [[1; 32], [2; 32], [3; 32]].concat());
+/// assert_eq!(args[97..], [0, 0]);
+/// ```
+///
+/// A binary op does not fit a ternary request:
+///
+/// ```compile_fail
+/// # use host_programs::builder::TernaryRequest;
+/// # use host_programs::types::Fhe16BinaryOp;
+/// TernaryRequest::new(Fhe16BinaryOp::Add, [1; 32], [2; 32], [3; 32]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct TernaryRequest {
+    pub op: Fhe16TernaryOp,
+    pub a: Handle,
+    pub b: Handle,
+    pub c: Handle,
+    pub caller_nonce: Option<u64>,
+    pub dry_run: bool,
+}
+
+impl TernaryRequest {
+    pub fn new(op: Fhe16TernaryOp, a: Handle, b: Handle, c: Handle) -> Self {
+        Self {
+            op,
+            a,
+            b,
+            c,
+            caller_nonce: None,
+            dry_run: false,
+        }
+    }
+
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.caller_nonce = Some(nonce);
+        self
+    }
+
+    /// See `finish_dry_run`: simulate-only, the transaction always fails.
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// Instruction data for `request_ternary_op`.
+    pub fn instruction_data(&self) -> Vec<u8> {
+        crate::instruction::RequestTernaryOp {
+            op: self.op,
+            a_handle: self.a,
+            b_handle: self.b,
+            c_handle: self.c,
+            caller_nonce: self.caller_nonce,
+            dry_run: self.dry_run,
+        }
+        .data()
+    }
+
+    /// Handle the instruction returns under the config's `salt`.
+    pub fn result_handle(&self, program_id: &Pubkey, salt: &DomainSalt) -> Handle {
+        derive_ternary_handle(self.op, &self.a, &self.b, &self.c, program_id, salt)
+    }
+}
+
+impl From<TernaryRequest> for OpRequest {
+    fn from(request: TernaryRequest) -> Self {
+        OpRequest::Ternary {
+            op: request.op,
+            a: request.a,
+            b: request.b,
+            c: request.c,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// Event compaction:
+// - handle tables for events that repeat the same 32-byte handle several
+//   times: each distinct handle is listed once and operands refer to it by
+//   a u8 index into the table
+// - packed request events (`Fhe16PackedOpRequested`), which drop the
+//   caller and origin repeated across the requests of one instruction and
+//   name the op by its program-wide `OpCode`
+use anchor_lang::prelude::*;
+
+use crate::envelope::{join_entry, split_entry};
+use crate::errors::ExecutorError;
+use crate::events::*;
+use crate::request::OpRequest;
+use crate::types::{ClientTag, Handle};
+
+/// Largest number of distinct handles a compacted event may carry.
+pub const MAX_COMPACT_HANDLES: usize = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct CompactHandles {
+    pub table: Vec<Handle>,
+    pub indices: Vec<u8>,
+}
+
+/// Builds the handle table with a fixed-size scratch array and a linear
+/// search, so the on-chain cost is one allocation per output vector.
+pub fn compact_handles(handles: &[Handle]) -> Result<CompactHandles> {
+    let mut table = [[0u8; 32]; MAX_COMPACT_HANDLES];
+    let mut unique = 0usize;
+    let mut indices = Vec::with_capacity(handles.len());
+
+    for handle in handles {
+        let index = match table[..unique].iter().position(|h| h == handle) {
+            Some(index) => index,
+            None => {
+                require!(unique < MAX_COMPACT_HANDLES, ExecutorError::TooManyCompactHandles);
+                table[unique] = *handle;
+                unique += 1;
+                unique - 1
+            }
+        };
+        indices.push(index as u8);
+    }
+
+    Ok(CompactHandles {
+        table: table[..unique].to_vec(),
+        indices,
+    })
+}
+
+/// Inverse of [`compact_handles`]. Rejects indices outside the table, and
+/// the encoder rejects more distinct handles than the table holds:
+///
+/// ```
+/// # use host_programs::compact::*;
+/// # use host_programs::errors::ExecutorError;
+/// let handles = [[1; 32], [2; 32], [1; 32]];
+/// let compact = compact_handles(&handles).unwrap();
+/// assert_eq!(compact.indices, vec![0, 1, 0]);
+/// assert_eq!(expand_handles(&compact).unwrap(), handles.to_vec());
+///
+/// let corrupt = CompactHandles { table: compact.table, indices: vec![0, 2] };
+/// let err = expand_handles(&corrupt).unwrap_err();
+/// assert_eq!(err, ExecutorError::InvalidCompactIndex.into());
+///
+/// let distinct: Vec<[u8; 32]> = (0..=MAX_COMPACT_HANDLES as u8).map(|i| [i; 32]).collect();
+/// let err = compact_handles(&distinct).err().unwrap();
+/// assert_eq!(err, ExecutorError::TooManyCompactHandles.into());
+/// assert!(compact_handles(&distinct[..MAX_COMPACT_HANDLES]).is_ok());
+/// ```
+pub fn expand_handles(compact: &CompactHandles) -> Result<Vec<Handle>> {
+    compact
+        .indices
+        .iter()
+        .map(|&index| {
+            compact
+                .table
+                .get(index as usize)
+                .copied()
+                .ok_or_else(|| error!(ExecutorError::InvalidCompactIndex))
+        })
+        .collect()
+}
+
+/// Emits one `Fhe16PackedOpRequested` per `(request, result_handle)`; only
+/// the first carries the caller and origin program.
+pub fn emit_packed_requests(
+    requests: &[(OpRequest, Handle)],
+    caller: Pubkey,
+    origin_program: Pubkey,
+) {
+    for (i, (request, result_handle)) in requests.iter().enumerate() {
+        let (op_code, body) = split_entry(request);
+        let first = i == 0;
+        emit!(Fhe16PackedOpRequested {
+            op_code,
+            request: body,
+            result_handle: *result_handle,
+            caller: first.then_some(caller),
+            origin_program: first.then_some(origin_program),
+        });
+    }
+}
+
+/// Client side of the packed path: replaces every packed event with the
+/// per-arity event it stands for, inheriting an omitted caller and origin
+/// from the previous packed event. `None` if a packed event does not decode,
+/// has nothing to inherit from or describes an impossible request:
+///
+/// ```
+/// # use anchor_lang::prelude::Pubkey;
+/// # use host_programs::compact::unpack_events;
+/// # use host_programs::envelope::split_entry;
+/// # use host_programs::events::{Fhe16Event, Fhe16PackedOpRequested};
+/// # use host_programs::request::OpRequest;
+/// let concat = |half_width| {
+///     let concat = OpRequest::Concat { lo: [1; 32], hi: [2; 32], half_width };
+///     let (op_code, request) = split_entry(&concat);
+///     Fhe16Event::PackedOpRequested(Fhe16PackedOpRequested {
+///         op_code,
+///         request,
+///         result_handle: [3; 32],
+///         caller: Some(Pubkey::default()),
+///         origin_program: Some(Pubkey::default()),
+///     })
+/// };
+/// let events = unpack_events(vec![concat(8)]).unwrap();
+/// let [Fhe16Event::ConcatRequested(joined)] = &events[..] else {
+///     panic!("expected one concat event");
+/// };
+/// assert_eq!(joined.result_width, 16);
+/// // twice the half width does not fit the event's u8
+/// assert!(unpack_events(vec![concat(200)]).is_none());
+/// ```
+pub fn unpack_events(events: Vec<Fhe16Event>) -> Option<Vec<Fhe16Event>> {
+    let mut last: Option<(Pubkey, Pubkey)> = None;
+    events
+        .into_iter()
+        .map(|event| {
+            let Fhe16Event::PackedOpRequested(packed) = event else {
+                return Some(event);
+            };
+            let (caller, origin_program) = match (packed.caller, packed.origin_program) {
+                (Some(caller), Some(origin_program)) => (caller, origin_program),
+                (None, None) => last?,
+                _ => return None,
+            };
+            last = Some((caller, origin_program));
+            let request = join_entry(packed.op_code, &packed.request).ok()?;
+            logical_event(request, caller, packed.result_handle, origin_program)
+        })
+        .collect()
+}
+
+fn logical_event(
+    request: OpRequest,
+    caller: Pubkey,
+    result_handle: Handle,
+    origin_program: Pubkey,
+) -> Option<Fhe16Event> {
+    let event = match request {
+        OpRequest::Register { handle, client_tag } => {
+            Fhe16Event::InputHandleRegistered(InputHandleRegistered {
+                caller,
+                handle,
+                client_tag,
+                is_labeled: ClientTag::from(client_tag).is_labeled(),
+                origin_program,
+            })
+        }
+        OpRequest::Unary { op, input } => Fhe16Event::UnaryOpRequested(Fhe16UnaryOpRequested {
+            caller,
+            op,
+            input_handle: input,
+            result_handle,
+            origin_program,
+        }),
+        OpRequest::Binary { op, lhs, rhs } => {
+            Fhe16Event::BinaryOpRequested(Fhe16BinaryOpRequested {
+                caller,
+                op,
+                lhs_handle: lhs,
+                rhs_handle: rhs,
+                result_handle,
+                origin_program,
+            })
+        }
+        OpRequest::Ternary { op, a, b, c } => {
+            Fhe16Event::TernaryOpRequested(Fhe16TernaryOpRequested {
+                caller,
+                op,
+                a_handle: a,
+                b_handle: b,
+                c_handle: c,
+                result_handle,
+                origin_program,
+            })
+        }
+        OpRequest::Pad { input, target_width } => Fhe16Event::PadRequested(Fhe16PadRequested {
+            caller,
+            input_handle: input,
+            target_width,
+            result_handle,
+            origin_program,
+        }),
+        OpRequest::Truncate { input, target_width } => {
+            Fhe16Event::TruncateRequested(Fhe16TruncateRequested {
+                caller,
+                input_handle: input,
+                target_width,
+                result_handle,
+                origin_program,
+            })
+        }
+        OpRequest::Concat { lo, hi, half_width } => {
+            Fhe16Event::ConcatRequested(Fhe16ConcatRequested {
+                caller,
+                lo_handle: lo,
+                hi_handle: hi,
+                result_width: half_width.checked_mul(2)?,
+                result_handle,
+                origin_program,
+            })
+        }
+    };
+    Some(event)
+}
@@ -0,0 +1,231 @@
+// Type-state request builders for dapp programs calling the host through
+// CPI. Each operand slot is `Missing` until it is set and `invoke` only
+// exists once every slot holds a `Handle`, so a forgotten, repeated or
+// swapped-in-the-wrong-slot operand is a compile error rather than a wrong
+// handle. The markers are zero-sized; a complete builder is just the
+// arguments it forwards.
+use anchor_lang::prelude::*;
+
+use crate::cpi::accounts::{RequestBinaryOp, RequestTernaryOp};
+use crate::profile::measure;
+use crate::types::{Fhe16BinaryOp, Fhe16TernaryOp, Handle};
+
+/// Operand slot that has not been set yet.
+#[derive(Clone, Copy)]
+pub struct Missing;
+
+/// `request_binary_op` through CPI.
+///
+/// ```
+/// # use anchor_lang::prelude::*;
+/// # use host_programs::cpi::accounts::RequestBinaryOp;
+/// # use host_programs::cpi_builder::BinaryCpi;
+/// # use host_programs::types::{Fhe16BinaryOp, Handle};
+/// fn add<'info>(
+///     ctx: CpiContext<'_, '_, '_, 'info, RequestBinaryOp<'info>>,
+///     tally: Handle,
+///     ballot: Handle,
+/// ) -> Result<Handle> {
+///     BinaryCpi::new(Fhe16BinaryOp::Add).lhs(tally).rhs(ballot).invoke(ctx)
+/// }
+/// ```
+///
+/// Invoking with an operand missing does not compile:
+///
+/// ```compile_fail
+/// # use anchor_lang::prelude::*;
+/// # use host_programs::cpi::accounts::RequestBinaryOp;
+/// # use host_programs::cpi_builder::BinaryCpi;
+/// # use host_programs::types::{Fhe16BinaryOp, Handle};
+/// fn add<'info>(
+///     ctx: CpiContext<'_, '_, '_, 'info, RequestBinaryOp<'info>>,
+///     tally: Handle,
+/// ) -> Result<Handle> {
+///     BinaryCpi::new(Fhe16BinaryOp::Add).lhs(tally).invoke(ctx)
+/// }
+/// ```
+///
+/// Neither does setting the same operand twice:
+///
+/// ```compile_fail
+/// # use host_programs::cpi_builder::BinaryCpi;
+/// # use host_programs::types::Fhe16BinaryOp;
+/// BinaryCpi::new(Fhe16BinaryOp::Sub).lhs([1; 32]).lhs([2; 32]);
+/// ```
+///
+/// Nor building a binary request from a ternary op:
+///
+/// ```compile_fail
+/// # use host_programs::cpi_builder::BinaryCpi;
+/// # use host_programs::types::Fhe16TernaryOp;
+/// BinaryCpi::new(Fhe16TernaryOp::Select);
+/// ```
+#[derive(Clone, Copy)]
+pub struct BinaryCpi<L = Missing, R = Missing> {
+    op: Fhe16BinaryOp,
+    lhs: L,
+    rhs: R,
+    caller_nonce: Option<u64>,
+}
+
+impl BinaryCpi {
+    pub fn new(op: Fhe16BinaryOp) -> Self {
+        Self {
+            op,
+            lhs: Missing,
+            rhs: Missing,
+            caller_nonce: None,
+        }
+    }
+}
+
+impl<L, R> BinaryCpi<L, R> {
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.caller_nonce = Some(nonce);
+        self
+    }
+}
+
+impl<R> BinaryCpi<Missing, R> {
+    pub fn lhs(self, lhs: Handle) -> BinaryCpi<Handle, R> {
+        BinaryCpi {
+            op: self.op,
+            lhs,
+            rhs: self.rhs,
+            caller_nonce: self.caller_nonce,
+        }
+    }
+}
+
+impl<L> BinaryCpi<L, Missing> {
+    pub fn rhs(self, rhs: Handle) -> BinaryCpi<L, Handle> {
+        BinaryCpi {
+            op: self.op,
+            lhs: self.lhs,
+            rhs,
+            caller_nonce: self.caller_nonce,
+        }
+    }
+}
+
+impl BinaryCpi<Handle, Handle> {
+    /// Returns the result handle the host derived. Under `cu-profile` the
+    /// whole CPI, as seen from the calling program, is reported as `CuUsed`.
+    pub fn invoke<'info>(
+        self,
+        ctx: CpiContext<'_, '_, '_, 'info, RequestBinaryOp<'info>>,
+    ) -> Result<Handle> {
+        measure("cpi:request_binary_op", || {
+            let result = crate::cpi::request_binary_op(
+                ctx,
+                self.op,
+                self.lhs,
+                self.rhs,
+                self.caller_nonce,
+                false,
+            )?;
+            Ok(result.get())
+        })
+    }
+}
+
+/// `request_ternary_op` through CPI; `invoke` needs `a`, `b` and `c`.
+///
+/// ```compile_fail
+/// # use anchor_lang::prelude::*;
+/// # use host_programs::cpi::accounts::RequestTernaryOp;
+/// # use host_programs::cpi_builder::TernaryCpi;
+/// # use host_programs::types::{Fhe16TernaryOp, Handle};
+/// fn select<'info>(
+///     ctx: CpiContext<'_, '_, '_, 'info, RequestTernaryOp<'info>>,
+///     cond: Handle,
+///     if_true: Handle,
+/// ) -> Result<Handle> {
+///     TernaryCpi::new(Fhe16TernaryOp::Select).a(cond).b(if_true).invoke(ctx)
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct TernaryCpi<A = Missing, B = Missing, C = Missing> {
+    op: Fhe16TernaryOp,
+    a: A,
+    b: B,
+    c: C,
+    caller_nonce: Option<u64>,
+}
+
+impl TernaryCpi {
+    pub fn new(op: Fhe16TernaryOp) -> Self {
+        Self {
+            op,
+            a: Missing,
+            b: Missing,
+            c: Missing,
+            caller_nonce: None,
+        }
+    }
+}
+
+impl<A, B, C> TernaryCpi<A, B, C> {
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.caller_nonce = Some(nonce);
+        self
+    }
+}
+
+impl<B, C> TernaryCpi<Missing, B, C> {
+    pub fn a(self, a: Handle) -> TernaryCpi<Handle, B, C> {
+        TernaryCpi {
+            op: self.op,
+            a,
+            b: self.b,
+            c: self.c,
+            caller_nonce: self.caller_nonce,
+        }
+    }
+}
+
+impl<A, C> TernaryCpi<A, Missing, C> {
+    pub fn b(self, b: Handle) -> TernaryCpi<A, Handle, C> {
+        TernaryCpi {
+            op: self.op,
+            a: self.a,
+            b,
+            c: self.c,
+            caller_nonce: self.caller_nonce,
+        }
+    }
+}
+
+impl<A, B> TernaryCpi<A, B, Missing> {
+    pub fn c(self, c: Handle) -> TernaryCpi<A, B, Handle> {
+        TernaryCpi {
+            op: self.op,
+            a: self.a,
+            b: self.b,
+            c,
+            caller_nonce: self.caller_nonce,
+        }
+    }
+}
+
+impl TernaryCpi<Handle, Handle, Handle> {
+    /// Returns the result handle the host derived. Under `cu-profile` the
+    /// whole CPI, as seen from the calling program, is reported as `CuUsed`.
+    pub fn invoke<'info>(
+        self,
+        ctx: CpiContext<'_, '_, '_, 'info, RequestTernaryOp<'info>>,
+    ) -> Result<Handle> {
+        measure("cpi:request_ternary_op", || {
+            let result = crate::cpi::request_ternary_op(
+                ctx,
+                self.op,
+                self.a,
+                self.b,
+                self.c,
+                self.caller_nonce,
+                false,
+            )?;
+            Ok(result.get())
+        })
+    }
+}
@@ -0,0 +1,255 @@
+// Off-chain reconstruction of the computation graph from the host's event
+// stream, for explorers and executors.
+use std::collections::HashMap;
+
+use crate::envelope::{decode_envelope, join_entry};
+use crate::events::Fhe16Event;
+use crate::request::OpRequest;
+use crate::types::{DotWeights, Handle};
+
+/// How a node's handle came into existence.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DagNodeKind {
+    /// Registered or imported input.
+    Input,
+    /// Result of an op request.
+    Op,
+    /// Used as an operand but never produced or registered in the events
+    /// given. Trivial constants (`constant_handle`) always end up here.
+    Dangling,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DagNode {
+    pub handle: Handle,
+    pub kind: DagNodeKind,
+}
+
+/// `nodes[from]` is operand number `position` of the request producing
+/// `nodes[to]`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DagEdge {
+    pub from: usize,
+    pub to: usize,
+    pub position: u8,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Dag {
+    pub nodes: Vec<DagNode>,
+    pub edges: Vec<DagEdge>,
+    index: HashMap<Handle, usize>,
+}
+
+impl Dag {
+    pub fn node_index(&self, handle: &Handle) -> Option<usize> {
+        self.index.get(handle).copied()
+    }
+
+    /// Operands nothing in the event stream produced or registered.
+    pub fn dangling(&self) -> impl Iterator<Item = &DagNode> {
+        self.nodes
+            .iter()
+            .filter(|node| node.kind == DagNodeKind::Dangling)
+    }
+
+    fn node(&mut self, handle: Handle) -> usize {
+        *self.index.entry(handle).or_insert_with(|| {
+            self.nodes.push(DagNode {
+                handle,
+                kind: DagNodeKind::Dangling,
+            });
+            self.nodes.len() - 1
+        })
+    }
+
+    fn add_input(&mut self, handle: Handle) {
+        let node = self.node(handle);
+        if self.nodes[node].kind == DagNodeKind::Dangling {
+            self.nodes[node].kind = DagNodeKind::Input;
+        }
+    }
+
+    /// A repeated request for an already produced handle adds no edges.
+    fn add_op(&mut self, operands: &[Handle], result: Handle) {
+        let to = self.node(result);
+        if self.nodes[to].kind == DagNodeKind::Op {
+            return;
+        }
+        self.nodes[to].kind = DagNodeKind::Op;
+        for (position, operand) in operands.iter().enumerate() {
+            let from = self.node(*operand);
+            self.edges.push(DagEdge {
+                from,
+                to,
+                position: position as u8,
+            });
+        }
+    }
+
+    fn add_request(&mut self, request: &OpRequest, result: Handle) {
+        match request {
+            OpRequest::Register { handle, .. } => self.add_input(*handle),
+            _ => self.add_op(&request.operands(), result),
+        }
+    }
+}
+
+/// Builds the DAG described by chronologically ordered `events`, with an
+/// edge from every operand to the result it feeds. A vector op adds one
+/// result per element pair, a dot product takes its encrypted weights then
+/// its values as operands, and a packed event stands for the request it
+/// encodes. Events that do not introduce handles are ignored, as are
+/// envelopes and packed events whose payload does not decode.
+///
+/// ```
+/// use anchor_lang::prelude::Pubkey;
+/// use host_programs::dag::{build_dag, DagEdge, DagNodeKind};
+/// use host_programs::envelope::split_entry;
+/// use host_programs::events::*;
+/// use host_programs::request::OpRequest;
+/// use host_programs::types::{DotWeights, Fhe16BinaryOp, Fhe16UnaryOp, Fhe16VectorOp, Handle};
+///
+/// let who = Pubkey::default();
+/// let input = |handle: Handle| {
+///     Fhe16Event::InputHandleRegistered(InputHandleRegistered {
+///         caller: who,
+///         handle,
+///         client_tag: [0; 32],
+///         is_labeled: false,
+///         origin_program: who,
+///     })
+/// };
+/// let not = |input_handle, result_handle| {
+///     Fhe16Event::UnaryOpRequested(Fhe16UnaryOpRequested {
+///         caller: who,
+///         op: Fhe16UnaryOp::Not,
+///         input_handle,
+///         result_handle,
+///         origin_program: who,
+///     })
+/// };
+/// let add = |lhs_handle, rhs_handle, result_handle| {
+///     Fhe16Event::BinaryOpRequested(Fhe16BinaryOpRequested {
+///         caller: who,
+///         op: Fhe16BinaryOp::Add,
+///         lhs_handle,
+///         rhs_handle,
+///         result_handle,
+///         origin_program: who,
+///     })
+/// };
+/// let (a, b, c, d) = ([1; 32], [2; 32], [3; 32], [4; 32]);
+///
+/// // linear chain a -> b -> c
+/// let chain = build_dag(&[input(a), not(a, b), not(b, c)]);
+/// assert_eq!(chain.nodes.len(), 3);
+/// let at = |handle: &Handle| chain.node_index(handle).unwrap();
+/// let edge = |from, to| DagEdge { from: at(from), to: at(to), position: 0 };
+/// assert_eq!(chain.edges, [edge(&a, &b), edge(&b, &c)]);
+/// assert_eq!(chain.dangling().count(), 0);
+///
+/// // diamond: b and c share a, d joins them
+/// let diamond = build_dag(&[input(a), not(a, b), not(a, c), add(b, c, d)]);
+/// let into_d = diamond.edges.iter().filter(|e| e.to == diamond.node_index(&d).unwrap());
+/// assert_eq!(into_d.count(), 2);
+/// let from_a = diamond.edges.iter().filter(|e| e.from == diamond.node_index(&a).unwrap());
+/// assert_eq!(from_a.count(), 2);
+///
+/// // an operand nothing produced or registered is dangling
+/// let broken = build_dag(&[input(a), add(a, b, c)]);
+/// let dangling: Vec<_> = broken.dangling().map(|node| node.handle).collect();
+/// assert_eq!(dangling, [b]);
+/// assert_eq!(broken.nodes[broken.node_index(&c).unwrap()].kind, DagNodeKind::Op);
+///
+/// // element-wise XOR of [a, b] and [c, d], then a scalar dot product of the results
+/// let (e, f, g) = ([5; 32], [6; 32], [7; 32]);
+/// let vector = Fhe16Event::VectorOpRequested(Fhe16VectorOpRequested {
+///     caller: who,
+///     op: Fhe16VectorOp::Xor,
+///     len: 2,
+///     lhs: vec![a, b],
+///     rhs: vec![c, d],
+///     results: vec![e, f],
+///     origin_program: who,
+/// });
+/// let dot = Fhe16Event::DotProductRequested(Fhe16DotProductRequested {
+///     caller: who,
+///     weights: DotWeights::Scalar(vec![2, 3]),
+///     values: vec![e, f],
+///     result_handle: g,
+///     origin_program: who,
+/// });
+/// let risk = build_dag(&[input(a), input(b), input(c), input(d), vector, dot]);
+/// let into = |handle: Handle| {
+///     let to = risk.node_index(&handle).unwrap();
+///     risk.edges.iter().filter(|edge| edge.to == to).count()
+/// };
+/// assert_eq!((into(e), into(f), into(g)), (2, 2, 2));
+/// assert_eq!(risk.dangling().count(), 0);
+///
+/// // a packed event counts as the request it encodes
+/// let add = OpRequest::Binary { op: Fhe16BinaryOp::Add, lhs: a, rhs: b };
+/// let (op_code, request) = split_entry(&add);
+/// let packed = Fhe16Event::PackedOpRequested(Fhe16PackedOpRequested {
+///     op_code,
+///     request,
+///     result_handle: c,
+///     caller: Some(who),
+///     origin_program: Some(who),
+/// });
+/// let unpacked = build_dag(&[input(a), input(b), packed]);
+/// assert_eq!(unpacked.edges.len(), 2);
+/// assert_eq!(unpacked.nodes[unpacked.node_index(&c).unwrap()].kind, DagNodeKind::Op);
+/// ```
+pub fn build_dag(events: &[Fhe16Event]) -> Dag {
+    let mut dag = Dag::default();
+    for event in events {
+        match event {
+            Fhe16Event::InputHandleRegistered(e) => dag.add_input(e.handle),
+            Fhe16Event::HandleImported(e) => dag.add_input(e.handle),
+            Fhe16Event::UnaryOpRequested(e) => dag.add_op(&[e.input_handle], e.result_handle),
+            Fhe16Event::BinaryOpRequested(e) => {
+                dag.add_op(&[e.lhs_handle, e.rhs_handle], e.result_handle)
+            }
+            Fhe16Event::BinaryOpCategorized(e) => {
+                dag.add_op(&[e.lhs_handle, e.rhs_handle], e.result_handle)
+            }
+            Fhe16Event::TernaryOpRequested(e) => {
+                dag.add_op(&[e.a_handle, e.b_handle, e.c_handle], e.result_handle)
+            }
+            Fhe16Event::PadRequested(e) => dag.add_op(&[e.input_handle], e.result_handle),
+            Fhe16Event::TruncateRequested(e) => dag.add_op(&[e.input_handle], e.result_handle),
+            Fhe16Event::ConcatRequested(e) => {
+                dag.add_op(&[e.lo_handle, e.hi_handle], e.result_handle)
+            }
+            Fhe16Event::VectorOpRequested(e) => {
+                for ((lhs, rhs), result) in e.lhs.iter().zip(&e.rhs).zip(&e.results) {
+                    dag.add_op(&[*lhs, *rhs], *result);
+                }
+            }
+            Fhe16Event::DotProductRequested(e) => {
+                let mut operands = match &e.weights {
+                    DotWeights::Encrypted(weights) => weights.clone(),
+                    DotWeights::Scalar(_) => Vec::new(),
+                };
+                operands.extend_from_slice(&e.values);
+                dag.add_op(&operands, e.result_handle);
+            }
+            Fhe16Event::PackedOpRequested(e) => {
+                if let Ok(request) = join_entry(e.op_code, &e.request) {
+                    dag.add_request(&request, e.result_handle);
+                }
+            }
+            Fhe16Event::EnvelopeRequested(e) => {
+                if let Ok(requests) = decode_envelope(&e.payload) {
+                    for (request, result) in requests.iter().zip(&e.result_handles) {
+                        dag.add_request(request, *result);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    dag
+}
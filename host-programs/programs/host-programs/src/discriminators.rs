@@ -0,0 +1,157 @@
+// 8-byte Anchor event discriminators, exported so RPC log subscribers can
+// filter `Program data:` lines by event type without depending on the IDL.
+use anchor_lang::Discriminator;
+
+use crate::events::*;
+
+const fn to_array(discriminator: &[u8]) -> [u8; 8] {
+    let mut out = [0u8; 8];
+    let mut i = 0;
+    while i < 8 {
+        out[i] = discriminator[i];
+        i += 1;
+    }
+    out
+}
+
+pub const INPUT_HANDLE_REGISTERED: [u8; 8] = to_array(InputHandleRegistered::DISCRIMINATOR);
+pub const FHE16_UNARY_OP_REQUESTED: [u8; 8] = to_array(Fhe16UnaryOpRequested::DISCRIMINATOR);
+pub const FHE16_BINARY_OP_REQUESTED: [u8; 8] = to_array(Fhe16BinaryOpRequested::DISCRIMINATOR);
+pub const FHE16_TERNARY_OP_REQUESTED: [u8; 8] = to_array(Fhe16TernaryOpRequested::DISCRIMINATOR);
+pub const FHE16_PAD_REQUESTED: [u8; 8] = to_array(Fhe16PadRequested::DISCRIMINATOR);
+pub const FHE16_ENVELOPE_REQUESTED: [u8; 8] = to_array(Fhe16EnvelopeRequested::DISCRIMINATOR);
+pub const HANDLE_EXPORTED: [u8; 8] = to_array(HandleExported::DISCRIMINATOR);
+pub const HANDLE_IMPORTED: [u8; 8] = to_array(HandleImported::DISCRIMINATOR);
+pub const ADMIN_OVERRIDE_USED: [u8; 8] = to_array(AdminOverrideUsed::DISCRIMINATOR);
+pub const UNIMPLEMENTED_OP_REQUESTED: [u8; 8] = to_array(UnimplementedOpRequested::DISCRIMINATOR);
+pub const DOMAIN_SALT_ROTATED: [u8; 8] = to_array(DomainSaltRotated::DISCRIMINATOR);
+pub const LAMPORTS_MOVED: [u8; 8] = to_array(LamportsMoved::DISCRIMINATOR);
+pub const CONFIG_UPDATED: [u8; 8] = to_array(ConfigUpdated::DISCRIMINATOR);
+pub const SUPPRESSED_DUPLICATE: [u8; 8] = to_array(SuppressedDuplicate::DISCRIMINATOR);
+pub const FHE16_TRUNCATE_REQUESTED: [u8; 8] = to_array(Fhe16TruncateRequested::DISCRIMINATOR);
+pub const CONDITION_SOURCE_CONFIRMED: [u8; 8] = to_array(ConditionSourceConfirmed::DISCRIMINATOR);
+pub const DERIVATION_ANOMALY: [u8; 8] = to_array(DerivationAnomaly::DISCRIMINATOR);
+pub const SEQUENCE_RESERVED: [u8; 8] = to_array(SequenceReserved::DISCRIMINATOR);
+pub const REQUEST_META: [u8; 8] = to_array(RequestMeta::DISCRIMINATOR);
+pub const FHE16_PACKED_OP_REQUESTED: [u8; 8] = to_array(Fhe16PackedOpRequested::DISCRIMINATOR);
+pub const FHE16_BINARY_OP_CATEGORIZED: [u8; 8] = to_array(Fhe16BinaryOpCategorized::DISCRIMINATOR);
+pub const HANDLE_FROZEN: [u8; 8] = to_array(HandleFrozen::DISCRIMINATOR);
+pub const HANDLE_UNFROZEN: [u8; 8] = to_array(HandleUnfrozen::DISCRIMINATOR);
+pub const FHE16_CONCAT_REQUESTED: [u8; 8] = to_array(Fhe16ConcatRequested::DISCRIMINATOR);
+pub const STRICTNESS_CHANGED: [u8; 8] = to_array(StrictnessChanged::DISCRIMINATOR);
+pub const FHE16_VECTOR_OP_REQUESTED: [u8; 8] = to_array(Fhe16VectorOpRequested::DISCRIMINATOR);
+pub const FHE16_DOT_PRODUCT_REQUESTED: [u8; 8] = to_array(Fhe16DotProductRequested::DISCRIMINATOR);
+pub const CU_USED: [u8; 8] = to_array(CuUsed::DISCRIMINATOR);
+pub const CONSTANTS_BOOTSTRAPPED: [u8; 8] = to_array(ConstantsBootstrapped::DISCRIMINATOR);
+
+/// Every event the host program emits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EventKind {
+    InputHandleRegistered,
+    Fhe16UnaryOpRequested,
+    Fhe16BinaryOpRequested,
+    Fhe16TernaryOpRequested,
+    Fhe16PadRequested,
+    Fhe16EnvelopeRequested,
+    HandleExported,
+    HandleImported,
+    AdminOverrideUsed,
+    UnimplementedOpRequested,
+    DomainSaltRotated,
+    LamportsMoved,
+    ConfigUpdated,
+    SuppressedDuplicate,
+    Fhe16TruncateRequested,
+    ConditionSourceConfirmed,
+    DerivationAnomaly,
+    SequenceReserved,
+    RequestMeta,
+    Fhe16PackedOpRequested,
+    Fhe16BinaryOpCategorized,
+    HandleFrozen,
+    HandleUnfrozen,
+    Fhe16ConcatRequested,
+    StrictnessChanged,
+    Fhe16VectorOpRequested,
+    Fhe16DotProductRequested,
+    CuUsed,
+    ConstantsBootstrapped,
+}
+
+/// Identifies the event kind from the leading 8 bytes of event data.
+pub fn match_discriminator(data: &[u8]) -> Option<EventKind> {
+    let prefix: [u8; 8] = data.get(..8)?.try_into().ok()?;
+    match prefix {
+        INPUT_HANDLE_REGISTERED => Some(EventKind::InputHandleRegistered),
+        FHE16_UNARY_OP_REQUESTED => Some(EventKind::Fhe16UnaryOpRequested),
+        FHE16_BINARY_OP_REQUESTED => Some(EventKind::Fhe16BinaryOpRequested),
+        FHE16_TERNARY_OP_REQUESTED => Some(EventKind::Fhe16TernaryOpRequested),
+        FHE16_PAD_REQUESTED => Some(EventKind::Fhe16PadRequested),
+        FHE16_ENVELOPE_REQUESTED => Some(EventKind::Fhe16EnvelopeRequested),
+        HANDLE_EXPORTED => Some(EventKind::HandleExported),
+        HANDLE_IMPORTED => Some(EventKind::HandleImported),
+        ADMIN_OVERRIDE_USED => Some(EventKind::AdminOverrideUsed),
+        UNIMPLEMENTED_OP_REQUESTED => Some(EventKind::UnimplementedOpRequested),
+        DOMAIN_SALT_ROTATED => Some(EventKind::DomainSaltRotated),
+        LAMPORTS_MOVED => Some(EventKind::LamportsMoved),
+        CONFIG_UPDATED => Some(EventKind::ConfigUpdated),
+        SUPPRESSED_DUPLICATE => Some(EventKind::SuppressedDuplicate),
+        FHE16_TRUNCATE_REQUESTED => Some(EventKind::Fhe16TruncateRequested),
+        CONDITION_SOURCE_CONFIRMED => Some(EventKind::ConditionSourceConfirmed),
+        DERIVATION_ANOMALY => Some(EventKind::DerivationAnomaly),
+        SEQUENCE_RESERVED => Some(EventKind::SequenceReserved),
+        REQUEST_META => Some(EventKind::RequestMeta),
+        FHE16_PACKED_OP_REQUESTED => Some(EventKind::Fhe16PackedOpRequested),
+        FHE16_BINARY_OP_CATEGORIZED => Some(EventKind::Fhe16BinaryOpCategorized),
+        HANDLE_FROZEN => Some(EventKind::HandleFrozen),
+        HANDLE_UNFROZEN => Some(EventKind::HandleUnfrozen),
+        FHE16_CONCAT_REQUESTED => Some(EventKind::Fhe16ConcatRequested),
+        STRICTNESS_CHANGED => Some(EventKind::StrictnessChanged),
+        FHE16_VECTOR_OP_REQUESTED => Some(EventKind::Fhe16VectorOpRequested),
+        FHE16_DOT_PRODUCT_REQUESTED => Some(EventKind::Fhe16DotProductRequested),
+        CU_USED => Some(EventKind::CuUsed),
+        CONSTANTS_BOOTSTRAPPED => Some(EventKind::ConstantsBootstrapped),
+        _ => None,
+    }
+}
+
+/// Request families, each of which is reported through its own event.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OpCategory {
+    Unary,
+    Binary,
+    Ternary,
+    Pad,
+    Truncate,
+    Concat,
+    Vector,
+    DotProduct,
+}
+
+/// Discriminator of the event emitted for requests in `category`.
+///
+/// Each category has its own discriminator, so a subscriber filtering on
+/// one never receives another category's events or non-op events:
+///
+/// ```
+/// use host_programs::discriminators::*;
+///
+/// let binary = op_event_discriminator(OpCategory::Binary);
+/// assert_eq!(binary, FHE16_BINARY_OP_REQUESTED);
+/// assert_ne!(binary, op_event_discriminator(OpCategory::Unary));
+/// assert_ne!(binary, op_event_discriminator(OpCategory::Vector));
+/// assert_ne!(binary, REQUEST_META);
+/// assert_ne!(binary, FHE16_PACKED_OP_REQUESTED);
+/// ```
+pub const fn op_event_discriminator(category: OpCategory) -> [u8; 8] {
+    match category {
+        OpCategory::Unary => FHE16_UNARY_OP_REQUESTED,
+        OpCategory::Binary => FHE16_BINARY_OP_REQUESTED,
+        OpCategory::Ternary => FHE16_TERNARY_OP_REQUESTED,
+        OpCategory::Pad => FHE16_PAD_REQUESTED,
+        OpCategory::Truncate => FHE16_TRUNCATE_REQUESTED,
+        OpCategory::Concat => FHE16_CONCAT_REQUESTED,
+        OpCategory::Vector => FHE16_VECTOR_OP_REQUESTED,
+        OpCategory::DotProduct => FHE16_DOT_PRODUCT_REQUESTED,
+    }
+}
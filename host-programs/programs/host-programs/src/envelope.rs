@@ -0,0 +1,271 @@
+// Compact binary envelope packing many requests into one instruction.
+//
+// Layout (v1):
+//   [version: u8][count: u8] then `count` entries of
+//   [kind: u8][body]
+//     kind 0 register: handle(32) client_tag(32)
+//     kind 1 unary:    op(1) input(32)
+//     kind 2 binary:   op(1) lhs(32) rhs(32)
+//     kind 3 ternary:  op(1) a(32) b(32) c(32)
+//     kind 4 pad:      target_width(1) input(32)
+//     kind 5 truncate: target_width(1) input(32)
+//     kind 6 concat:   half_width(1) lo(32) hi(32)
+// The unary, binary and ternary kinds are the `OpFamily` of the entry's
+// `OpCode` and the op byte is its index within the family, which is also
+// the Borsh variant index hashed into the handle. Only codes the registry
+// assigns decode.
+use anchor_lang::prelude::*;
+
+use crate::errors::ExecutorError;
+use crate::limits::MAX_BATCH_OPS;
+use crate::opcode::{OpCode, OpFamily, WIDTH_CONCAT, WIDTH_PAD, WIDTH_TRUNCATE};
+use crate::request::OpRequest;
+use crate::types::Handle;
+
+pub const ENVELOPE_VERSION: u8 = 1;
+pub const MAX_ENVELOPE_REQUESTS: usize = MAX_BATCH_OPS;
+
+const KIND_REGISTER: u8 = 0;
+const KIND_UNARY: u8 = 1;
+const KIND_BINARY: u8 = 2;
+const KIND_TERNARY: u8 = 3;
+const KIND_PAD: u8 = 4;
+const KIND_TRUNCATE: u8 = 5;
+const KIND_CONCAT: u8 = 6;
+
+/// Byte offset into the payload at which decoding failed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EnvelopeError {
+    pub offset: usize,
+}
+
+/// Encodes 1 to `MAX_ENVELOPE_REQUESTS` requests; any other count fails
+/// with `MalformedEnvelope`, as it would on decode.
+///
+/// ```
+/// # use host_programs::envelope::{encode_envelope, MAX_ENVELOPE_REQUESTS};
+/// # use host_programs::request::OpRequest;
+/// # use host_programs::types::Fhe16UnaryOp;
+/// let not = OpRequest::Unary { op: Fhe16UnaryOp::Not, input: [1; 32] };
+/// assert!(encode_envelope(&[not; MAX_ENVELOPE_REQUESTS]).is_ok());
+/// assert!(encode_envelope(&[not; MAX_ENVELOPE_REQUESTS + 1]).is_err());
+/// assert!(encode_envelope(&[not; 256]).is_err());
+/// assert!(encode_envelope(&[]).is_err());
+/// ```
+pub fn encode_envelope(requests: &[OpRequest]) -> Result<Vec<u8>> {
+    let count = u8::try_from(requests.len())
+        .ok()
+        .filter(|&count| count > 0 && usize::from(count) <= MAX_ENVELOPE_REQUESTS)
+        .ok_or(ExecutorError::MalformedEnvelope)?;
+    let mut out = vec![ENVELOPE_VERSION, count];
+    for request in requests {
+        encode_entry(request, &mut out);
+    }
+    Ok(out)
+}
+
+/// Appends one `[kind][body]` entry, the per-request part of the layout.
+pub fn encode_entry(request: &OpRequest, out: &mut Vec<u8>) {
+    match *request {
+        OpRequest::Register { handle, client_tag } => {
+            out.push(KIND_REGISTER);
+            out.extend_from_slice(&handle);
+            out.extend_from_slice(&client_tag);
+        }
+        OpRequest::Unary { op, input } => {
+            out.extend_from_slice(&[KIND_UNARY, OpCode::from(op).index()]);
+            out.extend_from_slice(&input);
+        }
+        OpRequest::Binary { op, lhs, rhs } => {
+            out.extend_from_slice(&[KIND_BINARY, OpCode::from(op).index()]);
+            out.extend_from_slice(&lhs);
+            out.extend_from_slice(&rhs);
+        }
+        OpRequest::Ternary { op, a, b, c } => {
+            out.extend_from_slice(&[KIND_TERNARY, OpCode::from(op).index()]);
+            out.extend_from_slice(&a);
+            out.extend_from_slice(&b);
+            out.extend_from_slice(&c);
+        }
+        OpRequest::Pad { input, target_width } => {
+            out.extend_from_slice(&[KIND_PAD, target_width]);
+            out.extend_from_slice(&input);
+        }
+        OpRequest::Truncate { input, target_width } => {
+            out.extend_from_slice(&[KIND_TRUNCATE, target_width]);
+            out.extend_from_slice(&input);
+        }
+        OpRequest::Concat { lo, hi, half_width } => {
+            out.extend_from_slice(&[KIND_CONCAT, half_width]);
+            out.extend_from_slice(&lo);
+            out.extend_from_slice(&hi);
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> core::result::Result<u8, EnvelopeError> {
+        let byte = *self
+            .data
+            .get(self.offset)
+            .ok_or(EnvelopeError { offset: self.offset })?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn handle(&mut self) -> core::result::Result<Handle, EnvelopeError> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + 32)
+            .ok_or(EnvelopeError { offset: self.offset })?;
+        self.offset += 32;
+        Ok(bytes.try_into().expect("slice is 32 bytes"))
+    }
+
+    fn op<T: TryFrom<OpCode>>(
+        &mut self,
+        family: OpFamily,
+    ) -> core::result::Result<T, EnvelopeError> {
+        let offset = self.offset;
+        let index = self.byte()?;
+        T::try_from(OpCode::new(family, index)).map_err(|_| EnvelopeError { offset })
+    }
+
+    fn entry(&mut self) -> core::result::Result<OpRequest, EnvelopeError> {
+        let kind_offset = self.offset;
+        let request = match self.byte()? {
+            KIND_REGISTER => OpRequest::Register {
+                handle: self.handle()?,
+                client_tag: self.handle()?,
+            },
+            KIND_UNARY => OpRequest::Unary {
+                op: self.op(OpFamily::Unary)?,
+                input: self.handle()?,
+            },
+            KIND_BINARY => OpRequest::Binary {
+                op: self.op(OpFamily::Binary)?,
+                lhs: self.handle()?,
+                rhs: self.handle()?,
+            },
+            KIND_TERNARY => OpRequest::Ternary {
+                op: self.op(OpFamily::Ternary)?,
+                a: self.handle()?,
+                b: self.handle()?,
+                c: self.handle()?,
+            },
+            KIND_PAD => OpRequest::Pad {
+                target_width: self.byte()?,
+                input: self.handle()?,
+            },
+            KIND_TRUNCATE => OpRequest::Truncate {
+                target_width: self.byte()?,
+                input: self.handle()?,
+            },
+            KIND_CONCAT => OpRequest::Concat {
+                half_width: self.byte()?,
+                lo: self.handle()?,
+                hi: self.handle()?,
+            },
+            _ => return Err(EnvelopeError { offset: kind_offset }),
+        };
+        Ok(request)
+    }
+
+    fn finish(&self) -> core::result::Result<(), EnvelopeError> {
+        if self.offset != self.data.len() {
+            return Err(EnvelopeError { offset: self.offset });
+        }
+        Ok(())
+    }
+}
+
+pub fn decode_envelope(payload: &[u8]) -> core::result::Result<Vec<OpRequest>, EnvelopeError> {
+    let mut reader = Reader { data: payload, offset: 0 };
+
+    if reader.byte()? != ENVELOPE_VERSION {
+        return Err(EnvelopeError { offset: 0 });
+    }
+    let count = reader.byte()? as usize;
+    if count == 0 || count > MAX_ENVELOPE_REQUESTS {
+        return Err(EnvelopeError { offset: 1 });
+    }
+
+    let mut requests = Vec::with_capacity(count);
+    for _ in 0..count {
+        requests.push(reader.entry()?);
+    }
+    reader.finish()?;
+    Ok(requests)
+}
+
+/// Decodes a single entry written by [`encode_entry`].
+pub fn decode_entry(bytes: &[u8]) -> core::result::Result<OpRequest, EnvelopeError> {
+    let mut reader = Reader { data: bytes, offset: 0 };
+    let request = reader.entry()?;
+    reader.finish()?;
+    Ok(request)
+}
+
+/// Splits a request's entry into its `OpCode` (`None` for registrations)
+/// and the body after the `[kind]` or `[kind][op]` header, which the code
+/// stands for. Compact events carry the two separately.
+///
+/// ```
+/// # use host_programs::envelope::{join_entry, split_entry};
+/// # use host_programs::opcode::OpCode;
+/// # use host_programs::request::OpRequest;
+/// # use host_programs::types::*;
+/// let requests = [
+///     OpRequest::Register { handle: [1; 32], client_tag: [2; 32] },
+///     OpRequest::Unary { op: Fhe16UnaryOp::Not, input: [1; 32] },
+///     OpRequest::Binary { op: Fhe16BinaryOp::Add, lhs: [1; 32], rhs: [2; 32] },
+///     OpRequest::Ternary { op: Fhe16TernaryOp::Select, a: [1; 32], b: [2; 32], c: [3; 32] },
+///     OpRequest::Pad { input: [1; 32], target_width: 16 },
+///     OpRequest::Truncate { input: [1; 32], target_width: 8 },
+///     OpRequest::Concat { lo: [1; 32], hi: [2; 32], half_width: 8 },
+/// ];
+/// for request in requests {
+///     let (op_code, body) = split_entry(&request);
+///     assert_eq!(op_code, request.op_code());
+///     assert!(join_entry(op_code, &body).unwrap() == request);
+/// }
+///
+/// // the same body under another family's code is not the same request
+/// let (_, body) = split_entry(&requests[2]);
+/// assert!(join_entry(Some(OpCode::ternary(Fhe16TernaryOp::Select)), &body).is_err());
+/// assert!(join_entry(Some(OpCode(0x0300)), &body).is_err());
+/// ```
+pub fn split_entry(request: &OpRequest) -> (Option<OpCode>, Vec<u8>) {
+    let mut entry = Vec::new();
+    encode_entry(request, &mut entry);
+    let header = match request {
+        OpRequest::Unary { .. } | OpRequest::Binary { .. } | OpRequest::Ternary { .. } => 2,
+        _ => 1,
+    };
+    (request.op_code(), entry.split_off(header))
+}
+
+/// Inverse of [`split_entry`]. Error offsets are into `body`.
+pub fn join_entry(
+    op_code: Option<OpCode>,
+    body: &[u8],
+) -> core::result::Result<OpRequest, EnvelopeError> {
+    let header = match op_code.map(|code| (code.family(), code.index())) {
+        None => vec![KIND_REGISTER],
+        Some((Some(OpFamily::Unary), op)) => vec![KIND_UNARY, op],
+        Some((Some(OpFamily::Binary), op)) => vec![KIND_BINARY, op],
+        Some((Some(OpFamily::Ternary), op)) => vec![KIND_TERNARY, op],
+        Some((Some(OpFamily::Width), WIDTH_PAD)) => vec![KIND_PAD],
+        Some((Some(OpFamily::Width), WIDTH_TRUNCATE)) => vec![KIND_TRUNCATE],
+        Some((Some(OpFamily::Width), WIDTH_CONCAT)) => vec![KIND_CONCAT],
+        _ => return Err(EnvelopeError { offset: 0 }),
+    };
+    decode_entry(&[header.as_slice(), body].concat()).map_err(|err| EnvelopeError {
+        offset: err.offset.saturating_sub(header.len()),
+    })
+}
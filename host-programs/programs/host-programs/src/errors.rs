@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum ExecutorError {
+    #[msg("Target width must be between 1 and 16 bits")]
+    InvalidWidth,
+    #[msg("Caller nonce must be greater than the last accepted nonce")]
+    NonceReused,
+    #[msg("A caller nonce was supplied without the caller's nonce account")]
+    MissingNonceAccount,
+    #[msg("Planned step refers to a step that does not precede it")]
+    InvalidPlanReference,
+    #[msg("Emitted events do not match the planned chain")]
+    PlanMismatch,
+    #[msg("Too many distinct handles to compact into one event")]
+    TooManyCompactHandles,
+    #[msg("Compacted operand index is outside the handle table")]
+    InvalidCompactIndex,
+    #[msg("A handle record can reference at most three parents")]
+    TooManyParents,
+    #[msg("Parent handle record missing or out of order in remaining accounts")]
+    MissingParentRecord,
+    #[msg("Handle record is still referenced by other records")]
+    HandleInUse,
+    #[msg("Request envelope is malformed (offset is logged)")]
+    MalformedEnvelope,
+    #[msg("Request invoked deeper in the CPI stack than the configured maximum")]
+    CpiDepthExceeded,
+    #[msg("client_tag uses the reserved 0xFFFFFFFF protocol prefix")]
+    ReservedClientTag,
+    #[msg("Binary op with identical operands has a trivial result (strict mode)")]
+    TrivialSelfOp,
+    #[msg("Export proof does not match handle, owner and source program")]
+    InvalidExportProof,
+    #[msg("Source record is not a matching handle record of the source program")]
+    InvalidSourceRecord,
+    #[msg("client_tag prefix is not in the registration allowlist")]
+    TagNotAllowed,
+    #[msg("Too many client_tag prefixes for the allowlist")]
+    TooManyTagPrefixes,
+    #[msg("Envelope payload exceeds MAX_ENVELOPE_BYTES")]
+    EnvelopeTooLarge,
+    #[msg("Op is valid but not supported by the executor yet")]
+    OpNotSupported,
+    #[msg("Provenance proof does not match the expected root or is malformed")]
+    InvalidProvenanceProof,
+    #[msg("Fold requires an associative op (ADD, AND, OR, XOR, MAX, MIN)")]
+    NonAssociativeOp,
+    #[msg("Fold requires at least two handles")]
+    NotEnoughFoldInputs,
+    #[msg("Too many handles for one fold")]
+    TooManyFoldInputs,
+    #[msg("SELECT condition equals a branch, or branches are equal in strict mode")]
+    SuspiciousSelect,
+    #[msg("Only the accumulator owner may accumulate unless it is public")]
+    AccumulatorNotPublic,
+    #[msg("Op name is not a known canonical or legacy op name")]
+    UnknownOpName,
+    #[msg("Dry run complete; derived handles are in return data")]
+    DryRunComplete,
+    #[msg("Host instruction data in the transaction could not be decoded")]
+    MalformedInstruction,
+    #[msg("Handle record is not a SELECT derived from its parents under the current salt")]
+    NotSelectRecord,
+    #[msg("SELECT condition does not match the expected handle")]
+    ConditionSourceMismatch,
+    #[msg("Derived result handle equals one of its operands")]
+    DerivedEqualsOperand,
+    #[msg("Result handle is already recorded with a different op or parents")]
+    ProvenanceMismatch,
+    #[msg("Unknown event format version")]
+    UnknownEventFormat,
+    #[msg("Handle is frozen by the admin")]
+    HandleFrozen,
+    #[msg("Too many frozen handles")]
+    TooManyFrozenHandles,
+    #[msg("max_cpi_depth must be between 1 and Solana's instruction stack limit")]
+    MaxCpiDepthOutOfRange,
+    #[msg("Registration bond requires the handle record, payer and system program")]
+    MissingBondAccounts,
+    #[msg("Bond is locked until bond_release_slots pass")]
+    BondLocked,
+    #[msg("Handle record holds no bond")]
+    NoBond,
+    #[msg("Claimed handle does not match the on-chain derivation")]
+    HandleMismatch,
+    #[msg("Caller exceeded max_requests_per_window in the current window")]
+    RateLimited,
+    #[msg("Request throttling is on but the caller's activity account is missing")]
+    MissingActivityAccount,
+    #[msg("Throttling window must be at least one slot")]
+    InvalidThrottleWindow,
+    #[msg("Vector ops take handle arrays; use request_vector_op with Fhe16VectorOp")]
+    UseVectorOp,
+    #[msg("Vector operands must have equal lengths between 1 and MAX_VECTOR_LEN")]
+    InvalidVectorLength,
+    #[msg("Dot product needs 2 to MAX_VECTOR_LEN weights and as many values")]
+    InvalidDotProductLength,
+    #[msg("External handle uses an unknown layout version")]
+    MalformedExternalHandle,
+    #[msg("External handle's FHE type does not fit an FHE16 ciphertext")]
+    UnsupportedExternalType,
+    #[msg("Ternary op with three identical operands (strict mode)")]
+    DegenerateTernary,
+    #[msg("Advancing a caller nonce requires the caller's signature")]
+    NonceRequiresSigner,
+    #[msg("Only the program's upgrade authority can initialize the config")]
+    NotUpgradeAuthority,
+    #[msg("Handle is a canonical constant; only the program registers its record")]
+    ReservedHandle,
+    #[msg("Handle record already exists with a different derivation")]
+    HandleRecordConflict,
+    #[msg("Result record passed with the request belongs to a different handle")]
+    ResultRecordMismatch,
+    #[msg("bond_release_slots is below the minimum release delay")]
+    BondReleaseTooShort,
+    #[msg("Throttled request has neither a signing caller nor a calling program")]
+    UnattributedRequest,
+    #[msg("Activity account belongs to a different throttle key")]
+    ActivityAccountMismatch,
+    #[msg("Request is a registration, whose handle has no derivation preimage")]
+    NoDerivationPreimage,
+    #[msg("The strictness profile requires the caller to sign the request")]
+    CallerNotSigned,
+    #[msg("Handle record was used as an operand and cannot be collected")]
+    RecordUsed,
+    #[msg("Unused handle records can only be collected after bond_release_slots")]
+    RecordTooRecent,
+    #[msg("Record passed with the request is not a handle record of one of its operands")]
+    OperandRecordMismatch,
+}
@@ -1,6 +1,14 @@
 // programs/fhe16_executor/src/events.rs
 use anchor_lang::prelude::*;
-use crate::types::{Fhe16UnaryOp, Fhe16BinaryOp, Fhe16TernaryOp, Handle};
+use crate::discriminators::{match_discriminator, EventKind};
+use crate::handle::DOMAIN_SALT_LEN;
+use crate::opcode::OpCode;
+use crate::origin::RegistrationOrigin;
+use crate::state::StrictnessProfile;
+use crate::types::{
+    BinaryOpCategory, DotWeights, Fhe16BinaryOp, Fhe16TernaryOp, Fhe16UnaryOp, Fhe16VectorOp,
+    Handle, MoveReason,
+};
 
 /// 유저가 "새로운 입력 handle"을 등록할 때 찍는 이벤트
 #[event]
@@ -8,6 +16,10 @@ pub struct InputHandleRegistered {
     pub caller: Pubkey,
     pub handle: Handle,
     pub client_tag: [u8; 32],
+    /// client_tag 가 ClientTag::from_str_label 로 만든 label 인지 여부
+    pub is_labeled: bool,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
 }
 
 /// FHE16 단항 연산 요청 (예: NOT)
@@ -17,6 +29,8 @@ pub struct Fhe16UnaryOpRequested {
     pub op: Fhe16UnaryOp,
     pub input_handle: Handle,
     pub result_handle: Handle,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
 }
 
 /// FHE16 이항 연산 요청 (예: AND, OR, XOR, SDIV)
@@ -27,6 +41,23 @@ pub struct Fhe16BinaryOpRequested {
     pub lhs_handle: Handle,
     pub rhs_handle: Handle,
     pub result_handle: Handle,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
+}
+
+/// `Fhe16BinaryOpRequested` under `EVENT_FORMAT_CATEGORIZED`. `category` is
+/// the first byte after the discriminator, so subscribers can filter on it
+/// without decoding the rest.
+#[event]
+pub struct Fhe16BinaryOpCategorized {
+    pub category: BinaryOpCategory,
+    pub caller: Pubkey,
+    pub op: Fhe16BinaryOp,
+    pub lhs_handle: Handle,
+    pub rhs_handle: Handle,
+    pub result_handle: Handle,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
 }
 
 /// FHE16 삼항 연산 요청 (예: ADD3, EQ3)
@@ -38,4 +69,451 @@ pub struct Fhe16TernaryOpRequested {
     pub b_handle: Handle,
     pub c_handle: Handle,
     pub result_handle: Handle,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
+}
+
+/// FHE16 폭 확장 요청 (예: W8 -> W16)
+#[event]
+pub struct Fhe16PadRequested {
+    pub caller: Pubkey,
+    pub input_handle: Handle,
+    pub target_width: u8,
+    pub result_handle: Handle,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
+}
+
+/// FHE16 폭 축소 요청 (예: W16 -> W8, 하위 비트만 유지)
+#[event]
+pub struct Fhe16TruncateRequested {
+    pub caller: Pubkey,
+    pub input_handle: Handle,
+    pub target_width: u8,
+    pub result_handle: Handle,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
+}
+
+/// FHE16 vector op 요청: `results[i]` 는 `lhs[i]`, `rhs[i]` 의 결과
+#[event]
+pub struct Fhe16VectorOpRequested {
+    pub caller: Pubkey,
+    pub op: Fhe16VectorOp,
+    pub len: u8,
+    pub lhs: Vec<Handle>,
+    pub rhs: Vec<Handle>,
+    pub results: Vec<Handle>,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
+}
+
+/// FHE16 dot product 요청: sum_i(weights[i] * values[i]) 을 handle 하나로
+#[event]
+pub struct Fhe16DotProductRequested {
+    pub caller: Pubkey,
+    pub weights: DotWeights,
+    pub values: Vec<Handle>,
+    pub result_handle: Handle,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
+}
+
+/// FHE16 두 ciphertext 연결 요청 (lo 가 하위, hi 가 상위 절반)
+#[event]
+pub struct Fhe16ConcatRequested {
+    pub caller: Pubkey,
+    pub lo_handle: Handle,
+    pub hi_handle: Handle,
+    /// Width of the joined value, twice the width of each half.
+    pub result_width: u8,
+    pub result_handle: Handle,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
+}
+
+/// Compact-event form of a request event, emitted by multi-request
+/// instructions instead of the per-arity events when
+/// `Config::compact_events` is set. `op_code` and `request` are the
+/// request's envelope entry as split by `envelope::split_entry`. `caller`
+/// and `origin_program` are `None` (same_caller) when equal to those of the
+/// previous packed event of the instruction.
+#[event]
+pub struct Fhe16PackedOpRequested {
+    /// Program-wide code of the requested op (`None` for registrations),
+    /// so subscribers can filter on the op without decoding `request`.
+    pub op_code: Option<OpCode>,
+    /// Entry body: the operands, preceded by the width for the width ops.
+    pub request: Vec<u8>,
+    pub result_handle: Handle,
+    pub caller: Option<Pubkey>,
+    pub origin_program: Option<Pubkey>,
+}
+
+/// 여러 요청을 하나의 envelope 로 제출 (payload 는 envelope v1 인코딩 그대로)
+#[event]
+pub struct Fhe16EnvelopeRequested {
+    pub caller: Pubkey,
+    pub payload: Vec<u8>,
+    pub result_handles: Vec<Handle>,
+    /// Program of the top-level instruction (host itself when called directly).
+    pub origin_program: Pubkey,
+}
+
+/// Every event format but the legacy one: context of the request reported
+/// by the next event of the same instruction. `seq` is its program-wide
+/// work cursor sequence number (the first one for multi-request
+/// instructions).
+#[event]
+pub struct RequestMeta {
+    pub seq: u64,
+    pub timestamp: i64,
+    pub stack_height: u8,
+}
+
+/// Multi-request instructions reserve their sequence numbers in one work
+/// cursor write: `first_seq..first_seq + count` go, in order, to the
+/// requests reported by the events that follow in the same instruction.
+#[event]
+pub struct SequenceReserved {
+    pub first_seq: u64,
+    pub count: u64,
+}
+
+/// 다른 host-program 배포로 handle 을 내보냄
+#[event]
+pub struct HandleExported {
+    pub handle: Handle,
+    pub source_program: Pubkey,
+    pub owner: Pubkey,
+    pub proof: [u8; 32],
+}
+
+/// 다른 배포에서 export 된 handle 을 로컬 registry 로 가져옴
+#[event]
+pub struct HandleImported {
+    pub handle: Handle,
+    pub source_program: Pubkey,
+    pub owner: Pubkey,
+}
+
+/// Admin froze `handle`; `reason_hash` points at the off-chain incident report.
+#[event]
+pub struct HandleFrozen {
+    pub handle: Handle,
+    pub reason_hash: [u8; 32],
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct HandleUnfrozen {
+    pub handle: Handle,
+    pub admin: Pubkey,
+}
+
+/// SELECT record 의 condition 이 기대한 handle 과 일치함을 확인 (디버깅용)
+#[event]
+pub struct ConditionSourceConfirmed {
+    pub select_handle: Handle,
+    pub condition_handle: Handle,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnomalyKind {
+    DerivedEqualsOperand,
+    ProvenanceMismatch,
+}
+
+/// A derivation self-check failed while `anomaly_soft_fail` was set; the
+/// request went through. Any occurrence indicates a derivation bug.
+#[event]
+pub struct DerivationAnomaly {
+    pub caller: Pubkey,
+    pub result_handle: Handle,
+    /// Op the request derived `result_handle` with (`None` for
+    /// registrations).
+    pub op_code: Option<OpCode>,
+    pub kind: AnomalyKind,
+}
+
+/// Every lamport movement the program performs, for treasury
+/// reconciliation. Only emitted through `accounting::emit_lamports_moved`.
+#[event]
+pub struct LamportsMoved {
+    pub reason: MoveReason,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub related_handle: Option<Handle>,
+}
+
+/// Emitted next to any event produced through an admin override path,
+/// which skips the normal request guards.
+#[event]
+pub struct AdminOverrideUsed {
+    pub admin: Pubkey,
+    pub caller: Pubkey,
+    pub handle: Handle,
+}
+
+/// A request whose result handle the caller already requested in this
+/// slot; emitted instead of the request event (see `DedupCache`).
+#[event]
+pub struct SuppressedDuplicate {
+    pub caller: Pubkey,
+    pub result_handle: Handle,
+    pub slot: u64,
+}
+
+/// Config field reported by `ConfigUpdated`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConfigField {
+    /// The config was created; `new` holds the whole initial config.
+    Initialized,
+    EmitEvents,
+    StrictMode,
+    MaxCpiDepth,
+    StrictSelect,
+    TemplatesEnabled,
+    TagAllowlist,
+    DomainSalt,
+    AnomalySoftFail,
+    EventFormat,
+    CompactEvents,
+    RegistrationBond,
+    Treasury,
+    RateLimit,
+    Strictness,
+}
+
+/// Governance audit trail: emitted by every admin instruction that
+/// mutates `Config`. `old`/`new` are the Borsh encoding of the field value.
+#[event]
+pub struct ConfigUpdated {
+    pub field: ConfigField,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+    pub admin: Pubkey,
+}
+
+/// The op handle derivation salt changed. Handles requested from this
+/// point on are derived under `new_salt`; earlier ones keep `old_salt`.
+#[event]
+pub struct DomainSaltRotated {
+    pub admin: Pubkey,
+    pub old_salt: [u8; DOMAIN_SALT_LEN],
+    pub new_salt: [u8; DOMAIN_SALT_LEN],
+    pub slot: u64,
+}
+
+/// The deployment's strictness profile changed; `Config::policy` resolves
+/// the checks of every later request under `new_profile`.
+#[event]
+pub struct StrictnessChanged {
+    pub admin: Pubkey,
+    pub old_profile: StrictnessProfile,
+    pub new_profile: StrictnessProfile,
+    pub slot: u64,
+}
+
+/// Compute units a request instruction's derivation consumed, or a whole
+/// host CPI for `cpi:`-prefixed names from `cpi_builder`. Only emitted by
+/// builds with the `cu-profile` feature.
+#[event]
+pub struct CuUsed {
+    pub instruction: String,
+    pub units: u64,
+}
+
+/// `bootstrap_constants` ran; `created` counts records it had to create
+/// (0 on a repeated call).
+#[event]
+pub struct ConstantsBootstrapped {
+    pub zero: Handle,
+    pub one: Handle,
+    pub true_handle: Handle,
+    pub false_handle: Handle,
+    pub created: u8,
+}
+
+/// Warning: a valid op the executor does not implement yet was requested
+/// (and rejected). `op_code`'s family gives the op's arity.
+#[event]
+pub struct UnimplementedOpRequested {
+    pub caller: Pubkey,
+    pub op_code: OpCode,
+}
+
+/// Decoded host-program event, for off-chain tooling that walks a
+/// transaction's logs in order.
+pub enum Fhe16Event {
+    InputHandleRegistered(InputHandleRegistered),
+    UnaryOpRequested(Fhe16UnaryOpRequested),
+    BinaryOpRequested(Fhe16BinaryOpRequested),
+    TernaryOpRequested(Fhe16TernaryOpRequested),
+    PadRequested(Fhe16PadRequested),
+    EnvelopeRequested(Fhe16EnvelopeRequested),
+    HandleExported(HandleExported),
+    HandleImported(HandleImported),
+    AdminOverrideUsed(AdminOverrideUsed),
+    UnimplementedOpRequested(UnimplementedOpRequested),
+    DomainSaltRotated(DomainSaltRotated),
+    LamportsMoved(LamportsMoved),
+    ConfigUpdated(ConfigUpdated),
+    SuppressedDuplicate(SuppressedDuplicate),
+    TruncateRequested(Fhe16TruncateRequested),
+    ConditionSourceConfirmed(ConditionSourceConfirmed),
+    DerivationAnomaly(DerivationAnomaly),
+    SequenceReserved(SequenceReserved),
+    RequestMeta(RequestMeta),
+    PackedOpRequested(Fhe16PackedOpRequested),
+    BinaryOpCategorized(Fhe16BinaryOpCategorized),
+    HandleFrozen(HandleFrozen),
+    HandleUnfrozen(HandleUnfrozen),
+    ConcatRequested(Fhe16ConcatRequested),
+    StrictnessChanged(StrictnessChanged),
+    VectorOpRequested(Fhe16VectorOpRequested),
+    DotProductRequested(Fhe16DotProductRequested),
+    CuUsed(CuUsed),
+    ConstantsBootstrapped(ConstantsBootstrapped),
+}
+
+impl Fhe16Event {
+    /// Decodes raw event data (discriminator + Borsh payload), e.g. the
+    /// base64-decoded body of a `Program data:` log line. `None` for an
+    /// unknown discriminator or a payload that does not decode:
+    ///
+    /// ```
+    /// use anchor_lang::{Discriminator, Event};
+    /// use host_programs::discriminators::{match_discriminator, EventKind};
+    /// use host_programs::events::{Fhe16Event, HandleFrozen};
+    ///
+    /// let admin = Default::default();
+    /// let data = HandleFrozen { handle: [7; 32], reason_hash: [0; 32], admin }.data();
+    /// assert_eq!(&data[..8], HandleFrozen::DISCRIMINATOR);
+    /// assert_eq!(match_discriminator(&data), Some(EventKind::HandleFrozen));
+    /// assert!(matches!(Fhe16Event::decode(&data), Some(Fhe16Event::HandleFrozen(_))));
+    ///
+    /// assert!(Fhe16Event::decode(&data[..data.len() - 1]).is_none());
+    /// assert!(match_discriminator(&data[..7]).is_none());
+    /// assert!(Fhe16Event::decode(&[0u8; 40]).is_none());
+    /// ```
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let kind = match_discriminator(data)?;
+        let mut payload = &data[8..];
+        let event = match kind {
+            EventKind::InputHandleRegistered => {
+                Self::InputHandleRegistered(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16UnaryOpRequested => {
+                Self::UnaryOpRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16BinaryOpRequested => {
+                Self::BinaryOpRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16TernaryOpRequested => {
+                Self::TernaryOpRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16PadRequested => {
+                Self::PadRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16EnvelopeRequested => {
+                Self::EnvelopeRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::HandleExported => {
+                Self::HandleExported(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::HandleImported => {
+                Self::HandleImported(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::AdminOverrideUsed => {
+                Self::AdminOverrideUsed(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::UnimplementedOpRequested => {
+                Self::UnimplementedOpRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::DomainSaltRotated => {
+                Self::DomainSaltRotated(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::LamportsMoved => {
+                Self::LamportsMoved(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::ConfigUpdated => {
+                Self::ConfigUpdated(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::SuppressedDuplicate => {
+                Self::SuppressedDuplicate(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16TruncateRequested => {
+                Self::TruncateRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::ConditionSourceConfirmed => {
+                Self::ConditionSourceConfirmed(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::DerivationAnomaly => {
+                Self::DerivationAnomaly(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::SequenceReserved => {
+                Self::SequenceReserved(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::RequestMeta => {
+                Self::RequestMeta(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16PackedOpRequested => {
+                Self::PackedOpRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16BinaryOpCategorized => {
+                Self::BinaryOpCategorized(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::HandleFrozen => {
+                Self::HandleFrozen(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::HandleUnfrozen => {
+                Self::HandleUnfrozen(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16ConcatRequested => {
+                Self::ConcatRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::StrictnessChanged => {
+                Self::StrictnessChanged(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16VectorOpRequested => {
+                Self::VectorOpRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::Fhe16DotProductRequested => {
+                Self::DotProductRequested(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::CuUsed => {
+                Self::CuUsed(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+            EventKind::ConstantsBootstrapped => {
+                Self::ConstantsBootstrapped(AnchorDeserialize::deserialize(&mut payload).ok()?)
+            }
+        };
+        Some(event)
+    }
+
+    /// Who started the request, for events that carry `origin_program`.
+    pub fn origin(&self) -> Option<RegistrationOrigin> {
+        let origin_program = match self {
+            Self::InputHandleRegistered(e) => e.origin_program,
+            Self::UnaryOpRequested(e) => e.origin_program,
+            Self::BinaryOpRequested(e) => e.origin_program,
+            Self::BinaryOpCategorized(e) => e.origin_program,
+            Self::TernaryOpRequested(e) => e.origin_program,
+            Self::PadRequested(e) => e.origin_program,
+            Self::TruncateRequested(e) => e.origin_program,
+            Self::ConcatRequested(e) => e.origin_program,
+            Self::EnvelopeRequested(e) => e.origin_program,
+            Self::PackedOpRequested(e) => e.origin_program?,
+            _ => return None,
+        };
+        Some(RegistrationOrigin::from_origin_program(origin_program))
+    }
+
+    /// The dapp program behind a CPI-originated request; `None` for direct
+    /// user calls and events without an origin.
+    pub fn cpi_program(&self) -> Option<Pubkey> {
+        self.origin()?.cpi_program()
+    }
 }
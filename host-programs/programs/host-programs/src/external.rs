@@ -0,0 +1,110 @@
+// Adapter for handles issued by fhEVM-style systems, so ciphertexts that
+// teams bring over can be referenced by a host `Handle`. An external handle
+// is 32 bytes laid out as
+//
+//   [0..21)  prehandle hash     [21]  output index
+//   [22..30) chain id (BE)      [30]  FHE type     [31]  layout version
+//
+// The mapping re-hashes all 32 bytes under its own domain tag. It is one
+// way: the host handle does not reveal the external one, so the caller must
+// keep the original handle to go back, and the same ciphertext registered
+// natively here gets a different, unrelated handle.
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+
+use crate::errors::ExecutorError;
+use crate::types::{Handle, FHE16_MAX_WIDTH};
+
+const HANDLE_DOMAIN_EXTERNAL: &[u8] = b"FHE16_EXTERNAL_V1";
+
+/// The only external layout version the adapter understands.
+pub const EXTERNAL_HANDLE_VERSION: u8 = 0;
+
+/// A parsed fhEVM-style handle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExternalHandle {
+    pub prehandle: [u8; 21],
+    pub index: u8,
+    pub chain_id: u64,
+    pub fhe_type: u8,
+    pub version: u8,
+}
+
+impl ExternalHandle {
+    /// Splits `raw` into its fields; fails with `MalformedExternalHandle`
+    /// for an unknown layout version.
+    pub fn parse(raw: &[u8; 32]) -> Result<Self> {
+        let version = raw[31];
+        require!(
+            version == EXTERNAL_HANDLE_VERSION,
+            ExecutorError::MalformedExternalHandle
+        );
+        let mut prehandle = [0u8; 21];
+        prehandle.copy_from_slice(&raw[..21]);
+        let mut chain_id = [0u8; 8];
+        chain_id.copy_from_slice(&raw[22..30]);
+        Ok(Self {
+            prehandle,
+            index: raw[21],
+            chain_id: u64::from_be_bytes(chain_id),
+            fhe_type: raw[30],
+            version,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut raw = [0u8; 32];
+        raw[..21].copy_from_slice(&self.prehandle);
+        raw[21] = self.index;
+        raw[22..30].copy_from_slice(&self.chain_id.to_be_bytes());
+        raw[30] = self.fhe_type;
+        raw[31] = self.version;
+        raw
+    }
+
+    /// Plaintext width of the external type: ebool, euint4, euint8 and
+    /// euint16 fit an FHE16 ciphertext; wider or unknown types are `None`.
+    pub fn width(&self) -> Option<u8> {
+        let width = match self.fhe_type {
+            0 => 1,
+            1 => 4,
+            2 => 8,
+            3 => 16,
+            _ => return None,
+        };
+        (width <= FHE16_MAX_WIDTH).then_some(width)
+    }
+
+    /// Host handle the external ciphertext is referenced by. Fails with
+    /// `UnsupportedExternalType` if the type does not fit FHE16.
+    ///
+    /// ```
+    /// use host_programs::external::ExternalHandle;
+    ///
+    /// // euint8 output 0 of a prehandle on chain 9000
+    /// let mut raw = [0xab; 32];
+    /// raw[21] = 0;
+    /// raw[22..30].copy_from_slice(&9000u64.to_be_bytes());
+    /// raw[30] = 2;
+    /// raw[31] = 0;
+    ///
+    /// let external = ExternalHandle::parse(&raw).unwrap();
+    /// assert_eq!((external.chain_id, external.width()), (9000, Some(8)));
+    /// assert_eq!(external.to_bytes(), raw);
+    ///
+    /// let handle = external.to_handle().unwrap();
+    /// assert_eq!(handle, ExternalHandle::parse(&raw).unwrap().to_handle().unwrap());
+    /// assert_ne!(handle, raw);
+    ///
+    /// raw[21] = 1;
+    /// assert_ne!(ExternalHandle::parse(&raw).unwrap().to_handle().unwrap(), handle);
+    /// raw[30] = 5; // euint64
+    /// assert!(ExternalHandle::parse(&raw).unwrap().to_handle().is_err());
+    /// raw[31] = 1;
+    /// assert!(ExternalHandle::parse(&raw).is_err());
+    /// ```
+    pub fn to_handle(&self) -> Result<Handle> {
+        require!(self.width().is_some(), ExecutorError::UnsupportedExternalType);
+        Ok(hashv(&[HANDLE_DOMAIN_EXTERNAL, &self.to_bytes()]).to_bytes())
+    }
+}
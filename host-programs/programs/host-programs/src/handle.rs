@@ -1,20 +1,136 @@
 use anchor_lang::prelude::*;
 use solana_sha256_hasher::hashv;
 
-use crate::types::{Fhe16UnaryOp, Fhe16BinaryOp, Fhe16TernaryOp, Handle};
+use crate::errors::ExecutorError;
+use crate::types::{
+    DotWeights, Fhe16BinaryOp, Fhe16TernaryOp, Fhe16UnaryOp, Fhe16VectorOp, Handle, HandleBytes,
+    FHE16_CONST_MAX, FHE16_CONST_ONE, FHE16_CONST_ZERO, HANDLE_LEN,
+};
 
-const HANDLE_DOMAIN_UNARY: &[u8] = b"FHE16_UNARY_V1";
-const HANDLE_DOMAIN_BINARY: &[u8] = b"FHE16_BINARY_V1";
-const HANDLE_DOMAIN_TERNARY: &[u8] = b"FHE16_TERNARY_V1";
+pub const HANDLE_DOMAIN_UNARY: &[u8] = b"FHE16_UNARY_V1";
+pub const HANDLE_DOMAIN_BINARY: &[u8] = b"FHE16_BINARY_V1";
+pub const HANDLE_DOMAIN_TERNARY: &[u8] = b"FHE16_TERNARY_V1";
+pub const HANDLE_DOMAIN_PAD: &[u8] = b"FHE16_PAD_V1";
+pub const HANDLE_DOMAIN_TRUNCATE: &[u8] = b"FHE16_TRUNCATE_V1";
+pub const HANDLE_DOMAIN_CONCAT: &[u8] = b"FHE16_CONCAT_V1";
+pub const HANDLE_DOMAIN_VECTOR: &[u8] = b"FHE16_VECTOR_V1";
+pub const HANDLE_DOMAIN_DOT: &[u8] = b"FHE16_DOT_V1";
+const HANDLE_DOMAIN_TRIVIAL: &[u8] = b"FHE16_TRIVIAL_V1";
+const HANDLE_DOMAIN_INPUT: &[u8] = b"FHE16_INPUT";
+const SHORT_ID_DOMAIN: &[u8] = b"FHE16_SHORT_ID_V1";
+const EXPORT_PROOF_DOMAIN: &[u8] = b"FHE16_EXPORT_V1";
+
+/// Length of the admin-rotatable salt mixed into op handle derivations.
+pub const DOMAIN_SALT_LEN: usize = 16;
+
+pub type DomainSalt = [u8; DOMAIN_SALT_LEN];
+
+/// The salt before any rotation. It contributes no bytes, so handles derived
+/// under it are exactly the original (unsalted) ones. Any other salt is
+/// hashed in right after the domain tag, which makes the preimage 16 bytes
+/// longer, so salted and unsalted handles can never coincide.
+pub const NO_DOMAIN_SALT: DomainSalt = [0u8; DOMAIN_SALT_LEN];
+
+fn salt_bytes(salt: &DomainSalt) -> &[u8] {
+    if *salt == NO_DOMAIN_SALT {
+        &[]
+    } else {
+        salt
+    }
+}
+
+/// Concatenation of the parts the `derive_*_handle` functions hash for an
+/// op: domain tag, salt (once rotated), program id, `param` (the op byte,
+/// the target width for PAD / TRUNCATE, or the half width for CONCAT) and the
+/// operands in order.
+pub(crate) fn op_preimage(
+    domain: &[u8],
+    salt: &DomainSalt,
+    program_id: &Pubkey,
+    param: u8,
+    operands: &[Handle],
+) -> Vec<u8> {
+    let mut preimage =
+        Vec::with_capacity(domain.len() + DOMAIN_SALT_LEN + 33 + 32 * operands.len());
+    preimage.extend_from_slice(domain);
+    preimage.extend_from_slice(salt_bytes(salt));
+    preimage.extend_from_slice(program_id.as_ref());
+    preimage.push(param);
+    for operand in operands {
+        preimage.extend_from_slice(operand);
+    }
+    preimage
+}
+
+/// Length-parameterized form of the op derivations over the same preimage
+/// as [`op_preimage`]. With `N = HANDLE_LEN` it is exactly the matching
+/// `derive_*_handle`. Other lengths are filled with 32-byte blocks
+/// `sha256(N, block index, preimage)`, the last one cut to fit, so they
+/// never coincide with a 32-byte handle.
+///
+/// ```
+/// use anchor_lang::prelude::Pubkey;
+/// use host_programs::handle::*;
+/// use host_programs::types::{Fhe16BinaryOp, HandleBytes};
+///
+/// let program_id = Pubkey::new_unique();
+/// let add = Fhe16BinaryOp::Add;
+/// let salt = NO_DOMAIN_SALT;
+///
+/// let (lhs, rhs) = ([1u8; 32], [2u8; 32]);
+/// let short: HandleBytes =
+///     derive_op_handle_bytes(HANDLE_DOMAIN_BINARY, &salt, &program_id, add as u8, &[lhs, rhs]);
+/// assert_eq!(short, derive_binary_handle(add, &lhs, &rhs, &program_id, &salt));
+///
+/// let derive = |operands: &[HandleBytes<64>]| {
+///     derive_op_handle_bytes(HANDLE_DOMAIN_BINARY, &salt, &program_id, add as u8, operands)
+/// };
+/// let (lhs, rhs) = ([1u8; 64], [2u8; 64]);
+/// let long = derive(&[lhs, rhs]);
+/// assert_eq!(long, derive(&[lhs, rhs]));
+/// assert_ne!(long, derive(&[rhs, lhs]));
+/// assert_ne!(long[..32], short[..]);
+/// assert_ne!(long[..32], long[32..]);
+/// ```
+pub fn derive_op_handle_bytes<const N: usize>(
+    domain: &[u8],
+    salt: &DomainSalt,
+    program_id: &Pubkey,
+    param: u8,
+    operands: &[HandleBytes<N>],
+) -> HandleBytes<N> {
+    let param = [param];
+    let mut parts: Vec<&[u8]> = vec![domain, salt_bytes(salt), program_id.as_ref(), &param];
+    parts.extend(operands.iter().map(|operand| operand.as_slice()));
+
+    let mut handle = [0u8; N];
+    if N == HANDLE_LEN {
+        handle.copy_from_slice(&hashv(&parts).to_bytes());
+        return handle;
+    }
+    let len_bytes = (N as u32).to_le_bytes();
+    for (index, block) in handle.chunks_mut(32).enumerate() {
+        let index_bytes = (index as u32).to_le_bytes();
+        let mut block_parts: Vec<&[u8]> = vec![&len_bytes, &index_bytes];
+        block_parts.extend_from_slice(&parts);
+        block.copy_from_slice(&hashv(&block_parts).to_bytes()[..block.len()]);
+    }
+    handle
+}
+
+const BASE58_ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
 
 pub fn derive_unary_handle(
     op: Fhe16UnaryOp,
     input: &Handle,
     program_id: &Pubkey,
+    salt: &DomainSalt,
 ) -> Handle {
     let op_byte = [op as u8];
     let hash = hashv(&[
         HANDLE_DOMAIN_UNARY,
+        salt_bytes(salt),
         program_id.as_ref(),
         &op_byte,
         input,
@@ -27,10 +143,12 @@ pub fn derive_binary_handle(
     lhs: &Handle,
     rhs: &Handle,
     program_id: &Pubkey,
+    salt: &DomainSalt,
 ) -> Handle {
     let op_byte = [op as u8];
     let hash = hashv(&[
         HANDLE_DOMAIN_BINARY,
+        salt_bytes(salt),
         program_id.as_ref(),
         &op_byte,
         lhs,
@@ -45,10 +163,12 @@ pub fn derive_ternary_handle(
     b: &Handle,
     c: &Handle,
     program_id: &Pubkey,
+    salt: &DomainSalt,
 ) -> Handle {
     let op_byte = [op as u8];
     let hash = hashv(&[
         HANDLE_DOMAIN_TERNARY,
+        salt_bytes(salt),
         program_id.as_ref(),
         &op_byte,
         a,
@@ -56,4 +176,306 @@ pub fn derive_ternary_handle(
         c,
     ]);
     hash.to_bytes()
-}
\ No newline at end of file
+}
+
+/// Pairwise steps `(lhs, rhs, result)` of the balanced-tree reduction of
+/// `handles` with `op`: adjacent handles are combined level by level and
+/// an odd trailing handle is carried up unchanged. The last step's result
+/// is the fold result.
+pub fn derive_fold_steps(
+    op: Fhe16BinaryOp,
+    handles: &[Handle],
+    program_id: &Pubkey,
+    salt: &DomainSalt,
+) -> Vec<(Handle, Handle, Handle)> {
+    let mut steps = Vec::with_capacity(handles.len().saturating_sub(1));
+    let mut level = handles.to_vec();
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        for pair in level.chunks(2) {
+            match *pair {
+                [lhs, rhs] => {
+                    let result = derive_binary_handle(op, &lhs, &rhs, program_id, salt);
+                    steps.push((lhs, rhs, result));
+                    next.push(result);
+                }
+                [carried] => next.push(carried),
+                _ => unreachable!(),
+            }
+        }
+        level = next;
+    }
+    steps
+}
+
+/// Handle of `input` zero-extended to `target_width` bits.
+pub fn derive_pad_handle(
+    input: &Handle,
+    target_width: u8,
+    program_id: &Pubkey,
+    salt: &DomainSalt,
+) -> Handle {
+    let width_byte = [target_width];
+    let hash = hashv(&[
+        HANDLE_DOMAIN_PAD,
+        salt_bytes(salt),
+        program_id.as_ref(),
+        &width_byte,
+        input,
+    ]);
+    hash.to_bytes()
+}
+
+/// Handle of `input` narrowed to its low `target_width` bits.
+pub fn derive_truncate_handle(
+    input: &Handle,
+    target_width: u8,
+    program_id: &Pubkey,
+    salt: &DomainSalt,
+) -> Handle {
+    let width_byte = [target_width];
+    let hash = hashv(&[
+        HANDLE_DOMAIN_TRUNCATE,
+        salt_bytes(salt),
+        program_id.as_ref(),
+        &width_byte,
+        input,
+    ]);
+    hash.to_bytes()
+}
+
+/// Handle of the `2 * half_width`-bit value whose low half is `lo` and high
+/// half is `hi`. The operands are hashed in that order, so swapping the
+/// halves gives a different handle.
+pub fn derive_concat_handle(
+    lo: &Handle,
+    hi: &Handle,
+    half_width: u8,
+    program_id: &Pubkey,
+    salt: &DomainSalt,
+) -> Handle {
+    let width_byte = [half_width];
+    let hash = hashv(&[
+        HANDLE_DOMAIN_CONCAT,
+        salt_bytes(salt),
+        program_id.as_ref(),
+        &width_byte,
+        lo,
+        hi,
+    ]);
+    hash.to_bytes()
+}
+
+/// One result handle per element pair of `lhs` and `rhs` (equal lengths,
+/// checked by the caller). The op byte, the element count and the element
+/// index are all hashed in, so the same pair at another position or in a
+/// longer vector yields a different handle. More than 255 elements fail
+/// with `InvalidVectorLength`.
+pub fn derive_vector_handles(
+    op: Fhe16VectorOp,
+    lhs: &[Handle],
+    rhs: &[Handle],
+    program_id: &Pubkey,
+    salt: &DomainSalt,
+) -> Result<Vec<Handle>> {
+    let len = u8::try_from(lhs.len()).map_err(|_| error!(ExecutorError::InvalidVectorLength))?;
+    // index < len <= u8::MAX
+    let handles = (0..=len)
+        .zip(lhs.iter().zip(rhs))
+        .map(|(index, (lhs_element, rhs_element))| {
+            let params = [op as u8, len, index];
+            hashv(&[
+                HANDLE_DOMAIN_VECTOR,
+                salt_bytes(salt),
+                program_id.as_ref(),
+                &params,
+                lhs_element,
+                rhs_element,
+            ])
+            .to_bytes()
+        })
+        .collect();
+    Ok(handles)
+}
+
+/// Handle of `sum_i(weights[i] * values[i])`. A kind byte (0 encrypted, 1
+/// scalar weights) and the length precede the weights (handles, or u16 LE
+/// scalars) and then the values, so the two variants never collide. More
+/// than 255 values fail with `InvalidDotProductLength`.
+pub fn derive_dot_product_handle(
+    weights: &DotWeights,
+    values: &[Handle],
+    program_id: &Pubkey,
+    salt: &DomainSalt,
+) -> Result<Handle> {
+    let (kind, weight_bytes) = match weights {
+        DotWeights::Encrypted(weights) => (0u8, weights.concat()),
+        DotWeights::Scalar(weights) => {
+            (1u8, weights.iter().flat_map(|weight| weight.to_le_bytes()).collect())
+        }
+    };
+    let len =
+        u8::try_from(values.len()).map_err(|_| error!(ExecutorError::InvalidDotProductLength))?;
+    let params = [kind, len];
+    let hash = hashv(&[
+        HANDLE_DOMAIN_DOT,
+        salt_bytes(salt),
+        program_id.as_ref(),
+        &params,
+        &weight_bytes,
+        &values.concat(),
+    ]);
+    Ok(hash.to_bytes())
+}
+
+/// Ciphertext encoding scheme input handles are derived under by default.
+pub const DEFAULT_INPUT_SCHEME_VERSION: u8 = 1;
+
+/// Handle of a freshly encrypted input. Version 1 is the plain
+/// `sha256(ciphertext)` clients have always used; later encoding schemes
+/// hash their version in so the same bytes never share a handle across
+/// schemes.
+///
+/// Every handle rests on `hashv` being plain SHA-256 over the concatenated
+/// parts. The known answers below (FIPS 180-2 examples, cross-checked with
+/// Python's `hashlib`) catch an accidental backend swap:
+///
+/// ```
+/// use host_programs::handle::{derive_input_handle, DEFAULT_INPUT_SCHEME_VERSION};
+/// use solana_sha256_hasher::hashv;
+///
+/// fn hex(digest: [u8; 32]) -> String {
+///     digest.iter().map(|byte| format!("{byte:02x}")).collect()
+/// }
+///
+/// const ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+/// assert_eq!(hex(hashv(&[b"abc"]).to_bytes()), ABC);
+/// // parts are hashed as one concatenated message
+/// assert_eq!(hex(hashv(&[b"a", b"", b"bc"]).to_bytes()), ABC);
+/// assert_eq!(hex(derive_input_handle(b"abc", DEFAULT_INPUT_SCHEME_VERSION)), ABC);
+///
+/// let two_blocks = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+/// assert_eq!(
+///     hex(hashv(&[two_blocks]).to_bytes()),
+///     "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+/// );
+/// ```
+///
+/// No two versions share a handle for the same bytes:
+///
+/// ```
+/// use host_programs::handle::derive_input_handle;
+///
+/// let ciphertext = [7u8; 64];
+/// let handles: Vec<_> = (0..=3).map(|v| derive_input_handle(&ciphertext, v)).collect();
+/// for (i, a) in handles.iter().enumerate() {
+///     assert!(handles[i + 1..].iter().all(|b| a != b), "version {i} collides");
+/// }
+/// ```
+pub fn derive_input_handle(ciphertext: &[u8], scheme_version: u8) -> Handle {
+    if scheme_version == DEFAULT_INPUT_SCHEME_VERSION {
+        return hashv(&[ciphertext]).to_bytes();
+    }
+    let version_byte = [scheme_version];
+    let hash = hashv(&[
+        HANDLE_DOMAIN_INPUT,
+        &version_byte,
+        ciphertext,
+    ]);
+    hash.to_bytes()
+}
+
+/// Canonical handle of the trivial encryption of `value`, shared by every
+/// dapp instead of each registering its own encrypted constant.
+pub fn constant_handle(value: u16, program_id: &Pubkey) -> Handle {
+    let value_bytes = value.to_le_bytes();
+    let hash = hashv(&[
+        HANDLE_DOMAIN_TRIVIAL,
+        program_id.as_ref(),
+        &value_bytes,
+    ]);
+    hash.to_bytes()
+}
+
+/// Canonical handle of the trivial encryption of a 1-bit boolean. The
+/// trailing width byte keeps it apart from `constant_handle` of 0 and 1,
+/// which are 16-bit values.
+pub fn boolean_constant_handle(value: bool, program_id: &Pubkey) -> Handle {
+    let value_bytes = u16::from(value).to_le_bytes();
+    let hash = hashv(&[
+        HANDLE_DOMAIN_TRIVIAL,
+        program_id.as_ref(),
+        &value_bytes,
+        &[1u8],
+    ]);
+    hash.to_bytes()
+}
+
+/// Whether `handle` is one of the canonical constants whose records only
+/// `initialize` and `bootstrap_constants` may create.
+pub fn is_constant_handle(handle: &Handle, program_id: &Pubkey) -> bool {
+    [FHE16_CONST_ZERO, FHE16_CONST_ONE, FHE16_CONST_MAX]
+        .into_iter()
+        .map(|value| constant_handle(value, program_id))
+        .chain([false, true].map(|value| boolean_constant_handle(value, program_id)))
+        .any(|constant| constant == *handle)
+}
+
+/// Binds a handle to its owner and the deployment exporting it. The
+/// importing side recomputes this and checks it against the export record
+/// the source deployment wrote, since the hash alone authenticates nothing.
+pub fn derive_export_proof(handle: &Handle, owner: &Pubkey, source_program: &Pubkey) -> [u8; 32] {
+    hashv(&[
+        EXPORT_PROOF_DOMAIN,
+        source_program.as_ref(),
+        owner.as_ref(),
+        handle,
+    ])
+    .to_bytes()
+}
+
+/// Short display id for a handle, for correlating handles in UIs and logs.
+///
+/// This is a truncated hash (64 bits) and is NOT collision resistant:
+/// never use it to look up, authorize or deduplicate handles. The full
+/// 32-byte handle remains the only source of truth.
+pub fn handle_short_id(handle: &Handle) -> [u8; 8] {
+    let hash = hashv(&[SHORT_ID_DOMAIN, handle]).to_bytes();
+    let mut short_id = [0u8; 8];
+    short_id.copy_from_slice(&hash[..8]);
+    short_id
+}
+
+/// Base58 rendering of [`handle_short_id`] (at most 11 characters).
+///
+/// ```
+/// use host_programs::handle::{handle_short_id, handle_short_id_base58};
+///
+/// let handle = [7u8; 32];
+/// assert_eq!(handle_short_id_base58(&handle), handle_short_id_base58(&handle));
+/// // a hash, not a prefix: handles sharing their first 8 bytes still differ
+/// let mut sibling = handle;
+/// sibling[31] ^= 1;
+/// assert_ne!(handle_short_id(&handle), handle_short_id(&sibling));
+/// assert_ne!(handle_short_id(&handle), [7u8; 8]);
+///
+/// for handle in [[0u8; 32], [0xff; 32], handle] {
+///     let id = handle_short_id_base58(&handle);
+///     assert!((1..=11).contains(&id.len()));
+///     assert!(!id.contains(['0', 'O', 'I', 'l']));
+/// }
+/// ```
+pub fn handle_short_id_base58(handle: &Handle) -> String {
+    let short_id = handle_short_id(handle);
+    let mut value = u64::from_be_bytes(short_id);
+    let mut digits = Vec::with_capacity(11);
+    while value > 0 {
+        digits.push(BASE58_ALPHABET[(value % 58) as usize]);
+        value /= 58;
+    }
+    // base58 keeps each leading zero byte as a leading '1'
+    let leading_zeros = short_id.iter().take_while(|&&b| b == 0).count();
+    digits.extend(std::iter::repeat_n(b'1', leading_zeros));
+    digits.reverse();
+    String::from_utf8(digits).expect("base58 alphabet is ASCII")
+}
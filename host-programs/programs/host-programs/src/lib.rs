@@ -1,20 +1,83 @@
 // ⚠️ WARNING: This is a minimal implementation for on-chain event logging testing.
 // This is NOT production code. Current design:
 // - Permissionless event emission (CCIP logging layer)
-// - No handle ownership verification (to be added later)
-// - No handle registry (to be added later)
-// - Pure stateless event machine for testing
+// - No handle ownership verification on op requests (to be added later)
+// - Optional handle registry: per-handle HandleRecord PDAs with parents,
+//   provenance and registration bonds
+// - State is an admin Config PDA plus opt-in PDAs: per-caller nonce,
+//   stats, activity (throttle) and dedup cache, the work cursor,
+//   accumulators and export records
 //
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
 
+pub mod accounting;
+pub mod anomaly;
+pub mod builder;
+pub mod compact;
+#[cfg(feature = "cpi")]
+pub mod cpi_builder;
+pub mod dag;
+pub mod discriminators;
+pub mod envelope;
+pub mod errors;
 pub mod events;
+pub mod external;
 pub mod handle;
+pub mod limits;
+pub mod math;
+pub mod opcode;
+pub mod origin;
+pub mod plan;
+pub mod profile;
+pub mod provenance;
+pub mod receipt;
+pub mod registry;
+pub mod request;
+pub mod state;
 pub mod types;
 
+use crate::accounting::emit_lamports_moved;
+use crate::compact::emit_packed_requests;
+use crate::errors::*;
+use crate::envelope::*;
 use crate::events::*;
 use crate::handle::*;
+use crate::limits::*;
+use crate::math::bump_counter;
+use crate::origin::{origin_program, throttle_key};
+use crate::profile::measure;
+use crate::provenance::*;
+use crate::receipt::*;
+use crate::registry::*;
+use crate::request::*;
+use crate::state::*;
 use crate::types::*;
 
+/// Borrows the accounts shared by every single-op request instruction out of
+/// `ctx`, whichever `Accounts` struct the instruction declares.
+macro_rules! request_accounts {
+    ($ctx:expr) => {
+        RequestAccounts {
+            caller: $ctx.accounts.caller.key(),
+            caller_signed: $ctx.accounts.caller.is_signer,
+            config: &$ctx.accounts.config,
+            work_cursor: &mut $ctx.accounts.work_cursor,
+            nonce_account: &mut $ctx.accounts.nonce_account,
+            dedup_cache: &mut $ctx.accounts.dedup_cache,
+            activity: &mut $ctx.accounts.activity,
+            instructions: $ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref()),
+            result_record: $ctx.accounts.result_record.as_deref(),
+            operand_records: $ctx.remaining_accounts,
+        }
+    };
+}
+
 declare_id!("FkLGYGk2bypUXgpGmcsCTmKZo6LCjHaXswbhY1LNGAKj");
 
 #[program]
@@ -24,8 +87,747 @@ pub mod host_programs {
     // -------------------------------------------------------------------
     // Program Initialization
     // -------------------------------------------------------------------
-    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
-        msg!("FHE16 Executor initialized: {:?}", _ctx.program_id);
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        msg!("FHE16 Executor initialized: {:?}", ctx.program_id);
+
+        // 공용 상수 0 / 1 / max: program 소유 record 로 한 번만 등록
+        let program_id = *ctx.program_id;
+        let payer = ctx.accounts.payer.key();
+        let slot = Clock::get()?.slot;
+        let accounts = &mut *ctx.accounts;
+        for (record, value, bump) in [
+            (&mut accounts.zero_record, FHE16_CONST_ZERO, ctx.bumps.zero_record),
+            (&mut accounts.one_record, FHE16_CONST_ONE, ctx.bumps.one_record),
+            (&mut accounts.max_record, FHE16_CONST_MAX, ctx.bumps.max_record),
+        ] {
+            record.init_constant(constant_handle(value, &program_id), program_id, payer, slot, bump);
+        }
+        Ok(())
+    }
+
+    /// 공용 상수 0 / 1 / true / false record 를 한 번에 준비. 이미 있는 record 는
+    /// 건드리지 않으므로 몇 번을 호출해도 결과는 같다. 단 program 소유의 root
+    /// record 가 아니면 HandleRecordConflict 로 실패
+    pub fn bootstrap_constants(ctx: Context<BootstrapConstants>) -> Result<()> {
+        let program_id = *ctx.program_id;
+        let payer = ctx.accounts.payer.key();
+        let slot = Clock::get()?.slot;
+        let zero = constant_handle(FHE16_CONST_ZERO, &program_id);
+        let one = constant_handle(FHE16_CONST_ONE, &program_id);
+        let true_handle = boolean_constant_handle(true, &program_id);
+        let false_handle = boolean_constant_handle(false, &program_id);
+
+        let accounts = &mut *ctx.accounts;
+        let mut created = 0u8;
+        for (record, handle, bump) in [
+            (&mut accounts.zero_record, zero, ctx.bumps.zero_record),
+            (&mut accounts.one_record, one, ctx.bumps.one_record),
+            (&mut accounts.true_record, true_handle, ctx.bumps.true_record),
+            (&mut accounts.false_record, false_handle, ctx.bumps.false_record),
+        ] {
+            // init_if_needed 가 방금 만든 record 는 handle 이 비어 있다
+            if record.handle == [0u8; 32] {
+                record.init_constant(handle, program_id, payer, slot, bump);
+                created += 1;
+            } else {
+                // 이미 있는 record 는 program 소유의 root 상수여야 한다
+                require!(
+                    record.handle == handle
+                        && record.owner == program_id
+                        && record.parent_count == 0,
+                    ExecutorError::HandleRecordConflict
+                );
+            }
+        }
+
+        emit!(ConstantsBootstrapped {
+            zero,
+            one,
+            true_handle,
+            false_handle,
+            created,
+        });
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // Program Config (admin-managed)
+    // -------------------------------------------------------------------
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.emit_events = true;
+        config.max_cpi_depth = DEFAULT_MAX_CPI_DEPTH;
+        config.strict_mode = false;
+        config.tag_allowlist = Vec::new();
+        config.frozen_handles = Vec::new();
+        config.templates_enabled = false;
+        config.strict_select = true;
+        config.domain_salt = NO_DOMAIN_SALT;
+        config.anomaly_soft_fail = false;
+        config.event_format = EVENT_FORMAT_LEGACY;
+        config.compact_events = false;
+        config.registration_bond_lamports = 0;
+        config.bond_release_slots = MIN_BOND_RELEASE_SLOTS;
+        config.treasury = config.admin;
+        config.window_slots = 1;
+        config.max_requests_per_window = 0;
+        config.strictness = DEFAULT_STRICTNESS;
+        config.bump = ctx.bumps.config;
+        emit!(ConfigUpdated {
+            field: ConfigField::Initialized,
+            old: Vec::new(),
+            new: config.try_to_vec()?,
+            admin: config.admin,
+        });
+
+        let work_cursor = &mut ctx.accounts.work_cursor;
+        work_cursor.total_requests = 0;
+        work_cursor.last_request_slot = 0;
+        work_cursor.bump = ctx.bumps.work_cursor;
+        Ok(())
+    }
+
+    /// false 이면 op 요청은 handle 만 derive 해서 return_data 로 돌려준다 (derive-only)
+    pub fn set_emit_events(ctx: Context<UpdateConfig>, emit_events: bool) -> Result<()> {
+        let old = ctx.accounts.config.emit_events;
+        ctx.accounts.config.emit_events = emit_events;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::EmitEvents,
+            &old,
+            &emit_events,
+        )
+    }
+
+    pub fn set_strict_mode(ctx: Context<UpdateConfig>, strict_mode: bool) -> Result<()> {
+        let old = ctx.accounts.config.strict_mode;
+        ctx.accounts.config.strict_mode = strict_mode;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::StrictMode,
+            &old,
+            &strict_mode,
+        )
+    }
+
+    /// 1 = top-level only, 2 = user -> dapp -> host (default)
+    pub fn set_max_cpi_depth(ctx: Context<UpdateConfig>, max_cpi_depth: u8) -> Result<()> {
+        require!(
+            (1..=MAX_STACK_HEIGHT).contains(&max_cpi_depth),
+            ExecutorError::MaxCpiDepthOutOfRange
+        );
+        let old = ctx.accounts.config.max_cpi_depth;
+        ctx.accounts.config.max_cpi_depth = max_cpi_depth;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::MaxCpiDepth,
+            &old,
+            &max_cpi_depth,
+        )
+    }
+
+    pub fn set_strict_select(ctx: Context<UpdateConfig>, strict_select: bool) -> Result<()> {
+        let old = ctx.accounts.config.strict_select;
+        ctx.accounts.config.strict_select = strict_select;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::StrictSelect,
+            &old,
+            &strict_select,
+        )
+    }
+
+    pub fn set_templates_enabled(ctx: Context<UpdateConfig>, templates_enabled: bool) -> Result<()> {
+        let old = ctx.accounts.config.templates_enabled;
+        ctx.accounts.config.templates_enabled = templates_enabled;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::TemplatesEnabled,
+            &old,
+            &templates_enabled,
+        )
+    }
+
+    pub fn set_anomaly_soft_fail(
+        ctx: Context<UpdateConfig>,
+        anomaly_soft_fail: bool,
+    ) -> Result<()> {
+        let old = ctx.accounts.config.anomaly_soft_fail;
+        ctx.accounts.config.anomaly_soft_fail = anomaly_soft_fail;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::AnomalySoftFail,
+            &old,
+            &anomaly_soft_fail,
+        )
+    }
+
+    /// indexer 세대에 맞춰 event layout 선택 (EVENT_FORMAT_*)
+    pub fn set_event_format(ctx: Context<UpdateConfig>, event_format: u8) -> Result<()> {
+        require!(
+            event_format <= EVENT_FORMAT_CATEGORIZED,
+            ExecutorError::UnknownEventFormat
+        );
+        let old = ctx.accounts.config.event_format;
+        ctx.accounts.config.event_format = event_format;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::EventFormat,
+            &old,
+            &event_format,
+        )
+    }
+
+    /// true 이면 fold 등 다중 요청 instruction 이 packed event 를 남긴다
+    pub fn set_compact_events(ctx: Context<UpdateConfig>, compact_events: bool) -> Result<()> {
+        let old = ctx.accounts.config.compact_events;
+        ctx.accounts.config.compact_events = compact_events;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::CompactEvents,
+            &old,
+            &compact_events,
+        )
+    }
+
+    /// bond_lamports = 0 이면 bond 없이 등록 (기본값).
+    /// bond_release_slots 는 MIN_BOND_RELEASE_SLOTS 이상
+    pub fn set_registration_bond(
+        ctx: Context<UpdateConfig>,
+        bond_lamports: u64,
+        bond_release_slots: u64,
+    ) -> Result<()> {
+        require!(
+            bond_release_slots >= MIN_BOND_RELEASE_SLOTS,
+            ExecutorError::BondReleaseTooShort
+        );
+        let config = &mut ctx.accounts.config;
+        let old = (config.registration_bond_lamports, config.bond_release_slots);
+        config.registration_bond_lamports = bond_lamports;
+        config.bond_release_slots = bond_release_slots;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::RegistrationBond,
+            &old,
+            &(bond_lamports, bond_release_slots),
+        )
+    }
+
+    pub fn set_treasury(ctx: Context<UpdateConfig>, treasury: Pubkey) -> Result<()> {
+        let old = ctx.accounts.config.treasury;
+        ctx.accounts.config.treasury = treasury;
+        emit_config_updated(ctx.accounts.admin.key(), ConfigField::Treasury, &old, &treasury)
+    }
+
+    /// caller 별 window_slots slot 당 최대 max_requests_per_window 요청 (0 = 제한 없음,
+    /// window_slots = 1 이면 slot 단위). 진행 중인 window 는 이전 설정을 유지
+    pub fn set_rate_limit(
+        ctx: Context<UpdateConfig>,
+        window_slots: u64,
+        max_requests_per_window: u32,
+    ) -> Result<()> {
+        require!(window_slots >= 1, ExecutorError::InvalidThrottleWindow);
+        let config = &mut ctx.accounts.config;
+        let old = (config.window_slots, config.max_requests_per_window);
+        config.window_slots = window_slots;
+        config.max_requests_per_window = max_requests_per_window;
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::RateLimit,
+            &old,
+            &(window_slots, max_requests_per_window),
+        )
+    }
+
+    /// 요청 검사 전체의 강도를 바꾼다. 개별 플래그는 그대로 두며
+    /// Config::policy() 가 프로필과 플래그를 합쳐 해석한다 (caller 서명, bond 하한,
+    /// throttle 기본값 포함). 진행 중인 throttle window 는 이전 설정을 유지
+    pub fn set_strictness(ctx: Context<UpdateConfig>, profile: StrictnessProfile) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let old = config.strictness;
+        config.strictness = profile;
+
+        emit!(StrictnessChanged {
+            admin: ctx.accounts.admin.key(),
+            old_profile: old,
+            new_profile: profile,
+            slot: Clock::get()?.slot,
+        });
+        emit_config_updated(ctx.accounts.admin.key(), ConfigField::Strictness, &old, &profile)
+    }
+
+    /// 이후 요청부터 새 salt 로 derive. 이미 발급된 handle 은 그대로 유효하며
+    /// executor 는 DomainSaltRotated 로 전환 시점을 알 수 있다
+    pub fn rotate_domain_salt(ctx: Context<UpdateConfig>, new_salt: DomainSalt) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let old_salt = config.domain_salt;
+        config.domain_salt = new_salt;
+
+        emit!(DomainSaltRotated {
+            admin: ctx.accounts.admin.key(),
+            old_salt,
+            new_salt,
+            slot: Clock::get()?.slot,
+        });
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::DomainSalt,
+            &old_salt,
+            &new_salt,
+        )
+    }
+
+    /// 빈 목록이면 모든 client_tag 허용 (기본값)
+    pub fn set_tag_allowlist(
+        ctx: Context<UpdateConfig>,
+        prefixes: Vec<[u8; TAG_PREFIX_LEN]>,
+    ) -> Result<()> {
+        validate_len(prefixes.len(), MAX_TAG_PREFIXES, ExecutorError::TooManyTagPrefixes)?;
+        let old = std::mem::replace(&mut ctx.accounts.config.tag_allowlist, prefixes);
+        emit_config_updated(
+            ctx.accounts.admin.key(),
+            ConfigField::TagAllowlist,
+            &old,
+            &ctx.accounts.config.tag_allowlist,
+        )
+    }
+
+    // -------------------------------------------------------------------
+    // Replay Protection: per-caller nonce account
+    // -------------------------------------------------------------------
+    pub fn init_caller_nonce(ctx: Context<InitCallerNonce>) -> Result<()> {
+        let nonce_account = &mut ctx.accounts.nonce_account;
+        nonce_account.caller = ctx.accounts.caller.key();
+        nonce_account.last_nonce = 0;
+        nonce_account.bump = ctx.bumps.nonce_account;
+        Ok(())
+    }
+
+    pub fn init_dedup_cache(ctx: Context<InitDedupCache>) -> Result<()> {
+        let cache = &mut ctx.accounts.dedup_cache;
+        cache.caller = ctx.accounts.caller.key();
+        cache.slot = 0;
+        cache.handles = [[0u8; 32]; DEDUP_CACHE_LEN];
+        cache.len = 0;
+        cache.next = 0;
+        cache.bump = ctx.bumps.dedup_cache;
+        Ok(())
+    }
+
+    pub fn init_caller_activity(ctx: Context<InitCallerActivity>) -> Result<()> {
+        let activity = &mut ctx.accounts.activity;
+        activity.caller = ctx.accounts.caller.key();
+        activity.window_start_slot = 0;
+        activity.window_slots = 0;
+        activity.max_requests = 0;
+        activity.count = 0;
+        activity.bump = ctx.bumps.activity;
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // Caller Stats: per-caller counters (opt-in)
+    // -------------------------------------------------------------------
+    pub fn init_caller_stats(ctx: Context<InitCallerStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.stats;
+        stats.caller = ctx.accounts.caller.key();
+        stats.registered_inputs = 0;
+        stats.bump = ctx.bumps.stats;
+        Ok(())
+    }
+
+    /// 등록된 input handle 수 (return data)
+    pub fn get_caller_stats(ctx: Context<GetCallerStats>) -> Result<u64> {
+        Ok(ctx.accounts.stats.registered_inputs)
+    }
+
+    // -------------------------------------------------------------------
+    // Handle Registry (opt-in bookkeeping of the computation graph)
+    // -------------------------------------------------------------------
+    /// 부모 없는 root handle (암호화된 input 등) 의 record. 부모가 있는 record 는
+    /// register_derived 로만 만든다 (parents 를 on-chain 에서 derive). 공용 상수
+    /// record 는 initialize / bootstrap_constants 만 만든다
+    pub fn create_handle_record(ctx: Context<CreateHandleRecord>, handle: Handle) -> Result<()> {
+        require!(!is_constant_handle(&handle, ctx.program_id), ExecutorError::ReservedHandle);
+
+        let record = &mut ctx.accounts.record;
+        record.handle = handle;
+        record.owner = ctx.accounts.caller.key();
+        record.payer = ctx.accounts.payer.key();
+        record.parent_count = 0;
+        record.op_code = None;
+        record.domain_salt = NO_DOMAIN_SALT;
+        record.ref_count = 0;
+        record.created_slot = Clock::get()?.slot;
+        record.provenance = HandleProvenance::Local;
+        record.source_program = *ctx.program_id;
+        record.frozen = false;
+        record.bond_lamports = 0;
+        record.first_used_slot = None;
+        record.bump = ctx.bumps.record;
+        record.seal_provenance();
+        Ok(())
+    }
+
+    /// client 가 off-chain 에서 미리 계산한 handle 등록: on-chain 에서 다시 derive 해서
+    /// 일치할 때만 operands 를 parents 로 하는 record 생성 (parent record 는
+    /// remaining_accounts 로, operands 순서대로).
+    /// derive 결과를 parent 없는 root record 로 선점한 경우 그 record 를 대체한다
+    /// (선점자의 rent / bond 는 새 payer 에게 넘어감). 이미 parents 가 있는 record 가
+    /// 이 derivation 과 다르면 derivation 버그이므로 ProvenanceMismatch
+    pub fn register_derived<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterDerived<'info>>,
+        request: OpRequestV1,
+        claimed_handle: Handle,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let request = OpRequest::try_from(request)?;
+        request.validate(config)?;
+        let derived = measure("register_derived", || {
+            request.derive_result(ctx.program_id, &config.domain_salt)
+        })?;
+        require!(derived == claimed_handle, ExecutorError::HandleMismatch);
+
+        // init_if_needed 가 방금 만든 record 는 handle 이 비어 있다
+        let parents = request.operands();
+        let record = &mut ctx.accounts.record;
+        let squatted = record.handle == claimed_handle;
+        if squatted && record.parent_count > 0 {
+            require!(
+                record.op_code == request.op_code()
+                    && record.parents() == parents.as_slice()
+                    && record.domain_salt == config.domain_salt,
+                ExecutorError::ProvenanceMismatch
+            );
+            return err!(ExecutorError::HandleRecordConflict);
+        }
+        require!(
+            !squatted
+                || (record.parent_count == 0 && record.provenance == HandleProvenance::Local),
+            ExecutorError::HandleRecordConflict
+        );
+
+        validate_len(parents.len(), MAX_HANDLE_PARENTS, ExecutorError::TooManyParents)?;
+        let parent_count =
+            u8::try_from(parents.len()).map_err(|_| error!(ExecutorError::TooManyParents))?;
+        let parent_roots = update_parent_ref_counts(
+            ctx.remaining_accounts,
+            &parents,
+            RefChange::Acquire,
+            ctx.program_id,
+        )?;
+
+        let record = &mut ctx.accounts.record;
+        if !squatted {
+            record.ref_count = 0;
+            record.frozen = false;
+            record.bond_lamports = 0;
+            record.first_used_slot = None;
+            record.bump = ctx.bumps.record;
+        }
+        record.handle = claimed_handle;
+        record.owner = ctx.accounts.caller.key();
+        record.payer = ctx.accounts.payer.key();
+        record.parents[..parents.len()].copy_from_slice(&parents);
+        record.parent_roots[..parents.len()].copy_from_slice(&parent_roots);
+        record.parent_count = parent_count;
+        record.op_code = request.op_code();
+        record.domain_salt = config.domain_salt;
+        record.created_slot = Clock::get()?.slot;
+        record.provenance = HandleProvenance::Derived;
+        record.source_program = *ctx.program_id;
+        record.seal_provenance();
+        Ok(())
+    }
+
+    /// 다른 record 가 parent 로 참조 중이면 (ref_count > 0) close 불가
+    pub fn close_handle_record<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseHandleRecord<'info>>,
+    ) -> Result<()> {
+        let record = &ctx.accounts.record;
+        require!(record.ref_count == 0, ExecutorError::HandleInUse);
+        let bond = record.bond_lamports;
+        require!(
+            bond == 0 || record.bond_releasable(&ctx.accounts.config, Clock::get()?.slot),
+            ExecutorError::BondLocked
+        );
+
+        update_parent_ref_counts(
+            ctx.remaining_accounts,
+            record.parents(),
+            RefChange::Release,
+            ctx.program_id,
+        )?;
+
+        // 실제 이동은 `close = payer` 가 수행 → record 의 현재 잔액 전부 (bond 포함)
+        let payer = ctx.accounts.payer.key();
+        if bond > 0 {
+            emit_lamports_moved(MoveReason::Refund, record.key(), payer, bond, Some(record.handle));
+        }
+        emit_lamports_moved(
+            MoveReason::RentReclaim,
+            record.key(),
+            payer,
+            record.to_account_info().lamports() - bond,
+            Some(record.handle),
+        );
+        Ok(())
+    }
+
+    /// bond 반환: release 지연 (bond_release_slots) 이 지난 뒤.
+    /// 받는 쪽은 record 의 payer 로 고정이므로 누구나 호출 가능
+    pub fn release_bond(ctx: Context<ReleaseBond>) -> Result<()> {
+        let record = &mut ctx.accounts.record;
+        let bond = record.bond_lamports;
+        require!(bond > 0, ExecutorError::NoBond);
+        require!(
+            record.bond_releasable(&ctx.accounts.config, Clock::get()?.slot),
+            ExecutorError::BondLocked
+        );
+
+        record.bond_lamports = 0;
+        record.sub_lamports(bond)?;
+        ctx.accounts.payer.add_lamports(bond)?;
+        emit_lamports_moved(
+            MoveReason::Refund,
+            record.key(),
+            ctx.accounts.payer.key(),
+            bond,
+            Some(record.handle),
+        );
+        Ok(())
+    }
+
+    /// Spam 정리 (admin): parent 로도 요청 operand 로도 쓰인 적 없고 bond_release_slots 가
+    /// 지난 record 를 닫는다. bond 는 treasury 로 몰수, 나머지 rent 는 payer 에게
+    pub fn collect_unused_record<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CollectUnusedRecord<'info>>,
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.record;
+        require!(record.ref_count == 0, ExecutorError::HandleInUse);
+        require!(record.first_used_slot.is_none(), ExecutorError::RecordUsed);
+        require!(
+            record.release_delay_elapsed(&ctx.accounts.config, Clock::get()?.slot),
+            ExecutorError::RecordTooRecent
+        );
+
+        update_parent_ref_counts(
+            ctx.remaining_accounts,
+            record.parents(),
+            RefChange::Release,
+            ctx.program_id,
+        )?;
+
+        let bond = record.bond_lamports;
+        if bond > 0 {
+            record.bond_lamports = 0;
+            record.sub_lamports(bond)?;
+            ctx.accounts.treasury.add_lamports(bond)?;
+            emit_lamports_moved(
+                MoveReason::Slash,
+                record.key(),
+                ctx.accounts.treasury.key(),
+                bond,
+                Some(record.handle),
+            );
+        }
+        emit_lamports_moved(
+            MoveReason::RentReclaim,
+            record.key(),
+            ctx.accounts.payer.key(),
+            record.to_account_info().lamports(),
+            Some(record.handle),
+        );
+        Ok(())
+    }
+
+    /// Incident response: 악성 / 깨진 ciphertext 로 등록된 handle 의 전파를 막는다.
+    /// 이후 operand 로 쓰는 요청과 parent 로 삼는 record 생성이 HandleFrozen 으로 실패.
+    /// record 가 없는 handle 도 config 목록만으로 freeze 할 수 있다
+    pub fn freeze_handle(
+        ctx: Context<SetHandleFrozen>,
+        handle: Handle,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let frozen_handles = &mut ctx.accounts.config.frozen_handles;
+        if !frozen_handles.contains(&handle) {
+            validate_len(
+                frozen_handles.len() + 1,
+                MAX_FROZEN_HANDLES,
+                ExecutorError::TooManyFrozenHandles,
+            )?;
+            frozen_handles.push(handle);
+        }
+        if let Some(record) = ctx.accounts.record.as_mut() {
+            record.frozen = true;
+        }
+
+        emit!(HandleFrozen {
+            handle,
+            reason_hash,
+            admin: ctx.accounts.admin.key(),
+        });
+        Ok(())
+    }
+
+    pub fn unfreeze_handle(ctx: Context<SetHandleFrozen>, handle: Handle) -> Result<()> {
+        ctx.accounts.config.frozen_handles.retain(|frozen| *frozen != handle);
+        if let Some(record) = ctx.accounts.record.as_mut() {
+            record.frozen = false;
+        }
+
+        emit!(HandleUnfrozen {
+            handle,
+            admin: ctx.accounts.admin.key(),
+        });
+        Ok(())
+    }
+
+    /// SELECT 분기 디버깅용: record 가 parents 로부터 derive 된 SELECT 이고
+    /// condition (첫 operand) 이 `expected_condition` 인지 확인 (record 의 salt 기준)
+    pub fn assert_condition_source(
+        ctx: Context<AssertConditionSource>,
+        select_handle: Handle,
+        expected_condition: Handle,
+    ) -> Result<()> {
+        let record = &ctx.accounts.record;
+        let [condition, if_true, if_false] = record.parents() else {
+            return err!(ExecutorError::NotSelectRecord);
+        };
+        let derived = derive_ternary_handle(
+            Fhe16TernaryOp::Select,
+            condition,
+            if_true,
+            if_false,
+            ctx.program_id,
+            &record.domain_salt,
+        );
+        require!(derived == select_handle, ExecutorError::NotSelectRecord);
+        require!(
+            *condition == expected_condition,
+            ExecutorError::ConditionSourceMismatch
+        );
+
+        emit!(ConditionSourceConfirmed {
+            select_handle,
+            condition_handle: *condition,
+        });
+        Ok(())
+    }
+
+    /// 제3자 검증용: request 의 result handle 이 hash 하는 preimage 그대로
+    /// (sha256(preimage) == handle, 현재 salt 기준)
+    pub fn derivation_preimage(
+        ctx: Context<DerivationPreimage>,
+        request: OpRequestV1,
+    ) -> Result<Vec<u8>> {
+        let request = OpRequest::try_from(request)?;
+        let salt = &ctx.accounts.config.domain_salt;
+        request.derive_result(ctx.program_id, salt)?;
+        request
+            .derivation_preimage(ctx.program_id, salt)
+            .ok_or(error!(ExecutorError::NoDerivationPreimage))
+    }
+
+    // -------------------------------------------------------------------
+    // Cross-deployment Handle Export / Import
+    // -------------------------------------------------------------------
+    /// record 와 remaining_accounts 로 받은 조상 record 들로 provenance proof 생성
+    /// (view 용; 조상이 빠지면 partial)
+    pub fn prove_provenance(ctx: Context<ProveProvenance>) -> Result<ProvenanceProof> {
+        build_provenance_proof(&ctx.accounts.record, ctx.remaining_accounts, ctx.program_id)
+    }
+
+    /// export record 를 남겨 importer 가 source 배포의 PDA 로 확인할 수 있게 한다
+    pub fn export_handle(ctx: Context<ExportHandle>) -> Result<ExportReceipt> {
+        let record = &ctx.accounts.record;
+        let receipt = ExportReceipt {
+            handle: record.handle,
+            source_program: *ctx.program_id,
+            owner: record.owner,
+            proof: derive_export_proof(&record.handle, &record.owner, ctx.program_id),
+        };
+
+        let export_record = &mut ctx.accounts.export_record;
+        export_record.handle = receipt.handle;
+        export_record.owner = receipt.owner;
+        export_record.proof = receipt.proof;
+        export_record.exported_slot = Clock::get()?.slot;
+        export_record.bump = ctx.bumps.export_record;
+
+        emit!(HandleExported {
+            handle: receipt.handle,
+            source_program: receipt.source_program,
+            owner: receipt.owner,
+            proof: receipt.proof,
+        });
+
+        Ok(receipt)
+    }
+
+    pub fn import_handle(
+        ctx: Context<ImportHandle>,
+        handle: Handle,
+        source_program: Pubkey,
+        owner: Pubkey,
+        proof: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            derive_export_proof(&handle, &owner, &source_program) == proof,
+            ExecutorError::InvalidExportProof
+        );
+
+        // source 배포가 직접 쓴 PDA 들만 신뢰: handle record 와 export_handle 의 export record
+        require!(source_program != *ctx.program_id, ExecutorError::InvalidSourceRecord);
+        let source_record: HandleRecord = load_foreign_pda(
+            ctx.accounts.source_record.as_ref(),
+            &source_program,
+            HANDLE_RECORD_SEED,
+            &handle,
+            |record: &HandleRecord| record.bump,
+        )?;
+        require!(
+            source_record.handle == handle && source_record.owner == owner,
+            ExecutorError::InvalidSourceRecord
+        );
+        let source_export: ExportRecord = load_foreign_pda(
+            ctx.accounts.source_export.as_ref(),
+            &source_program,
+            EXPORT_RECORD_SEED,
+            &handle,
+            |record: &ExportRecord| record.bump,
+        )?;
+        require!(
+            source_export.handle == handle
+                && source_export.owner == owner
+                && source_export.proof == proof,
+            ExecutorError::InvalidExportProof
+        );
+
+        let record = &mut ctx.accounts.record;
+        record.handle = handle;
+        record.owner = owner;
+        record.payer = ctx.accounts.payer.key();
+        record.parent_count = 0;
+        record.op_code = None;
+        record.domain_salt = NO_DOMAIN_SALT;
+        record.ref_count = 0;
+        record.created_slot = Clock::get()?.slot;
+        record.provenance = HandleProvenance::Imported;
+        record.source_program = source_program;
+        record.frozen = false;
+        record.bond_lamports = 0;
+        record.first_used_slot = None;
+        record.bump = ctx.bumps.record;
+        record.seal_provenance();
+
+        emit!(HandleImported {
+            handle,
+            source_program,
+            owner,
+        });
+
         Ok(())
     }
 
@@ -37,41 +839,139 @@ pub mod host_programs {
         handle: Handle,
         client_tag: [u8; 32],
     ) -> Result<()> {
+        ctx.accounts.config.check_cpi_depth()?;
+        let tag = ClientTag::from(client_tag);
+        require!(!tag.is_reserved(), ExecutorError::ReservedClientTag);
+        ctx.accounts.config.check_client_tag(&client_tag)?;
+        ctx.accounts.config.check_not_frozen(&[handle])?;
+
+        // bond 가 켜져 있으면 handle record 를 만들어 bond 를 예치 (spam 억제)
+        let bond = ctx.accounts.config.policy().registration_bond_lamports;
+        if bond > 0 || ctx.accounts.record.is_some() {
+            require!(!is_constant_handle(&handle, ctx.program_id), ExecutorError::ReservedHandle);
+            let accounts = &mut *ctx.accounts;
+            let (Some(record), Some(payer), Some(system)) = (
+                accounts.record.as_mut(),
+                accounts.payer.as_ref(),
+                accounts.system_program.as_ref(),
+            ) else {
+                return err!(ExecutorError::MissingBondAccounts);
+            };
+            if bond > 0 {
+                transfer(
+                    CpiContext::new(
+                        system.to_account_info(),
+                        Transfer {
+                            from: payer.to_account_info(),
+                            to: record.to_account_info(),
+                        },
+                    ),
+                    bond,
+                )?;
+                emit_lamports_moved(
+                    MoveReason::Bond,
+                    payer.key(),
+                    record.key(),
+                    bond,
+                    Some(handle),
+                );
+            }
+            record.handle = handle;
+            record.owner = accounts.caller.key();
+            record.payer = payer.key();
+            record.parent_count = 0;
+            record.op_code = None;
+            record.domain_salt = NO_DOMAIN_SALT;
+            record.ref_count = 0;
+            record.created_slot = Clock::get()?.slot;
+            record.provenance = HandleProvenance::Local;
+            record.source_program = *ctx.program_id;
+            record.frozen = false;
+            record.bond_lamports = bond;
+            record.first_used_slot = None;
+            record.bump = ctx.bumps.record.unwrap_or_default();
+            record.seal_provenance();
+        }
+
+        let caller_signed = ctx.accounts.caller.is_signer;
+        ctx.accounts.config.check_caller_signed(caller_signed)?;
+        let instructions = ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref());
+        record_caller_activity(&mut ctx.accounts.activity, &ctx.accounts.config, 1, || {
+            throttle_key(ctx.accounts.caller.key(), caller_signed, instructions, ctx.program_id)
+        })?;
+        let seq = ctx.accounts.work_cursor.record_requests(1)?;
+        if let Some(stats) = ctx.accounts.stats.as_mut() {
+            bump_counter(&mut stats.registered_inputs);
+        }
         let caller = ctx.accounts.caller.key();
+        let origin = origin_program(
+            ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref()),
+            ctx.program_id,
+        )?;
 
+        ctx.accounts.config.emit_request_meta(seq)?;
         emit!(InputHandleRegistered {
             caller,
             handle,
             client_tag,
+            is_labeled: tag.is_labeled(),
+            origin_program: origin,
         });
 
         Ok(())
     }
 
-    // -------------------------------------------------------------------
-    // 2) Unary Operations (NOT, ABS, NEG, ...)
-    // -------------------------------------------------------------------
-    pub fn request_unary_op(
-        ctx: Context<RequestUnaryOp>,
-        op: Fhe16UnaryOp,
-        input_handle: Handle,
+    /// Migration 용 escape hatch: depth / reserved tag / allowlist 검사를 모두
+    /// 건너뛴다. admin 서명 필수, 사용 시 AdminOverrideUsed 를 함께 남긴다.
+    pub fn admin_register_input_handle(
+        ctx: Context<AdminRegisterInputHandle>,
+        handle: Handle,
+        client_tag: [u8; 32],
     ) -> Result<()> {
+        let seq = ctx.accounts.work_cursor.record_requests(1)?;
+        let admin = ctx.accounts.admin.key();
         let caller = ctx.accounts.caller.key();
+        msg!("admin override: register_input_handle by {}", admin);
 
-        // handle 생성 (immutable, deterministic)
-        let result_handle = derive_unary_handle(op, &input_handle, ctx.program_id);
-
-        // 이벤트 → executor 가 이 job 을 비동기 처리
-        emit!(Fhe16UnaryOpRequested {
+        ctx.accounts.config.emit_request_meta(seq)?;
+        emit!(InputHandleRegistered {
             caller,
-            op,
-            input_handle,
-            result_handle,
+            handle,
+            client_tag,
+            is_labeled: ClientTag::from(client_tag).is_labeled(),
+            origin_program: *ctx.program_id,
+        });
+        emit!(AdminOverrideUsed {
+            admin,
+            caller,
+            handle,
         });
 
         Ok(())
     }
 
+    // -------------------------------------------------------------------
+    // 2) Unary Operations (NOT, ABS, NEG, ...)
+    // -------------------------------------------------------------------
+    pub fn request_unary_op(
+        ctx: Context<RequestUnaryOp>,
+        op: Fhe16UnaryOp,
+        input_handle: Handle,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_request(
+            request_accounts!(ctx),
+            ctx.program_id,
+            OpRequest::Unary {
+                op,
+                input: input_handle,
+            },
+            caller_nonce,
+            dry_run,
+        )
+    }
+
     // -------------------------------------------------------------------
     // 3) Binary Operations (ADD, SUB, AND, OR, MAX, LT ...)
     // -------------------------------------------------------------------
@@ -80,21 +980,20 @@ pub mod host_programs {
         op: Fhe16BinaryOp,
         lhs_handle: Handle,
         rhs_handle: Handle,
-    ) -> Result<()> {
-        let caller = ctx.accounts.caller.key();
-
-        let result_handle =
-            derive_binary_handle(op, &lhs_handle, &rhs_handle, ctx.program_id);
-
-        emit!(Fhe16BinaryOpRequested {
-            caller,
-            op,
-            lhs_handle,
-            rhs_handle,
-            result_handle,
-        });
-
-        Ok(())
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_request(
+            request_accounts!(ctx),
+            ctx.program_id,
+            OpRequest::Binary {
+                op,
+                lhs: lhs_handle,
+                rhs: rhs_handle,
+            },
+            caller_nonce,
+            dry_run,
+        )
     }
 
     // -------------------------------------------------------------------
@@ -106,24 +1005,586 @@ pub mod host_programs {
         a_handle: Handle,
         b_handle: Handle,
         c_handle: Handle,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_request(
+            request_accounts!(ctx),
+            ctx.program_id,
+            OpRequest::Ternary {
+                op,
+                a: a_handle,
+                b: b_handle,
+                c: c_handle,
+            },
+            caller_nonce,
+            dry_run,
+        )
+    }
+
+    // -------------------------------------------------------------------
+    // 5) Width Padding (W8 -> W16 promotion)
+    // -------------------------------------------------------------------
+    pub fn request_pad(
+        ctx: Context<RequestPad>,
+        input_handle: Handle,
+        target_width: u8,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_request(
+            request_accounts!(ctx),
+            ctx.program_id,
+            OpRequest::Pad {
+                input: input_handle,
+                target_width,
+            },
+            caller_nonce,
+            dry_run,
+        )
+    }
+
+    // -------------------------------------------------------------------
+    // 5b) Width Truncation (W16 -> W8 downcast)
+    // -------------------------------------------------------------------
+    pub fn request_truncate(
+        ctx: Context<RequestTruncate>,
+        input_handle: Handle,
+        target_width: u8,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_request(
+            request_accounts!(ctx),
+            ctx.program_id,
+            OpRequest::Truncate {
+                input: input_handle,
+                target_width,
+            },
+            caller_nonce,
+            dry_run,
+        )
+    }
+
+    // -------------------------------------------------------------------
+    // 5c) Width Concatenation (two W8 halves -> W16, lo 가 하위 비트)
+    // -------------------------------------------------------------------
+    pub fn request_concat(
+        ctx: Context<RequestConcat>,
+        lo_handle: Handle,
+        hi_handle: Handle,
+        half_width: u8,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_request(
+            request_accounts!(ctx),
+            ctx.program_id,
+            OpRequest::Concat {
+                lo: lo_handle,
+                hi: hi_handle,
+                half_width,
+            },
+            caller_nonce,
+            dry_run,
+        )
+    }
+
+    // -------------------------------------------------------------------
+    // 5d) Generic Request (tagged union over every op arity)
+    // -------------------------------------------------------------------
+    /// 새 arity 는 OpRequestV1 의 reserved variant 를 채워 추가한다. 기존
+    /// per-arity event 를 그대로 남기므로 indexer 는 바뀌지 않는다
+    pub fn request_op(
+        ctx: Context<RequestOp>,
+        request: OpRequestV1,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_request(
+            request_accounts!(ctx),
+            ctx.program_id,
+            request.try_into()?,
+            caller_nonce,
+            dry_run,
+        )
+    }
+
+    // -------------------------------------------------------------------
+    // 6) Request Envelope (many requests, one instruction)
+    // -------------------------------------------------------------------
+    pub fn submit_envelope(
+        ctx: Context<SubmitEnvelope>,
+        payload: Vec<u8>,
+        dry_run: bool,
     ) -> Result<()> {
+        validate_len(payload.len(), MAX_ENVELOPE_BYTES, ExecutorError::EnvelopeTooLarge)?;
+        ctx.accounts.config.check_cpi_depth()?;
+        let requests = decode_envelope(&payload).map_err(|e| {
+            msg!("Malformed envelope at byte offset {}", e.offset);
+            ExecutorError::MalformedEnvelope
+        })?;
+
+        for request in &requests {
+            request.check_supported(&ctx.accounts.config, ctx.accounts.caller.key())?;
+            request.validate(&ctx.accounts.config)?;
+        }
+        let result_handles = measure("submit_envelope", || {
+            requests
+                .iter()
+                .map(|request| {
+                    request.derive_result(ctx.program_id, &ctx.accounts.config.domain_salt)
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        if dry_run {
+            return finish_dry_run(&result_handles);
+        }
+        let count = requests.len() as u64;
+        let caller_signed = ctx.accounts.caller.is_signer;
+        ctx.accounts.config.check_caller_signed(caller_signed)?;
+        let operands: Vec<Handle> = requests.iter().flat_map(OpRequest::operands).collect();
+        mark_operands_used(ctx.remaining_accounts, &operands, ctx.program_id)?;
+        let instructions = ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref());
+        record_caller_activity(&mut ctx.accounts.activity, &ctx.accounts.config, count, || {
+            throttle_key(ctx.accounts.caller.key(), caller_signed, instructions, ctx.program_id)
+        })?;
+        let first_seq = ctx.accounts.work_cursor.record_requests(count)?;
+
+        if ctx.accounts.config.emit_events {
+            let origin = origin_program(
+                ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref()),
+                ctx.program_id,
+            )?;
+            ctx.accounts.config.emit_request_meta(first_seq)?;
+            emit!(SequenceReserved { first_seq, count });
+            emit!(Fhe16EnvelopeRequested {
+                caller: ctx.accounts.caller.key(),
+                payload,
+                result_handles,
+                origin_program: origin,
+            });
+        }
+
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // 7) Fold: balanced-tree reduction with an associative op
+    // -------------------------------------------------------------------
+    pub fn request_fold(
+        ctx: Context<RequestFold>,
+        op: Fhe16BinaryOp,
+        handles: Vec<Handle>,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        validate_len(handles.len(), MAX_NARY_INPUTS, ExecutorError::TooManyFoldInputs)?;
+        require!(handles.len() >= 2, ExecutorError::NotEnoughFoldInputs);
+        require!(op.is_associative(), ExecutorError::NonAssociativeOp);
+        ctx.accounts.config.check_cpi_depth()?;
+        ctx.accounts.config.check_not_frozen(&handles)?;
+
+        // 각 단계는 일반 binary 요청과 동일 → executor 는 binary job 으로 처리
+        let steps = measure("request_fold", || {
+            Ok(derive_fold_steps(
+                op,
+                &handles,
+                ctx.program_id,
+                &ctx.accounts.config.domain_salt,
+            ))
+        })?;
+        for (lhs, rhs, _) in &steps {
+            ctx.accounts.config.check_binary_operands(op, lhs, rhs)?;
+        }
+        let result_handle = steps[steps.len() - 1].2;
+
+        if dry_run {
+            check_caller_nonce(&ctx.accounts.nonce_account, caller_nonce)?;
+            return finish_dry_run(&result_handle);
+        }
+        let caller_signed = ctx.accounts.caller.is_signer;
+        ctx.accounts.config.check_caller_signed(caller_signed)?;
+        consume_caller_nonce(&mut ctx.accounts.nonce_account, caller_nonce, caller_signed)?;
+        mark_operands_used(ctx.remaining_accounts, &handles, ctx.program_id)?;
+        let count = steps.len() as u64;
+        let instructions = ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref());
+        record_caller_activity(&mut ctx.accounts.activity, &ctx.accounts.config, count, || {
+            throttle_key(ctx.accounts.caller.key(), caller_signed, instructions, ctx.program_id)
+        })?;
+        let first_seq = ctx.accounts.work_cursor.record_requests(count)?;
+
         let caller = ctx.accounts.caller.key();
 
-        let result_handle =
-            derive_ternary_handle(op, &a_handle, &b_handle, &c_handle, ctx.program_id);
+        if ctx.accounts.config.emit_events {
+            let origin = origin_program(
+                ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref()),
+                ctx.program_id,
+            )?;
+            ctx.accounts.config.emit_request_meta(first_seq)?;
+            emit!(SequenceReserved { first_seq, count });
+            if ctx.accounts.config.compact_events {
+                let requests: Vec<_> = steps
+                    .iter()
+                    .map(|&(lhs, rhs, result)| (OpRequest::Binary { op, lhs, rhs }, result))
+                    .collect();
+                emit_packed_requests(&requests, caller, origin);
+            } else {
+                for (lhs_handle, rhs_handle, step_result) in steps {
+                    ctx.accounts.config.emit_binary_requested(
+                        caller,
+                        op,
+                        lhs_handle,
+                        rhs_handle,
+                        step_result,
+                        origin,
+                    );
+                }
+            }
+        }
 
-        emit!(Fhe16TernaryOpRequested {
-            caller,
-            op,
-            a_handle,
-            b_handle,
-            c_handle,
-            result_handle,
-        });
+        Ok(result_handle)
+    }
+
+    // -------------------------------------------------------------------
+    // 7b) Vector: element-wise op over two equally long handle arrays
+    // -------------------------------------------------------------------
+    pub fn request_vector_op(
+        ctx: Context<RequestVectorOp>,
+        op: Fhe16VectorOp,
+        lhs: Vec<Handle>,
+        rhs: Vec<Handle>,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Vec<Handle>> {
+        validate_len(lhs.len(), MAX_VECTOR_LEN, ExecutorError::InvalidVectorLength)?;
+        validate_len(rhs.len(), MAX_VECTOR_LEN, ExecutorError::InvalidVectorLength)?;
+        require!(
+            !lhs.is_empty() && lhs.len() == rhs.len(),
+            ExecutorError::InvalidVectorLength
+        );
+        let len =
+            u8::try_from(lhs.len()).map_err(|_| error!(ExecutorError::InvalidVectorLength))?;
+        ctx.accounts.config.check_cpi_depth()?;
+        ctx.accounts.config.check_not_frozen(&lhs)?;
+        ctx.accounts.config.check_not_frozen(&rhs)?;
+        for (lhs_element, rhs_element) in lhs.iter().zip(&rhs) {
+            ctx.accounts.config.check_binary_operands(op.element_op(), lhs_element, rhs_element)?;
+        }
+
+        // 원소마다 별도 job → 원소 index 와 길이가 handle 에 들어간다
+        let results = measure("request_vector_op", || {
+            derive_vector_handles(op, &lhs, &rhs, ctx.program_id, &ctx.accounts.config.domain_salt)
+        })?;
+
+        if dry_run {
+            check_caller_nonce(&ctx.accounts.nonce_account, caller_nonce)?;
+            return finish_dry_run(&results);
+        }
+        let caller_signed = ctx.accounts.caller.is_signer;
+        ctx.accounts.config.check_caller_signed(caller_signed)?;
+        consume_caller_nonce(&mut ctx.accounts.nonce_account, caller_nonce, caller_signed)?;
+        let operands = [lhs.as_slice(), &rhs].concat();
+        mark_operands_used(ctx.remaining_accounts, &operands, ctx.program_id)?;
+        let count = results.len() as u64;
+        let instructions = ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref());
+        record_caller_activity(&mut ctx.accounts.activity, &ctx.accounts.config, count, || {
+            throttle_key(ctx.accounts.caller.key(), caller_signed, instructions, ctx.program_id)
+        })?;
+        let first_seq = ctx.accounts.work_cursor.record_requests(count)?;
 
+        if ctx.accounts.config.emit_events {
+            let origin = origin_program(
+                ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref()),
+                ctx.program_id,
+            )?;
+            ctx.accounts.config.emit_request_meta(first_seq)?;
+            emit!(SequenceReserved { first_seq, count });
+            emit!(Fhe16VectorOpRequested {
+                caller: ctx.accounts.caller.key(),
+                op,
+                len,
+                lhs,
+                rhs,
+                results: results.clone(),
+                origin_program: origin,
+            });
+        }
+
+        Ok(results)
+    }
+
+    // -------------------------------------------------------------------
+    // 7c) Range check: AND(GE(x, lo), LE(x, hi))
+    // -------------------------------------------------------------------
+    pub fn request_range_check(
+        ctx: Context<RequestRangeCheck>,
+        x: Handle,
+        lo: Handle,
+        hi: Handle,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        ctx.accounts.config.check_cpi_depth()?;
+        let config = &ctx.accounts.config;
+        let salt = &config.domain_salt;
+
+        // 세 단계 모두 일반 binary 요청과 동일 → executor 는 binary job 3 개로 처리
+        let ge = OpRequest::Binary { op: Fhe16BinaryOp::Ge, lhs: x, rhs: lo };
+        let le = OpRequest::Binary { op: Fhe16BinaryOp::Le, lhs: x, rhs: hi };
+        let (ge_handle, le_handle, and, and_handle) = measure("request_range_check", || {
+            let ge_handle = ge.derive_result(ctx.program_id, salt)?;
+            let le_handle = le.derive_result(ctx.program_id, salt)?;
+            let and =
+                OpRequest::Binary { op: Fhe16BinaryOp::And, lhs: ge_handle, rhs: le_handle };
+            let and_handle = and.derive_result(ctx.program_id, salt)?;
+            Ok((ge_handle, le_handle, and, and_handle))
+        })?;
+        let requests = [(ge, ge_handle), (le, le_handle), (and, and_handle)];
+        for (request, _) in &requests {
+            request.validate(config)?;
+        }
+
+        if dry_run {
+            check_caller_nonce(&ctx.accounts.nonce_account, caller_nonce)?;
+            return finish_dry_run(&and_handle);
+        }
+        let caller_signed = ctx.accounts.caller.is_signer;
+        ctx.accounts.config.check_caller_signed(caller_signed)?;
+        consume_caller_nonce(&mut ctx.accounts.nonce_account, caller_nonce, caller_signed)?;
+        mark_operands_used(ctx.remaining_accounts, &[x, lo, hi], ctx.program_id)?;
+        let count = requests.len() as u64;
+        let instructions = ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref());
+        record_caller_activity(&mut ctx.accounts.activity, &ctx.accounts.config, count, || {
+            throttle_key(ctx.accounts.caller.key(), caller_signed, instructions, ctx.program_id)
+        })?;
+        let first_seq = ctx.accounts.work_cursor.record_requests(count)?;
+
+        let config = &ctx.accounts.config;
+        if config.emit_events {
+            let caller = ctx.accounts.caller.key();
+            let origin = origin_program(
+                ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref()),
+                ctx.program_id,
+            )?;
+            config.emit_request_meta(first_seq)?;
+            emit!(SequenceReserved { first_seq, count });
+            if config.compact_events {
+                emit_packed_requests(&requests, caller, origin);
+            } else {
+                for (request, result_handle) in requests {
+                    request.emit_requested(config, caller, result_handle, origin);
+                }
+            }
+        }
+
+        Ok(and_handle)
+    }
+
+    // -------------------------------------------------------------------
+    // 7d) Dot product: sum_i(w_i * x_i) as a single request
+    // -------------------------------------------------------------------
+    pub fn request_dot_product(
+        ctx: Context<RequestDotProduct>,
+        weights: Vec<Handle>,
+        values: Vec<Handle>,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_dot_product(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            DotWeights::Encrypted(weights),
+            values,
+            caller_nonce,
+            dry_run,
+        )
+    }
+
+    /// 평문 weight 버전: weight 값이 handle derivation 에 들어간다
+    pub fn request_scalar_dot_product(
+        ctx: Context<RequestDotProduct>,
+        weights: Vec<u16>,
+        values: Vec<Handle>,
+        caller_nonce: Option<u64>,
+        dry_run: bool,
+    ) -> Result<Handle> {
+        process_dot_product(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            ctx.program_id,
+            DotWeights::Scalar(weights),
+            values,
+            caller_nonce,
+            dry_run,
+        )
+    }
+
+    // -------------------------------------------------------------------
+    // 8) Accumulator: running encrypted total stored on-chain
+    // -------------------------------------------------------------------
+    pub fn create_accumulator(
+        ctx: Context<CreateAccumulator>,
+        tag: [u8; ACCUMULATOR_TAG_LEN],
+    ) -> Result<()> {
+        let accumulator = &mut ctx.accounts.accumulator;
+        accumulator.owner = ctx.accounts.owner.key();
+        accumulator.tag = tag;
+        accumulator.current_handle = constant_handle(FHE16_CONST_ZERO, ctx.program_id);
+        accumulator.is_public = false;
+        accumulator.accumulate_count = 0;
+        accumulator.bump = ctx.bumps.accumulator;
+        Ok(())
+    }
+
+    pub fn set_accumulator_public(
+        ctx: Context<SetAccumulatorPublic>,
+        is_public: bool,
+    ) -> Result<()> {
+        ctx.accounts.accumulator.is_public = is_public;
         Ok(())
     }
 
+    /// current = ADD(current, value). 일반 binary 요청과 같은 event 를 남기므로
+    /// executor 쪽 처리는 그대로
+    pub fn accumulate(ctx: Context<Accumulate>, value_handle: Handle) -> Result<Handle> {
+        ctx.accounts.config.check_cpi_depth()?;
+        let caller = ctx.accounts.caller.key();
+        ctx.accounts.accumulator.check_caller(&caller)?;
+        // caller 는 signer 이므로 throttle key 는 caller 자신
+        record_caller_activity(&mut ctx.accounts.activity, &ctx.accounts.config, 1, || Ok(caller))?;
+        let seq = ctx.accounts.work_cursor.record_requests(1)?;
+
+        let op = Fhe16BinaryOp::Add;
+        let lhs_handle = ctx.accounts.accumulator.current_handle;
+        ctx.accounts.config.check_not_frozen(&[lhs_handle, value_handle])?;
+        mark_operands_used(ctx.remaining_accounts, &[lhs_handle, value_handle], ctx.program_id)?;
+        let result_handle = measure("accumulate", || {
+            Ok(derive_binary_handle(
+                op,
+                &lhs_handle,
+                &value_handle,
+                ctx.program_id,
+                &ctx.accounts.config.domain_salt,
+            ))
+        })?;
+
+        let accumulator = &mut ctx.accounts.accumulator;
+        accumulator.current_handle = result_handle;
+        bump_counter(&mut accumulator.accumulate_count);
+
+        if ctx.accounts.config.emit_events {
+            let origin = origin_program(
+                ctx.accounts.instructions.as_ref().map(|ix| ix.as_ref()),
+                ctx.program_id,
+            )?;
+            ctx.accounts.config.emit_request_meta(seq)?;
+            ctx.accounts.config.emit_binary_requested(
+                caller,
+                op,
+                lhs_handle,
+                value_handle,
+                result_handle,
+                origin,
+            );
+        }
+
+        Ok(result_handle)
+    }
+
+    pub fn read_accumulator(ctx: Context<ReadAccumulator>) -> Result<Handle> {
+        Ok(ctx.accounts.accumulator.current_handle)
+    }
+
+    // -------------------------------------------------------------------
+    // 9) Transaction Receipt (last instruction of the transaction)
+    // -------------------------------------------------------------------
+    /// 앞선 top-level host 요청들을 순서대로 접은 receipt (return data)
+    pub fn tx_receipt(ctx: Context<TxReceipt>) -> Result<[u8; 32]> {
+        let instructions = ctx.accounts.instructions.as_ref();
+        let current = load_current_index_checked(instructions)? as usize;
+
+        let mut receipt = EMPTY_RECEIPT;
+        for index in 0..current {
+            let ix = load_instruction_at_checked(index, instructions)?;
+            if ix.program_id != *ctx.program_id {
+                continue;
+            }
+            for request in summarize_instruction(&ix.data)? {
+                receipt = fold_receipt(&receipt, &request)?;
+            }
+        }
+        Ok(receipt)
+    }
+}
+
+/// Body shared by the two dot product instructions. Every product and the
+/// sum are one executor job, so this is a single request with one event.
+fn process_dot_product(
+    accounts: &mut RequestDotProduct,
+    operand_records: &[AccountInfo],
+    program_id: &Pubkey,
+    weights: DotWeights,
+    values: Vec<Handle>,
+    caller_nonce: Option<u64>,
+    dry_run: bool,
+) -> Result<Handle> {
+    validate_len(weights.len(), MAX_VECTOR_LEN, ExecutorError::InvalidDotProductLength)?;
+    validate_len(values.len(), MAX_VECTOR_LEN, ExecutorError::InvalidDotProductLength)?;
+    require!(
+        values.len() >= 2 && weights.len() == values.len(),
+        ExecutorError::InvalidDotProductLength
+    );
+    accounts.config.check_cpi_depth()?;
+    if let DotWeights::Encrypted(weights) = &weights {
+        accounts.config.check_not_frozen(weights)?;
+    }
+    accounts.config.check_not_frozen(&values)?;
+
+    let instruction = match weights {
+        DotWeights::Encrypted(_) => "request_dot_product",
+        DotWeights::Scalar(_) => "request_scalar_dot_product",
+    };
+    let result_handle = measure(instruction, || {
+        derive_dot_product_handle(&weights, &values, program_id, &accounts.config.domain_salt)
+    })?;
+
+    if dry_run {
+        check_caller_nonce(&accounts.nonce_account, caller_nonce)?;
+        return finish_dry_run(&result_handle);
+    }
+    let caller_signed = accounts.caller.is_signer;
+    accounts.config.check_caller_signed(caller_signed)?;
+    consume_caller_nonce(&mut accounts.nonce_account, caller_nonce, caller_signed)?;
+    let mut operands = values.clone();
+    if let DotWeights::Encrypted(weights) = &weights {
+        operands.extend_from_slice(weights);
+    }
+    mark_operands_used(operand_records, &operands, program_id)?;
+    let instructions = accounts.instructions.as_ref().map(|ix| ix.as_ref());
+    record_caller_activity(&mut accounts.activity, &accounts.config, 1, || {
+        throttle_key(accounts.caller.key(), caller_signed, instructions, program_id)
+    })?;
+    let seq = accounts.work_cursor.record_requests(1)?;
+
+    if accounts.config.emit_events {
+        let origin = origin_program(
+            accounts.instructions.as_ref().map(|ix| ix.as_ref()),
+            program_id,
+        )?;
+        accounts.config.emit_request_meta(seq)?;
+        emit!(Fhe16DotProductRequested {
+            caller: accounts.caller.key(),
+            weights,
+            values,
+            result_handle,
+            origin_program: origin,
+        });
+    }
+
+    Ok(result_handle)
 }
 
 // -----------------------------------------------------------------------
@@ -135,29 +1596,880 @@ pub struct Initialize<'info> {
     #[account(executable)]
     /// CHECK: This is the program itself
     pub program: UncheckedAccount<'info>,
+    // 상수 record 는 init 이므로 두 번째 initialize 는 실패
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, constant_handle(FHE16_CONST_ZERO, &crate::ID).as_ref()],
+        bump,
+    )]
+    pub zero_record: Account<'info, HandleRecord>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, constant_handle(FHE16_CONST_ONE, &crate::ID).as_ref()],
+        bump,
+    )]
+    pub one_record: Account<'info, HandleRecord>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, constant_handle(FHE16_CONST_MAX, &crate::ID).as_ref()],
+        bump,
+    )]
+    pub max_record: Account<'info, HandleRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BootstrapConstants<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, constant_handle(FHE16_CONST_ZERO, &crate::ID).as_ref()],
+        bump,
+    )]
+    pub zero_record: Account<'info, HandleRecord>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, constant_handle(FHE16_CONST_ONE, &crate::ID).as_ref()],
+        bump,
+    )]
+    pub one_record: Account<'info, HandleRecord>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, boolean_constant_handle(true, &crate::ID).as_ref()],
+        bump,
+    )]
+    pub true_record: Account<'info, HandleRecord>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, boolean_constant_handle(false, &crate::ID).as_ref()],
+        bump,
+    )]
+    pub false_record: Account<'info, HandleRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + WorkCursor::INIT_SPACE,
+        seeds = [WORK_CURSOR_SEED],
+        bump,
+    )]
+    pub work_cursor: Account<'info, WorkCursor>,
+    /// Only the program's upgrade authority may claim the admin role, so a
+    /// front-runner cannot initialize the deployment first.
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ExecutorError::NotUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminRegisterInputHandle<'info> {
+    /// CHECK: 등록 대상 caller (migration 원본 주소)
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitCallerNonce<'info> {
+    /// CHECK: nonce 는 caller 주소 기준으로만 추적
+    pub caller: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CallerNonce::INIT_SPACE,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump,
+    )]
+    pub nonce_account: Account<'info, CallerNonce>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitDedupCache<'info> {
+    /// CHECK: cache 는 caller 주소 기준으로만 추적
+    pub caller: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + DedupCache::INIT_SPACE,
+        seeds = [DEDUP_CACHE_SEED, caller.key().as_ref()],
+        bump,
+    )]
+    pub dedup_cache: Account<'info, DedupCache>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitCallerActivity<'info> {
+    /// CHECK: throttle key (서명한 caller 또는 요청을 시작한 program), 주소만 사용
+    pub caller: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CallerActivity::INIT_SPACE,
+        seeds = [CALLER_ACTIVITY_SEED, caller.key().as_ref()],
+        bump,
+    )]
+    pub activity: Account<'info, CallerActivity>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+pub struct InitCallerStats<'info> {
+    /// CHECK: stats 는 caller 주소 기준으로만 추적
+    pub caller: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CallerStats::INIT_SPACE,
+        seeds = [CALLER_STATS_SEED, caller.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, CallerStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetCallerStats<'info> {
+    #[account(seeds = [CALLER_STATS_SEED, stats.caller.as_ref()], bump = stats.bump)]
+    pub stats: Account<'info, CallerStats>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: Handle)]
+pub struct CreateHandleRecord<'info> {
+    /// record owner: 서명 필수 (dapp program 은 PDA 로 invoke_signed)
+    pub caller: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, handle.as_ref()],
+        bump,
+    )]
+    pub record: Account<'info, HandleRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(request: OpRequestV1, claimed_handle: Handle)]
+pub struct RegisterDerived<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    /// record owner: 서명 필수 (dapp program 은 PDA 로 invoke_signed)
+    pub caller: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, claimed_handle.as_ref()],
+        bump,
+    )]
+    pub record: Account<'info, HandleRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseHandleRecord<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [HANDLE_RECORD_SEED, record.handle.as_ref()],
+        bump = record.bump,
+        has_one = payer,
+        close = payer,
+    )]
+    pub record: Account<'info, HandleRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseBond<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [HANDLE_RECORD_SEED, record.handle.as_ref()],
+        bump = record.bump,
+        has_one = payer,
+    )]
+    pub record: Account<'info, HandleRecord>,
+    /// CHECK: bond 를 돌려받는 record.payer (has_one 으로 검증)
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CollectUnusedRecord<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin, has_one = treasury)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [HANDLE_RECORD_SEED, record.handle.as_ref()],
+        bump = record.bump,
+        has_one = payer,
+        close = payer,
+    )]
+    pub record: Account<'info, HandleRecord>,
+    /// CHECK: rent 를 돌려받는 record.payer (has_one 으로 검증)
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    /// CHECK: config.treasury (has_one 으로 검증)
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: Handle)]
+pub struct SetHandleFrozen<'info> {
+    #[account(mut, seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    /// The handle's record, if it has one; its `frozen` flag follows the list.
+    #[account(mut, seeds = [HANDLE_RECORD_SEED, handle.as_ref()], bump = record.bump)]
+    pub record: Option<Account<'info, HandleRecord>>,
+}
+
+#[derive(Accounts)]
+#[instruction(select_handle: Handle)]
+pub struct AssertConditionSource<'info> {
+    #[account(seeds = [HANDLE_RECORD_SEED, select_handle.as_ref()], bump = record.bump)]
+    pub record: Account<'info, HandleRecord>,
+}
+
+#[derive(Accounts)]
+pub struct DerivationPreimage<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ProveProvenance<'info> {
+    pub record: Account<'info, HandleRecord>,
+}
+
+#[derive(Accounts)]
+pub struct ExportHandle<'info> {
+    #[account(
+        seeds = [HANDLE_RECORD_SEED, record.handle.as_ref()],
+        bump = record.bump,
+        has_one = owner,
+    )]
+    pub record: Account<'info, HandleRecord>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ExportRecord::INIT_SPACE,
+        seeds = [EXPORT_RECORD_SEED, record.handle.as_ref()],
+        bump,
+    )]
+    pub export_record: Account<'info, ExportRecord>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: Handle)]
+pub struct ImportHandle<'info> {
+    /// CHECK: source 배포의 handle record PDA (owner, seeds, 내용은 import_handle 에서 검증)
+    pub source_record: UncheckedAccount<'info>,
+    /// CHECK: source 배포의 export record PDA (source_record 와 같은 방식으로 검증)
+    pub source_export: UncheckedAccount<'info>,
+    // 이미 import 된 handle 은 init 단계에서 실패 (재-import 불가)
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, handle.as_ref()],
+        bump,
+    )]
+    pub record: Account<'info, HandleRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: Handle)]
 pub struct RegisterInputHandle<'info> {
     /// CHECK: user wallet, dapp program, PDA 등 모두 가능
     pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_STATS_SEED, caller.key().as_ref()],
+        bump = stats.bump,
+    )]
+    pub stats: Option<Account<'info, CallerStats>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// registration bond 가 켜져 있으면 필수: bond 를 예치할 handle record
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + HandleRecord::INIT_SPACE,
+        seeds = [HANDLE_RECORD_SEED, handle.as_ref()],
+        bump,
+    )]
+    pub record: Option<Account<'info, HandleRecord>>,
+    #[account(mut)]
+    pub payer: Option<Signer<'info>>,
+    pub system_program: Option<Program<'info, System>>,
 }
 
 #[derive(Accounts)]
 pub struct RequestUnaryOp<'info> {
     /// CHECK: signer 요구 없음 → Dapp CPI 허용
     pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    #[account(
+        mut,
+        seeds = [DEDUP_CACHE_SEED, caller.key().as_ref()],
+        bump = dedup_cache.bump,
+    )]
+    pub dedup_cache: Option<Account<'info, DedupCache>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Registry record of the result handle, if the caller knows one
+    /// exists; its parents must match the operands.
+    #[account(
+        seeds = [HANDLE_RECORD_SEED, result_record.handle.as_ref()],
+        bump = result_record.bump,
+    )]
+    pub result_record: Option<Account<'info, HandleRecord>>,
 }
 
 #[derive(Accounts)]
 pub struct RequestBinaryOp<'info> {
     /// CHECK
     pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    #[account(
+        mut,
+        seeds = [DEDUP_CACHE_SEED, caller.key().as_ref()],
+        bump = dedup_cache.bump,
+    )]
+    pub dedup_cache: Option<Account<'info, DedupCache>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Registry record of the result handle, if the caller knows one
+    /// exists; its parents must match the operands.
+    #[account(
+        seeds = [HANDLE_RECORD_SEED, result_record.handle.as_ref()],
+        bump = result_record.bump,
+    )]
+    pub result_record: Option<Account<'info, HandleRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestFold<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestVectorOp<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestRangeCheck<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestDotProduct<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
 pub struct RequestTernaryOp<'info> {
     /// CHECK
     pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    #[account(
+        mut,
+        seeds = [DEDUP_CACHE_SEED, caller.key().as_ref()],
+        bump = dedup_cache.bump,
+    )]
+    pub dedup_cache: Option<Account<'info, DedupCache>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Registry record of the result handle, if the caller knows one
+    /// exists; its parents must match the operands.
+    #[account(
+        seeds = [HANDLE_RECORD_SEED, result_record.handle.as_ref()],
+        bump = result_record.bump,
+    )]
+    pub result_record: Option<Account<'info, HandleRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestPad<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    #[account(
+        mut,
+        seeds = [DEDUP_CACHE_SEED, caller.key().as_ref()],
+        bump = dedup_cache.bump,
+    )]
+    pub dedup_cache: Option<Account<'info, DedupCache>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Registry record of the result handle, if the caller knows one
+    /// exists; its parents must match the operands.
+    #[account(
+        seeds = [HANDLE_RECORD_SEED, result_record.handle.as_ref()],
+        bump = result_record.bump,
+    )]
+    pub result_record: Option<Account<'info, HandleRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestTruncate<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    #[account(
+        mut,
+        seeds = [DEDUP_CACHE_SEED, caller.key().as_ref()],
+        bump = dedup_cache.bump,
+    )]
+    pub dedup_cache: Option<Account<'info, DedupCache>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Registry record of the result handle, if the caller knows one
+    /// exists; its parents must match the operands.
+    #[account(
+        seeds = [HANDLE_RECORD_SEED, result_record.handle.as_ref()],
+        bump = result_record.bump,
+    )]
+    pub result_record: Option<Account<'info, HandleRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct RequestConcat<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    #[account(
+        mut,
+        seeds = [DEDUP_CACHE_SEED, caller.key().as_ref()],
+        bump = dedup_cache.bump,
+    )]
+    pub dedup_cache: Option<Account<'info, DedupCache>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Registry record of the result handle, if the caller knows one
+    /// exists; its parents must match the operands.
+    #[account(
+        seeds = [HANDLE_RECORD_SEED, result_record.handle.as_ref()],
+        bump = result_record.bump,
+    )]
+    pub result_record: Option<Account<'info, HandleRecord>>,
 }
 
+#[derive(Accounts)]
+pub struct RequestOp<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    #[account(
+        mut,
+        seeds = [CALLER_NONCE_SEED, caller.key().as_ref()],
+        bump = nonce_account.bump,
+    )]
+    pub nonce_account: Option<Account<'info, CallerNonce>>,
+    #[account(
+        mut,
+        seeds = [DEDUP_CACHE_SEED, caller.key().as_ref()],
+        bump = dedup_cache.bump,
+    )]
+    pub dedup_cache: Option<Account<'info, DedupCache>>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// Registry record of the result handle, if the caller knows one
+    /// exists; its parents must match the operands.
+    #[account(
+        seeds = [HANDLE_RECORD_SEED, result_record.handle.as_ref()],
+        bump = result_record.bump,
+    )]
+    pub result_record: Option<Account<'info, HandleRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitEnvelope<'info> {
+    /// CHECK
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(tag: [u8; ACCUMULATOR_TAG_LEN])]
+pub struct CreateAccumulator<'info> {
+    /// Dapp 는 PDA 를 owner 로 두고 invoke_signed 로 서명
+    pub owner: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Accumulator::INIT_SPACE,
+        seeds = [ACCUMULATOR_SEED, owner.key().as_ref(), tag.as_ref()],
+        bump,
+    )]
+    pub accumulator: Account<'info, Accumulator>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAccumulatorPublic<'info> {
+    #[account(
+        mut,
+        seeds = [ACCUMULATOR_SEED, accumulator.owner.as_ref(), accumulator.tag.as_ref()],
+        bump = accumulator.bump,
+        has_one = owner,
+    )]
+    pub accumulator: Account<'info, Accumulator>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Accumulate<'info> {
+    /// accumulator 쓰기 권한 확인을 위해 signer 필요 (CPI 면 invoke_signed)
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ACCUMULATOR_SEED, accumulator.owner.as_ref(), accumulator.tag.as_ref()],
+        bump = accumulator.bump,
+    )]
+    pub accumulator: Account<'info, Accumulator>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut, seeds = [WORK_CURSOR_SEED], bump = work_cursor.bump)]
+    pub work_cursor: Account<'info, WorkCursor>,
+    /// Throttle window of `origin::throttle_key`, required while throttling
+    /// is on.
+    #[account(
+        mut,
+        seeds = [CALLER_ACTIVITY_SEED, activity.caller.as_ref()],
+        bump = activity.bump,
+    )]
+    pub activity: Option<Account<'info, CallerActivity>>,
+    /// CHECK: instructions sysvar (주소로 검증), origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ReadAccumulator<'info> {
+    #[account(
+        seeds = [ACCUMULATOR_SEED, accumulator.owner.as_ref(), accumulator.tag.as_ref()],
+        bump = accumulator.bump,
+    )]
+    pub accumulator: Account<'info, Accumulator>,
+}
+
+#[derive(Accounts)]
+pub struct TxReceipt<'info> {
+    /// CHECK: instructions sysvar (주소로 검증)
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
@@ -0,0 +1,26 @@
+// Caps on variable-length instruction arguments. Instructions check them
+// before any other work, so oversized payloads fail with a dedicated error
+// instead of somewhere deep in Borsh or on the BPF heap.
+use anchor_lang::prelude::*;
+
+use crate::errors::ExecutorError;
+
+/// Requests in one batch instruction (`submit_envelope`).
+pub const MAX_BATCH_OPS: usize = 32;
+
+/// Operands of an n-ary op.
+pub const MAX_NARY_INPUTS: usize = 8;
+
+/// Elements per operand of a vector op, and terms of a dot product.
+pub const MAX_VECTOR_LEN: usize = 8;
+
+/// Bytes of a `submit_envelope` payload; leaves room for the accounts and
+/// signature within a 1232-byte transaction.
+pub const MAX_ENVELOPE_BYTES: usize = 900;
+
+pub fn validate_len(actual: usize, max: usize, error: ExecutorError) -> Result<()> {
+    if actual > max {
+        return Err(error.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,26 @@
+// Counter arithmetic shared by every on-chain statistic. Counters saturate
+// at their maximum instead of panicking (debug) or wrapping (release).
+
+/// Increments `counter` by one, saturating at `u64::MAX`.
+pub fn bump_counter(counter: &mut u64) {
+    bump_counter_by(counter, 1);
+}
+
+/// Increments `counter` by `amount`, saturating at `u64::MAX`.
+///
+/// ```
+/// use host_programs::math::{bump_counter, bump_counter_by};
+///
+/// let mut counter = u64::MAX - 1;
+/// bump_counter(&mut counter);
+/// assert_eq!(counter, u64::MAX);
+/// bump_counter(&mut counter);
+/// assert_eq!(counter, u64::MAX);
+///
+/// let mut counter = 5;
+/// bump_counter_by(&mut counter, u64::MAX);
+/// assert_eq!(counter, u64::MAX);
+/// ```
+pub fn bump_counter_by(counter: &mut u64, amount: u64) {
+    *counter = counter.checked_add(amount).unwrap_or(u64::MAX);
+}
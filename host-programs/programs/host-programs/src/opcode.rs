@@ -0,0 +1,194 @@
+// Program-wide op-code registry. The `op as u8` byte hashed into handles is
+// only unique within its enum; an `OpCode` is unique on its own, so a
+// format carrying a bare code can never mistake a unary NOT for a binary
+// AND. Every family owns a 0x100-wide range, which makes collisions
+// impossible by construction; the const block at the bottom re-checks it
+// for every variant at compile time.
+use anchor_lang::prelude::*;
+
+use crate::request::OpRequest;
+use crate::types::{Fhe16BinaryOp, Fhe16TernaryOp, Fhe16UnaryOp};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(InitSpace)]
+pub struct OpCode(pub u16);
+
+/// Op families and the high byte of their codes. Scalar, shift, cast and
+/// nary have no ops yet; their ranges are reserved so adding them later
+/// cannot move existing codes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OpFamily {
+    Unary = 0x00,
+    Binary = 0x01,
+    Ternary = 0x02,
+    Scalar = 0x03,
+    Shift = 0x04,
+    Cast = 0x05,
+    Nary = 0x06,
+    /// PAD / TRUNCATE / CONCAT.
+    Width = 0x07,
+}
+
+impl OpFamily {
+    const ALL: [Self; 8] = [
+        OpFamily::Unary,
+        OpFamily::Binary,
+        OpFamily::Ternary,
+        OpFamily::Scalar,
+        OpFamily::Shift,
+        OpFamily::Cast,
+        OpFamily::Nary,
+        OpFamily::Width,
+    ];
+
+    /// First code of the family's range.
+    pub const fn base(self) -> u16 {
+        (self as u16) << 8
+    }
+}
+
+/// Index of PAD within `OpFamily::Width`.
+pub const WIDTH_PAD: u8 = 0;
+/// Index of TRUNCATE within `OpFamily::Width`.
+pub const WIDTH_TRUNCATE: u8 = 1;
+/// Index of CONCAT within `OpFamily::Width`.
+pub const WIDTH_CONCAT: u8 = 2;
+
+impl OpCode {
+    pub const fn new(family: OpFamily, index: u8) -> Self {
+        OpCode(family.base() | index as u16)
+    }
+
+    pub const fn unary(op: Fhe16UnaryOp) -> Self {
+        Self::new(OpFamily::Unary, op as u8)
+    }
+
+    pub const fn binary(op: Fhe16BinaryOp) -> Self {
+        Self::new(OpFamily::Binary, op as u8)
+    }
+
+    pub const fn ternary(op: Fhe16TernaryOp) -> Self {
+        Self::new(OpFamily::Ternary, op as u8)
+    }
+
+    /// `None` for codes in unassigned ranges.
+    pub fn family(self) -> Option<OpFamily> {
+        OpFamily::ALL.get((self.0 >> 8) as usize).copied()
+    }
+
+    /// Position within the family; the per-enum op byte for the op enums.
+    pub const fn index(self) -> u8 {
+        self.0 as u8
+    }
+}
+
+impl From<Fhe16UnaryOp> for OpCode {
+    fn from(op: Fhe16UnaryOp) -> Self {
+        OpCode::unary(op)
+    }
+}
+
+impl From<Fhe16BinaryOp> for OpCode {
+    fn from(op: Fhe16BinaryOp) -> Self {
+        OpCode::binary(op)
+    }
+}
+
+impl From<Fhe16TernaryOp> for OpCode {
+    fn from(op: Fhe16TernaryOp) -> Self {
+        OpCode::ternary(op)
+    }
+}
+
+impl TryFrom<OpCode> for Fhe16UnaryOp {
+    type Error = OpCode;
+
+    fn try_from(code: OpCode) -> core::result::Result<Self, OpCode> {
+        match code.family() {
+            Some(OpFamily::Unary) => Self::ALL.get(code.index() as usize).copied().ok_or(code),
+            _ => Err(code),
+        }
+    }
+}
+
+impl TryFrom<OpCode> for Fhe16BinaryOp {
+    type Error = OpCode;
+
+    fn try_from(code: OpCode) -> core::result::Result<Self, OpCode> {
+        match code.family() {
+            Some(OpFamily::Binary) => Self::ALL.get(code.index() as usize).copied().ok_or(code),
+            _ => Err(code),
+        }
+    }
+}
+
+impl TryFrom<OpCode> for Fhe16TernaryOp {
+    type Error = OpCode;
+
+    fn try_from(code: OpCode) -> core::result::Result<Self, OpCode> {
+        match code.family() {
+            Some(OpFamily::Ternary) => Self::ALL.get(code.index() as usize).copied().ok_or(code),
+            _ => Err(code),
+        }
+    }
+}
+
+impl OpRequest {
+    /// Registry code of the requested op; `None` for registrations.
+    pub fn op_code(&self) -> Option<OpCode> {
+        let code = match *self {
+            OpRequest::Register { .. } => return None,
+            OpRequest::Unary { op, .. } => OpCode::unary(op),
+            OpRequest::Binary { op, .. } => OpCode::binary(op),
+            OpRequest::Ternary { op, .. } => OpCode::ternary(op),
+            OpRequest::Pad { .. } => OpCode::new(OpFamily::Width, WIDTH_PAD),
+            OpRequest::Truncate { .. } => OpCode::new(OpFamily::Width, WIDTH_TRUNCATE),
+            OpRequest::Concat { .. } => OpCode::new(OpFamily::Width, WIDTH_CONCAT),
+        };
+        Some(code)
+    }
+}
+
+// Walks every variant of every op enum (plus the width ops): each must fit
+// its family's range and no two may share a code.
+const _: () = {
+    const COUNT: usize =
+        Fhe16UnaryOp::ALL.len() + Fhe16BinaryOp::ALL.len() + Fhe16TernaryOp::ALL.len() + 3;
+    let mut codes = [0u16; COUNT];
+    let mut n = 0;
+
+    let mut i = 0;
+    while i < Fhe16UnaryOp::ALL.len() {
+        assert!((Fhe16UnaryOp::ALL[i] as usize) < 0x100);
+        codes[n] = OpCode::unary(Fhe16UnaryOp::ALL[i]).0;
+        n += 1;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < Fhe16BinaryOp::ALL.len() {
+        assert!((Fhe16BinaryOp::ALL[i] as usize) < 0x100);
+        codes[n] = OpCode::binary(Fhe16BinaryOp::ALL[i]).0;
+        n += 1;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < Fhe16TernaryOp::ALL.len() {
+        assert!((Fhe16TernaryOp::ALL[i] as usize) < 0x100);
+        codes[n] = OpCode::ternary(Fhe16TernaryOp::ALL[i]).0;
+        n += 1;
+        i += 1;
+    }
+    codes[n] = OpCode::new(OpFamily::Width, WIDTH_PAD).0;
+    codes[n + 1] = OpCode::new(OpFamily::Width, WIDTH_TRUNCATE).0;
+    codes[n + 2] = OpCode::new(OpFamily::Width, WIDTH_CONCAT).0;
+
+    let mut a = 0;
+    while a < COUNT {
+        let mut b = a + 1;
+        while b < COUNT {
+            assert!(codes[a] != codes[b]);
+            b += 1;
+        }
+        a += 1;
+    }
+};
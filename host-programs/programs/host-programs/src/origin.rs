@@ -0,0 +1,76 @@
+// Attribution of host events to the program that started the transaction.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+use crate::errors::ExecutorError;
+
+/// Program of the top-level instruction that (directly or through CPIs)
+/// invoked the host. For top-level calls this is the host itself; nested
+/// calls need the instructions sysvar and report `Pubkey::default()`
+/// without it.
+pub fn origin_program(instructions: Option<&AccountInfo>, program_id: &Pubkey) -> Result<Pubkey> {
+    if get_stack_height() <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(*program_id);
+    }
+    let Some(instructions) = instructions else {
+        return Ok(Pubkey::default());
+    };
+    let index = load_current_index_checked(instructions)?;
+    let top_level = load_instruction_at_checked(index as usize, instructions)?;
+    Ok(top_level.program_id)
+}
+
+/// Address a request is throttled under: the caller if it signed, else the
+/// program that started the transaction. An unsigned caller can be any
+/// address, so keying on it would let one sender spread requests over
+/// fresh windows. Unsigned top-level requests, and nested ones without the
+/// instructions sysvar, have no such program and fail with
+/// `UnattributedRequest`.
+pub fn throttle_key(
+    caller: Pubkey,
+    caller_signed: bool,
+    instructions: Option<&AccountInfo>,
+    program_id: &Pubkey,
+) -> Result<Pubkey> {
+    if caller_signed {
+        return Ok(caller);
+    }
+    let origin = origin_program(instructions, program_id)?;
+    require!(
+        origin != *program_id && origin != Pubkey::default(),
+        ExecutorError::UnattributedRequest
+    );
+    Ok(origin)
+}
+
+/// Organic vs dapp-driven requests, as told by an event's `origin_program`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RegistrationOrigin {
+    /// The host instruction was top-level, i.e. signed for by a user.
+    User,
+    /// The host was invoked through `program`'s top-level instruction.
+    /// `Pubkey::default()` when the CPI did not pass the instructions sysvar.
+    Cpi { program: Pubkey },
+}
+
+impl RegistrationOrigin {
+    pub fn from_origin_program(origin_program: Pubkey) -> Self {
+        if origin_program == crate::ID {
+            RegistrationOrigin::User
+        } else {
+            RegistrationOrigin::Cpi {
+                program: origin_program,
+            }
+        }
+    }
+
+    pub fn cpi_program(&self) -> Option<Pubkey> {
+        match *self {
+            RegistrationOrigin::User => None,
+            RegistrationOrigin::Cpi { program } => Some(program),
+        }
+    }
+}
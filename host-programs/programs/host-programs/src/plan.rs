@@ -0,0 +1,291 @@
+// Off-chain prediction of multi-step request chains (e.g. lending-demo's
+// GE -> SUB -> SELECT withdraw) before any transaction is sent.
+use anchor_lang::prelude::*;
+
+use crate::errors::ExecutorError;
+use crate::events::Fhe16Event;
+use crate::handle::*;
+use crate::state::{has_request_meta, Config};
+use crate::types::{Fhe16BinaryOp, Fhe16TernaryOp, Fhe16UnaryOp, Handle};
+
+/// Operand of a planned step: an existing handle or the result of an
+/// earlier step in the same plan.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PlannedOperand {
+    Leaf(Handle),
+    Step(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PlannedStep {
+    Unary {
+        op: Fhe16UnaryOp,
+        input: PlannedOperand,
+    },
+    Binary {
+        op: Fhe16BinaryOp,
+        lhs: PlannedOperand,
+        rhs: PlannedOperand,
+    },
+    Ternary {
+        op: Fhe16TernaryOp,
+        a: PlannedOperand,
+        b: PlannedOperand,
+        c: PlannedOperand,
+    },
+    Pad {
+        input: PlannedOperand,
+        target_width: u8,
+    },
+}
+
+fn resolve(operand: PlannedOperand, step: usize, derived: &[Handle]) -> Result<Handle> {
+    match operand {
+        PlannedOperand::Leaf(handle) => Ok(handle),
+        PlannedOperand::Step(index) if index < step => Ok(derived[index]),
+        PlannedOperand::Step(_) => err!(ExecutorError::InvalidPlanReference),
+    }
+}
+
+/// Derives the result handle of every step, in order, exactly as the
+/// corresponding request instructions would under `salt` (the config's
+/// current `domain_salt`).
+///
+/// A step may only refer to earlier steps:
+///
+/// ```
+/// # use anchor_lang::prelude::Pubkey;
+/// # use host_programs::errors::ExecutorError;
+/// # use host_programs::handle::NO_DOMAIN_SALT;
+/// # use host_programs::plan::{plan_chain, PlannedStep};
+/// # use host_programs::plan::PlannedOperand::{Leaf, Step};
+/// # use host_programs::types::Fhe16BinaryOp;
+/// let program_id = Pubkey::new_unique();
+/// let forward = [
+///     PlannedStep::Binary { op: Fhe16BinaryOp::Add, lhs: Leaf([1; 32]), rhs: Step(1) },
+///     PlannedStep::Binary { op: Fhe16BinaryOp::Add, lhs: Leaf([1; 32]), rhs: Leaf([2; 32]) },
+/// ];
+/// let err = plan_chain(&program_id, &NO_DOMAIN_SALT, &forward).unwrap_err();
+/// assert_eq!(err, ExecutorError::InvalidPlanReference.into());
+///
+/// let own_result = [PlannedStep::Binary { op: Fhe16BinaryOp::Add, lhs: Step(0), rhs: Step(0) }];
+/// assert!(plan_chain(&program_id, &NO_DOMAIN_SALT, &own_result).is_err());
+/// ```
+pub fn plan_chain(
+    program_id: &Pubkey,
+    salt: &DomainSalt,
+    steps: &[PlannedStep],
+) -> Result<Vec<Handle>> {
+    let mut derived: Vec<Handle> = Vec::with_capacity(steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        let handle = match *step {
+            PlannedStep::Unary { op, input } => {
+                derive_unary_handle(op, &resolve(input, i, &derived)?, program_id, salt)
+            }
+            PlannedStep::Binary { op, lhs, rhs } => derive_binary_handle(
+                op,
+                &resolve(lhs, i, &derived)?,
+                &resolve(rhs, i, &derived)?,
+                program_id,
+                salt,
+            ),
+            PlannedStep::Ternary { op, a, b, c } => derive_ternary_handle(
+                op,
+                &resolve(a, i, &derived)?,
+                &resolve(b, i, &derived)?,
+                &resolve(c, i, &derived)?,
+                program_id,
+                salt,
+            ),
+            PlannedStep::Pad { input, target_width } => derive_pad_handle(
+                &resolve(input, i, &derived)?,
+                target_width,
+                program_id,
+                salt,
+            ),
+        };
+        derived.push(handle);
+    }
+    Ok(derived)
+}
+
+/// Host call of a pipeline whose events are being counted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PipelineCall {
+    /// One single-op request instruction (including CPI from a dapp).
+    Step(PlannedStep),
+    /// `request_fold` over `inputs` handles.
+    Fold { inputs: usize },
+    /// `request_vector_op`; one event for all element pairs.
+    Vector,
+    /// `request_range_check`: GE, LE and AND.
+    RangeCheck,
+    /// Either dot product instruction; a single request.
+    DotProduct,
+}
+
+/// The `Config` settings that decide how many events a request emits.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct EventSettings {
+    pub emit_events: bool,
+    pub event_format: u8,
+    /// Packed events stand in for op events one for one, so this changes
+    /// the events' layout but not their number.
+    pub compact_events: bool,
+}
+
+impl From<&Config> for EventSettings {
+    fn from(config: &Config) -> Self {
+        Self {
+            emit_events: config.emit_events,
+            event_format: config.event_format,
+            compact_events: config.compact_events,
+        }
+    }
+}
+
+/// Number of host events `pipeline` emits, so harnesses can assert on a
+/// composed instruction's logs (a dapp's own events come on top). A step
+/// has exactly one result and one op event; every format but the legacy
+/// one adds a `RequestMeta` per instruction, and multi-request instructions
+/// (fold, vector, range check) add a `SequenceReserved`. A fold or range
+/// check emits one op event per step, packed or not: `compact_events`
+/// swaps op events for packed ones one for one, so it does not change the
+/// count. Assumes no request is rejected or deduplicated.
+///
+/// ```
+/// # use host_programs::plan::{expected_event_count, EventSettings, PipelineCall, PlannedStep};
+/// # use host_programs::plan::PlannedOperand::{Leaf, Step};
+/// # use host_programs::state::*;
+/// # use host_programs::types::{Fhe16BinaryOp, Fhe16TernaryOp};
+/// let (balance, amount) = (Leaf([1; 32]), Leaf([2; 32]));
+/// // lending-demo withdraw: GE, SUB, SELECT(ge, sub, balance)
+/// let binary = |op| PlannedStep::Binary { op, lhs: balance, rhs: amount };
+/// let withdraw = [
+///     PipelineCall::Step(binary(Fhe16BinaryOp::Ge)),
+///     PipelineCall::Step(binary(Fhe16BinaryOp::Sub)),
+///     PipelineCall::Step(PlannedStep::Ternary {
+///         op: Fhe16TernaryOp::Select,
+///         a: Step(0),
+///         b: Step(1),
+///         c: balance,
+///     }),
+/// ];
+/// let fold = [PipelineCall::Fold { inputs: 4 }];
+///
+/// let mut settings = EventSettings {
+///     emit_events: true,
+///     event_format: EVENT_FORMAT_LEGACY,
+///     compact_events: false,
+/// };
+/// assert_eq!(expected_event_count(settings, &withdraw), 3);
+/// assert_eq!(expected_event_count(settings, &fold), 1 + 3);
+/// assert_eq!(expected_event_count(settings, &[PipelineCall::RangeCheck]), 1 + 3);
+///
+/// settings.event_format = EVENT_FORMAT_ENRICHED;
+/// assert_eq!(expected_event_count(settings, &withdraw), 6);
+/// assert_eq!(expected_event_count(settings, &fold), 1 + 1 + 3);
+/// settings.event_format = EVENT_FORMAT_CATEGORIZED;
+/// assert_eq!(expected_event_count(settings, &withdraw), 6);
+///
+/// // one packed event per fold step, not one for the whole fold
+/// settings.compact_events = true;
+/// assert_eq!(expected_event_count(settings, &fold), 1 + 1 + 3);
+/// assert_eq!(expected_event_count(settings, &[PipelineCall::RangeCheck]), 1 + 1 + 3);
+///
+/// settings.emit_events = false;
+/// assert_eq!(expected_event_count(settings, &withdraw), 0);
+/// ```
+pub fn expected_event_count(settings: EventSettings, pipeline: &[PipelineCall]) -> usize {
+    if !settings.emit_events {
+        return 0;
+    }
+    let meta = usize::from(has_request_meta(settings.event_format));
+    pipeline
+        .iter()
+        .map(|call| match *call {
+            PipelineCall::Step(_) | PipelineCall::DotProduct => meta + 1,
+            PipelineCall::Fold { inputs } => meta + 1 + inputs.saturating_sub(1),
+            PipelineCall::Vector => meta + 2,
+            PipelineCall::RangeCheck => meta + 1 + 3,
+        })
+        .sum()
+}
+
+/// Checks that the op events of a transaction (registrations are skipped)
+/// match `steps` one-to-one: same op, same operands, same result handle.
+///
+/// ```
+/// # use anchor_lang::prelude::Pubkey;
+/// # use host_programs::errors::ExecutorError;
+/// # use host_programs::events::{Fhe16BinaryOpRequested, Fhe16Event};
+/// # use host_programs::handle::NO_DOMAIN_SALT;
+/// # use host_programs::plan::{plan_chain, verify_chain_against_events, PlannedStep};
+/// # use host_programs::plan::PlannedOperand::Leaf;
+/// # use host_programs::types::Fhe16BinaryOp;
+/// let program_id = Pubkey::new_unique();
+/// let (lhs, rhs) = (Leaf([1; 32]), Leaf([2; 32]));
+/// let steps = [PlannedStep::Binary { op: Fhe16BinaryOp::Sub, lhs, rhs }];
+/// let plan = plan_chain(&program_id, &NO_DOMAIN_SALT, &steps).unwrap();
+/// let event = |result_handle| {
+///     Fhe16Event::BinaryOpRequested(Fhe16BinaryOpRequested {
+///         caller: Pubkey::new_unique(),
+///         op: Fhe16BinaryOp::Sub,
+///         lhs_handle: [1; 32],
+///         rhs_handle: [2; 32],
+///         result_handle,
+///         origin_program: program_id,
+///     })
+/// };
+/// assert!(verify_chain_against_events(&[event(plan[0])], &steps, &plan).is_ok());
+///
+/// // a different result handle, or a missing event, is a mismatch
+/// let forged = verify_chain_against_events(&[event([9; 32])], &steps, &plan).unwrap_err();
+/// assert_eq!(forged, ExecutorError::PlanMismatch.into());
+/// assert!(verify_chain_against_events(&[], &steps, &plan).is_err());
+/// ```
+pub fn verify_chain_against_events(
+    events: &[Fhe16Event],
+    steps: &[PlannedStep],
+    plan: &[Handle],
+) -> Result<()> {
+    let op_events: Vec<&Fhe16Event> = events
+        .iter()
+        .filter(|event| !matches!(event, Fhe16Event::InputHandleRegistered(_)))
+        .collect();
+    require!(
+        op_events.len() == steps.len() && plan.len() == steps.len(),
+        ExecutorError::PlanMismatch
+    );
+
+    for (i, (event, step)) in op_events.iter().zip(steps).enumerate() {
+        let matches = match (*event, *step) {
+            (Fhe16Event::UnaryOpRequested(e), PlannedStep::Unary { op, input }) => {
+                e.op == op
+                    && e.input_handle == resolve(input, i, plan)?
+                    && e.result_handle == plan[i]
+            }
+            (Fhe16Event::BinaryOpRequested(e), PlannedStep::Binary { op, lhs, rhs }) => {
+                e.op == op
+                    && e.lhs_handle == resolve(lhs, i, plan)?
+                    && e.rhs_handle == resolve(rhs, i, plan)?
+                    && e.result_handle == plan[i]
+            }
+            (Fhe16Event::TernaryOpRequested(e), PlannedStep::Ternary { op, a, b, c }) => {
+                e.op == op
+                    && e.a_handle == resolve(a, i, plan)?
+                    && e.b_handle == resolve(b, i, plan)?
+                    && e.c_handle == resolve(c, i, plan)?
+                    && e.result_handle == plan[i]
+            }
+            (Fhe16Event::PadRequested(e), PlannedStep::Pad { input, target_width }) => {
+                e.target_width == target_width
+                    && e.input_handle == resolve(input, i, plan)?
+                    && e.result_handle == plan[i]
+            }
+            _ => false,
+        };
+        require!(matches, ExecutorError::PlanMismatch);
+    }
+    Ok(())
+}
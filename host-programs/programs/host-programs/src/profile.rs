@@ -0,0 +1,41 @@
+// Compute-unit self-reporting for profiling without an external profiler.
+// Every request instruction measures its handle derivation, and the CPI
+// builders measure the whole call. Only builds with the `cu-profile`
+// feature measure anything; elsewhere `measure` is a plain call and
+// `CuUsed` is never emitted.
+use anchor_lang::prelude::*;
+
+#[cfg(feature = "cu-profile")]
+use crate::events::CuUsed;
+
+#[cfg(feature = "cu-profile")]
+fn remaining_compute_units() -> u64 {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        solana_define_syscall::definitions::sol_remaining_compute_units()
+    }
+
+    // 호스트 빌드 (doctest 등) 에는 compute meter 가 없다
+    #[cfg(not(target_os = "solana"))]
+    {
+        0
+    }
+}
+
+/// Runs `f` and emits `CuUsed` with the compute units it consumed.
+#[cfg(feature = "cu-profile")]
+pub fn measure<T>(instruction: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let before = remaining_compute_units();
+    let value = f()?;
+    emit!(CuUsed {
+        instruction: instruction.to_string(),
+        units: before.saturating_sub(remaining_compute_units()),
+    });
+    Ok(value)
+}
+
+#[cfg(not(feature = "cu-profile"))]
+#[inline(always)]
+pub fn measure<T>(_instruction: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    f()
+}
@@ -0,0 +1,243 @@
+// Self-contained provenance proofs for handle records, so a third party can
+// check "H was derived from these parents at slot S" without RPC access to
+// the registry. Every record commits to its lineage in `provenance_root`, a
+// hash over its own fields and its parents' roots. A proof is checked
+// against the root stored in the proven handle's record, which the verifier
+// reads from chain once; everything else in the proof is bound to it.
+use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+
+use crate::errors::ExecutorError;
+use crate::state::HandleRecord;
+use crate::types::Handle;
+
+const PROVENANCE_DOMAIN: &[u8] = b"FHE16_PROVENANCE_V1";
+
+/// Max records in one proof, keeping the worst case (three parents, none of
+/// them expanded) within the 1024-byte return data.
+pub const MAX_PROVENANCE_NODES: usize = 3;
+
+/// One record of the proof. `pruned_roots` are the roots of the parents not
+/// expanded elsewhere in the proof, in `parents` order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ProvenanceNode {
+    pub handle: Handle,
+    pub parents: Vec<Handle>,
+    pub created_slot: u64,
+    pub source_program: Pubkey,
+    pub pruned_roots: Vec<[u8; 32]>,
+}
+
+/// Nodes in breadth-first order from the proven handle. `partial` is set
+/// when some ancestor record was not available to the prover.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ProvenanceProof {
+    pub nodes: Vec<ProvenanceNode>,
+    pub partial: bool,
+}
+
+/// What a verified proof establishes about its first node.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProvenanceClaim {
+    pub handle: Handle,
+    pub parents: Vec<Handle>,
+    pub created_slot: u64,
+    pub partial: bool,
+}
+
+/// `HandleRecord::provenance_root` of a record with these fields. The
+/// parents' roots carry their lineage in turn.
+pub fn node_root(
+    handle: &Handle,
+    parents: &[Handle],
+    created_slot: u64,
+    source_program: &Pubkey,
+    parent_roots: &[[u8; 32]],
+) -> [u8; 32] {
+    let slot = created_slot.to_le_bytes();
+    let mut parts: Vec<&[u8]> = vec![PROVENANCE_DOMAIN, handle, &slot, source_program.as_ref()];
+    for (parent, root) in parents.iter().zip(parent_roots) {
+        parts.push(parent);
+        parts.push(root);
+    }
+    hashv(&parts).to_bytes()
+}
+
+/// Collects `record` and whichever of its ancestors appear in
+/// `ancestor_accounts` (any order), up to `MAX_PROVENANCE_NODES`.
+pub fn build_provenance_proof(
+    record: &HandleRecord,
+    ancestor_accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<ProvenanceProof> {
+    let mut ancestors = Vec::with_capacity(ancestor_accounts.len());
+    for account_info in ancestor_accounts {
+        require_keys_eq!(
+            *account_info.owner,
+            *program_id,
+            ExecutorError::MissingParentRecord
+        );
+        let data = account_info.try_borrow_data()?;
+        ancestors.push(HandleRecord::try_deserialize(&mut &data[..])?);
+    }
+
+    let mut records = vec![record];
+    let mut next = 0;
+    while next < records.len() {
+        let parents = records[next].parents();
+        next += 1;
+        for parent in parents {
+            if records.iter().any(|expanded| expanded.handle == *parent) {
+                continue;
+            }
+            if let Some(ancestor) = ancestors.iter().find(|ancestor| ancestor.handle == *parent) {
+                if records.len() < MAX_PROVENANCE_NODES {
+                    records.push(ancestor);
+                }
+            }
+        }
+    }
+
+    let nodes: Vec<ProvenanceNode> = records
+        .iter()
+        .map(|record| ProvenanceNode {
+            handle: record.handle,
+            parents: record.parents().to_vec(),
+            created_slot: record.created_slot,
+            source_program: record.source_program,
+            pruned_roots: record
+                .parents()
+                .iter()
+                .zip(record.parent_roots())
+                .filter(|(parent, _)| !records.iter().any(|r| r.handle == **parent))
+                .map(|(_, root)| *root)
+                .collect(),
+        })
+        .collect();
+    let partial = nodes.iter().any(|node| !node.pruned_roots.is_empty());
+    Ok(ProvenanceProof { nodes, partial })
+}
+
+/// Offline check of a serialized `ProvenanceProof` against `record`, the
+/// proven handle's `HandleRecord` as read from chain. Every node after the
+/// first must be a parent of an earlier node, and the roots rebuilt from
+/// the proof must reach `record.provenance_root`.
+///
+/// ```
+/// use anchor_lang::prelude::*;
+/// use host_programs::provenance::*;
+/// use host_programs::state::HandleRecord;
+///
+/// let program_id = Pubkey::new_unique();
+/// let record = |handle: [u8; 32], parents: &[&HandleRecord]| {
+///     let mut record = HandleRecord::try_deserialize_unchecked(&mut &[0u8; 512][..]).unwrap();
+///     record.init_constant(handle, program_id, program_id, 7, 255);
+///     for (i, parent) in parents.iter().enumerate() {
+///         record.parents[i] = parent.handle;
+///         record.parent_roots[i] = parent.provenance_root;
+///     }
+///     record.parent_count = parents.len() as u8;
+///     record.seal_provenance();
+///     record
+/// };
+/// let node = |record: &HandleRecord, pruned_roots: Vec<[u8; 32]>| ProvenanceNode {
+///     handle: record.handle,
+///     parents: record.parents().to_vec(),
+///     created_slot: record.created_slot,
+///     source_program: record.source_program,
+///     pruned_roots,
+/// };
+/// let (p, q) = (record([1; 32], &[]), record([2; 32], &[]));
+/// let r = record([3; 32], &[&p, &q]);
+///
+/// let full = ProvenanceProof {
+///     nodes: vec![node(&r, vec![]), node(&p, vec![]), node(&q, vec![])],
+///     partial: false,
+/// };
+/// let bytes = full.try_to_vec().unwrap();
+/// let claim = verify_provenance(&bytes, &r).unwrap();
+/// assert_eq!((claim.parents, claim.partial), (vec![p.handle, q.handle], false));
+///
+/// // q pruned to its root
+/// let partial = ProvenanceProof {
+///     nodes: vec![node(&r, vec![q.provenance_root]), node(&p, vec![])],
+///     partial: true,
+/// };
+/// assert!(verify_provenance(&partial.try_to_vec().unwrap(), &r).unwrap().partial);
+///
+/// // a tampered byte, or a root other than the one on chain, is rejected
+/// let mut tampered = bytes.clone();
+/// tampered[4 + 32 + 4] ^= 1;
+/// assert!(verify_provenance(&tampered, &r).is_err());
+/// assert!(verify_provenance(&bytes, &p).is_err());
+/// let mut forged = r.clone();
+/// forged.provenance_root = [0; 32];
+/// assert!(verify_provenance(&bytes, &forged).is_err());
+/// ```
+pub fn verify_provenance(bytes: &[u8], record: &HandleRecord) -> Result<ProvenanceClaim> {
+    let proof = ProvenanceProof::try_from_slice(bytes)
+        .map_err(|_| error!(ExecutorError::InvalidProvenanceProof))?;
+
+    let (first, rest) = proof
+        .nodes
+        .split_first()
+        .ok_or(ExecutorError::InvalidProvenanceProof)?;
+    for (i, node) in rest.iter().enumerate() {
+        let linked = proof.nodes[..=i]
+            .iter()
+            .any(|earlier| earlier.parents.contains(&node.handle));
+        require!(linked, ExecutorError::InvalidProvenanceProof);
+    }
+
+    let nodes = &proof.nodes;
+    let index_of = |handle: &Handle| nodes.iter().position(|node| node.handle == *handle);
+    for (i, node) in nodes.iter().enumerate() {
+        require!(index_of(&node.handle) == Some(i), ExecutorError::InvalidProvenanceProof);
+    }
+
+    // a node's root needs its expanded parents' roots first; one node per
+    // pass at least resolves unless the proof has a cycle
+    let mut roots: Vec<Option<[u8; 32]>> = vec![None; nodes.len()];
+    for _ in 0..nodes.len() {
+        for (i, node) in nodes.iter().enumerate() {
+            if roots[i].is_some() {
+                continue;
+            }
+            let mut pruned = node.pruned_roots.iter();
+            let mut parent_roots = Vec::with_capacity(node.parents.len());
+            for parent in &node.parents {
+                let root = match index_of(parent) {
+                    Some(j) => roots[j],
+                    None => Some(*pruned.next().ok_or(ExecutorError::InvalidProvenanceProof)?),
+                };
+                parent_roots.extend(root);
+            }
+            if parent_roots.len() < node.parents.len() {
+                continue;
+            }
+            require!(pruned.next().is_none(), ExecutorError::InvalidProvenanceProof);
+            roots[i] = Some(node_root(
+                &node.handle,
+                &node.parents,
+                node.created_slot,
+                &node.source_program,
+                &parent_roots,
+            ));
+        }
+    }
+
+    let partial = proof.nodes.iter().any(|node| !node.pruned_roots.is_empty());
+    require!(
+        first.handle == record.handle
+            && roots[0] == Some(record.provenance_root)
+            && proof.partial == partial,
+        ExecutorError::InvalidProvenanceProof
+    );
+
+    Ok(ProvenanceClaim {
+        handle: first.handle,
+        parents: first.parents.clone(),
+        created_slot: first.created_slot,
+        partial,
+    })
+}
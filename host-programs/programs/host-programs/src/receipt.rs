@@ -0,0 +1,235 @@
+// Per-transaction request receipts: a single order-sensitive 32-byte hash
+// over every host request in a transaction, cheap for integrators to store
+// and later re-derive from the requests they claim to have made.
+//
+//   receipt_0     = [0; 32]
+//   receipt_{i+1} = sha256(RECEIPT_DOMAIN || receipt_i || borsh(request_i))
+//
+// `tx_receipt` computes it on-chain from the instructions sysvar over the
+// top-level host instructions preceding it. Every request instruction is
+// covered; dry runs never land and are skipped. Requests a program makes
+// through CPI are not top-level instructions, so they are not part of the
+// receipt of the transaction that carries them.
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use solana_sha256_hasher::hashv;
+
+use crate::envelope::decode_envelope;
+use crate::errors::ExecutorError;
+use crate::instruction;
+use crate::request::OpRequest;
+use crate::types::{DotWeights, Fhe16BinaryOp, Fhe16VectorOp, Handle};
+
+const RECEIPT_DOMAIN: &[u8] = b"FHE16_RECEIPT_V2";
+
+/// Receipt of a transaction without host requests.
+pub const EMPTY_RECEIPT: [u8; 32] = [0u8; 32];
+
+/// One receipt entry. Instructions whose later steps depend on handles
+/// derived on-chain (fold, range check) or on account state (accumulate)
+/// are summarized by their arguments, as one entry.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ReceiptRequest {
+    Op(OpRequest),
+    Fold {
+        op: Fhe16BinaryOp,
+        handles: Vec<Handle>,
+    },
+    Vector {
+        op: Fhe16VectorOp,
+        lhs: Vec<Handle>,
+        rhs: Vec<Handle>,
+    },
+    RangeCheck {
+        x: Handle,
+        lo: Handle,
+        hi: Handle,
+    },
+    DotProduct {
+        weights: DotWeights,
+        values: Vec<Handle>,
+    },
+    Accumulate {
+        value: Handle,
+    },
+}
+
+impl From<OpRequest> for ReceiptRequest {
+    fn from(request: OpRequest) -> Self {
+        ReceiptRequest::Op(request)
+    }
+}
+
+pub fn fold_receipt(receipt: &[u8; 32], request: &ReceiptRequest) -> Result<[u8; 32]> {
+    let summary = request.try_to_vec()?;
+    Ok(hashv(&[RECEIPT_DOMAIN, receipt, &summary]).to_bytes())
+}
+
+pub fn compute_receipt(requests: &[ReceiptRequest]) -> Result<[u8; 32]> {
+    requests
+        .iter()
+        .try_fold(EMPTY_RECEIPT, |receipt, request| fold_receipt(&receipt, request))
+}
+
+/// Off-chain check that `requests`, in this order, produce `receipt`.
+pub fn verify_receipt(requests: &[ReceiptRequest], receipt: &[u8; 32]) -> bool {
+    compute_receipt(requests).is_ok_and(|computed| computed == *receipt)
+}
+
+fn decode_args<T: AnchorDeserialize>(mut args: &[u8]) -> Result<T> {
+    T::deserialize(&mut args).map_err(|_| error!(ExecutorError::MalformedInstruction))
+}
+
+/// Requests carried by one host instruction (`data` includes the 8-byte
+/// discriminator). Non-request instructions yield nothing.
+pub fn summarize_instruction(data: &[u8]) -> Result<Vec<ReceiptRequest>> {
+    if data.len() < 8 {
+        return Ok(Vec::new());
+    }
+    let (discriminator, args) = data.split_at(8);
+
+    let requests = if discriminator == instruction::RegisterInputHandle::DISCRIMINATOR {
+        let ix: instruction::RegisterInputHandle = decode_args(args)?;
+        vec![ReceiptRequest::Op(OpRequest::Register {
+            handle: ix.handle,
+            client_tag: ix.client_tag,
+        })]
+    } else if discriminator == instruction::RequestUnaryOp::DISCRIMINATOR {
+        let ix: instruction::RequestUnaryOp = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::Op(OpRequest::Unary {
+            op: ix.op,
+            input: ix.input_handle,
+        })]
+    } else if discriminator == instruction::RequestBinaryOp::DISCRIMINATOR {
+        let ix: instruction::RequestBinaryOp = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::Op(OpRequest::Binary {
+            op: ix.op,
+            lhs: ix.lhs_handle,
+            rhs: ix.rhs_handle,
+        })]
+    } else if discriminator == instruction::RequestTernaryOp::DISCRIMINATOR {
+        let ix: instruction::RequestTernaryOp = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::Op(OpRequest::Ternary {
+            op: ix.op,
+            a: ix.a_handle,
+            b: ix.b_handle,
+            c: ix.c_handle,
+        })]
+    } else if discriminator == instruction::RequestPad::DISCRIMINATOR {
+        let ix: instruction::RequestPad = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::Op(OpRequest::Pad {
+            input: ix.input_handle,
+            target_width: ix.target_width,
+        })]
+    } else if discriminator == instruction::RequestTruncate::DISCRIMINATOR {
+        let ix: instruction::RequestTruncate = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::Op(OpRequest::Truncate {
+            input: ix.input_handle,
+            target_width: ix.target_width,
+        })]
+    } else if discriminator == instruction::RequestConcat::DISCRIMINATOR {
+        let ix: instruction::RequestConcat = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::Op(OpRequest::Concat {
+            lo: ix.lo_handle,
+            hi: ix.hi_handle,
+            half_width: ix.half_width,
+        })]
+    } else if discriminator == instruction::RequestOp::DISCRIMINATOR {
+        let ix: instruction::RequestOp = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        let request = OpRequest::try_from(ix.request)
+            .map_err(|_| error!(ExecutorError::MalformedInstruction))?;
+        vec![request.into()]
+    } else if discriminator == instruction::SubmitEnvelope::DISCRIMINATOR {
+        let ix: instruction::SubmitEnvelope = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        decode_envelope(&ix.payload)
+            .map_err(|_| error!(ExecutorError::MalformedEnvelope))?
+            .into_iter()
+            .map(ReceiptRequest::from)
+            .collect()
+    } else if discriminator == instruction::AdminRegisterInputHandle::DISCRIMINATOR {
+        let ix: instruction::AdminRegisterInputHandle = decode_args(args)?;
+        vec![ReceiptRequest::Op(OpRequest::Register {
+            handle: ix.handle,
+            client_tag: ix.client_tag,
+        })]
+    } else if discriminator == instruction::RequestFold::DISCRIMINATOR {
+        let ix: instruction::RequestFold = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::Fold {
+            op: ix.op,
+            handles: ix.handles,
+        }]
+    } else if discriminator == instruction::RequestVectorOp::DISCRIMINATOR {
+        let ix: instruction::RequestVectorOp = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::Vector {
+            op: ix.op,
+            lhs: ix.lhs,
+            rhs: ix.rhs,
+        }]
+    } else if discriminator == instruction::RequestRangeCheck::DISCRIMINATOR {
+        let ix: instruction::RequestRangeCheck = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::RangeCheck {
+            x: ix.x,
+            lo: ix.lo,
+            hi: ix.hi,
+        }]
+    } else if discriminator == instruction::RequestDotProduct::DISCRIMINATOR {
+        let ix: instruction::RequestDotProduct = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::DotProduct {
+            weights: DotWeights::Encrypted(ix.weights),
+            values: ix.values,
+        }]
+    } else if discriminator == instruction::RequestScalarDotProduct::DISCRIMINATOR {
+        let ix: instruction::RequestScalarDotProduct = decode_args(args)?;
+        if ix.dry_run {
+            return Ok(Vec::new());
+        }
+        vec![ReceiptRequest::DotProduct {
+            weights: DotWeights::Scalar(ix.weights),
+            values: ix.values,
+        }]
+    } else if discriminator == instruction::Accumulate::DISCRIMINATOR {
+        let ix: instruction::Accumulate = decode_args(args)?;
+        vec![ReceiptRequest::Accumulate {
+            value: ix.value_handle,
+        }]
+    } else {
+        Vec::new()
+    };
+    Ok(requests)
+}
@@ -0,0 +1,98 @@
+// Handle registry bookkeeping: parent records are passed as remaining
+// accounts, in the same order as the child's `parents` list.
+use anchor_lang::prelude::*;
+
+use crate::errors::ExecutorError;
+use crate::math::bump_counter;
+use crate::state::HandleRecord;
+use crate::types::Handle;
+
+/// Direction of a parent reference-count update.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RefChange {
+    Acquire,
+    Release,
+}
+
+/// Increments or decrements `ref_count` on the record of every parent.
+/// Returns the parents' provenance roots, in order.
+pub fn update_parent_ref_counts<'info>(
+    parent_accounts: &'info [AccountInfo<'info>],
+    parents: &[Handle],
+    change: RefChange,
+    program_id: &Pubkey,
+) -> Result<Vec<[u8; 32]>> {
+    require!(
+        parent_accounts.len() >= parents.len(),
+        ExecutorError::MissingParentRecord
+    );
+
+    let mut roots = Vec::with_capacity(parents.len());
+    for (parent, account_info) in parents.iter().zip(parent_accounts) {
+        let mut record: Account<'info, HandleRecord> = Account::try_from(account_info)?;
+        require!(record.handle == *parent, ExecutorError::MissingParentRecord);
+        roots.push(record.provenance_root);
+
+        match change {
+            RefChange::Acquire => {
+                require!(!record.frozen, ExecutorError::HandleFrozen);
+                bump_counter(&mut record.ref_count)
+            }
+            RefChange::Release => record.ref_count = record.ref_count.saturating_sub(1),
+        }
+        record.exit(program_id)?;
+    }
+    Ok(roots)
+}
+
+/// Stamps `first_used_slot` on the operand records passed in
+/// `record_accounts` (any order; operands without a record are left out)
+/// the first time their handle is an operand of a successful request.
+/// Every account must be the record of one of `operands`.
+pub fn mark_operands_used(
+    record_accounts: &[AccountInfo],
+    operands: &[Handle],
+    program_id: &Pubkey,
+) -> Result<()> {
+    if record_accounts.is_empty() {
+        return Ok(());
+    }
+    let slot = Clock::get()?.slot;
+    for account_info in record_accounts {
+        require_keys_eq!(
+            *account_info.owner,
+            *program_id,
+            ExecutorError::OperandRecordMismatch
+        );
+        let mut data = account_info.try_borrow_mut_data()?;
+        let mut record = HandleRecord::try_deserialize(&mut &data[..])?;
+        require!(
+            operands.contains(&record.handle),
+            ExecutorError::OperandRecordMismatch
+        );
+        if record.first_used_slot.is_none() {
+            record.first_used_slot = Some(slot);
+            record.try_serialize(&mut &mut data[..])?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads an account of another deployment of this program, after checking
+/// that `source_program` owns it and that it is that deployment's PDA for
+/// `[namespace, handle]` under the bump stored in the account.
+pub fn load_foreign_pda<T: AccountDeserialize>(
+    account_info: &AccountInfo,
+    source_program: &Pubkey,
+    namespace: &[u8],
+    handle: &Handle,
+    bump: impl Fn(&T) -> u8,
+) -> Result<T> {
+    require_keys_eq!(*account_info.owner, *source_program, ExecutorError::InvalidSourceRecord);
+    let account = T::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+    let expected =
+        Pubkey::create_program_address(&[namespace, handle, &[bump(&account)]], source_program)
+            .map_err(|_| error!(ExecutorError::InvalidSourceRecord))?;
+    require_keys_eq!(expected, account_info.key(), ExecutorError::InvalidSourceRecord);
+    Ok(account)
+}
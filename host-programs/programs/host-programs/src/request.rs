@@ -0,0 +1,389 @@
+// Generic request path: shared by instructions that carry several
+// heterogeneous requests at once, and by every single-op request
+// instruction through `process_request`.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+
+use crate::anomaly::check_derivation;
+use crate::errors::ExecutorError;
+use crate::events::*;
+use crate::handle::*;
+use crate::opcode::OpCode;
+use crate::origin::{origin_program, throttle_key};
+use crate::profile::measure;
+use crate::registry::mark_operands_used;
+use crate::state::*;
+use crate::types::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OpRequest {
+    Register {
+        handle: Handle,
+        client_tag: [u8; 32],
+    },
+    Unary {
+        op: Fhe16UnaryOp,
+        input: Handle,
+    },
+    Binary {
+        op: Fhe16BinaryOp,
+        lhs: Handle,
+        rhs: Handle,
+    },
+    Ternary {
+        op: Fhe16TernaryOp,
+        a: Handle,
+        b: Handle,
+        c: Handle,
+    },
+    Pad {
+        input: Handle,
+        target_width: u8,
+    },
+    Truncate {
+        input: Handle,
+        target_width: u8,
+    },
+    /// `lo` and `hi` joined into one `2 * half_width`-bit value.
+    Concat {
+        lo: Handle,
+        hi: Handle,
+        half_width: u8,
+    },
+}
+
+impl OpRequest {
+    /// Config-dependent checks (tag allowlist, strict mode etc.).
+    pub fn validate(&self, config: &Config) -> Result<()> {
+        config.check_not_frozen(&self.operands())?;
+        match self {
+            OpRequest::Register { handle, client_tag } => {
+                config.check_not_frozen(&[*handle])?;
+                config.check_client_tag(client_tag)?
+            }
+            OpRequest::Binary { op, lhs, rhs } => {
+                require!(!op.is_vector(), ExecutorError::UseVectorOp);
+                config.check_binary_operands(*op, lhs, rhs)?
+            }
+            OpRequest::Ternary { op, a, b, c } => config.check_ternary_operands(*op, a, b, c)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Rejects ops the executor has flagged as not yet implemented with
+    /// `OpNotSupported`, leaving an `UnimplementedOpRequested` event so
+    /// operators can see demand for the missing feature.
+    pub fn check_supported(&self, config: &Config, caller: Pubkey) -> Result<()> {
+        if let OpRequest::Binary { op, .. } = self {
+            if !config.supports_binary_op(*op) {
+                emit!(UnimplementedOpRequested {
+                    caller,
+                    op_code: OpCode::binary(*op),
+                });
+                return err!(ExecutorError::OpNotSupported);
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles the result is derived from (none for registrations).
+    pub fn operands(&self) -> Vec<Handle> {
+        match *self {
+            OpRequest::Register { .. } => Vec::new(),
+            OpRequest::Unary { input, .. }
+            | OpRequest::Pad { input, .. }
+            | OpRequest::Truncate { input, .. } => vec![input],
+            OpRequest::Binary { lhs, rhs, .. } => vec![lhs, rhs],
+            OpRequest::Concat { lo, hi, .. } => vec![lo, hi],
+            OpRequest::Ternary { a, b, c, .. } => vec![a, b, c],
+        }
+    }
+
+    /// Emits the per-arity event executors and indexers watch for, in the
+    /// format `config` selects.
+    pub fn emit_requested(
+        &self,
+        config: &Config,
+        caller: Pubkey,
+        result_handle: Handle,
+        origin_program: Pubkey,
+    ) {
+        match *self {
+            OpRequest::Register { handle, client_tag } => emit!(InputHandleRegistered {
+                caller,
+                handle,
+                client_tag,
+                is_labeled: ClientTag::from(client_tag).is_labeled(),
+                origin_program,
+            }),
+            OpRequest::Unary { op, input } => emit!(Fhe16UnaryOpRequested {
+                caller,
+                op,
+                input_handle: input,
+                result_handle,
+                origin_program,
+            }),
+            OpRequest::Binary { op, lhs, rhs } => {
+                config.emit_binary_requested(caller, op, lhs, rhs, result_handle, origin_program)
+            }
+            OpRequest::Ternary { op, a, b, c } => emit!(Fhe16TernaryOpRequested {
+                caller,
+                op,
+                a_handle: a,
+                b_handle: b,
+                c_handle: c,
+                result_handle,
+                origin_program,
+            }),
+            OpRequest::Pad { input, target_width } => emit!(Fhe16PadRequested {
+                caller,
+                input_handle: input,
+                target_width,
+                result_handle,
+                origin_program,
+            }),
+            OpRequest::Truncate { input, target_width } => emit!(Fhe16TruncateRequested {
+                caller,
+                input_handle: input,
+                target_width,
+                result_handle,
+                origin_program,
+            }),
+            OpRequest::Concat { lo, hi, half_width } => emit!(Fhe16ConcatRequested {
+                caller,
+                lo_handle: lo,
+                hi_handle: hi,
+                result_width: 2 * half_width,
+                result_handle,
+                origin_program,
+            }),
+        }
+    }
+
+    /// Validates the request and derives its result handle. For
+    /// registrations the "result" is the registered handle itself.
+    pub fn derive_result(&self, program_id: &Pubkey, salt: &DomainSalt) -> Result<Handle> {
+        let handle = match *self {
+            OpRequest::Register { handle, client_tag } => {
+                require!(
+                    !ClientTag::from(client_tag).is_reserved(),
+                    ExecutorError::ReservedClientTag
+                );
+                handle
+            }
+            OpRequest::Unary { op, input } => {
+                derive_unary_handle(op, &input, program_id, salt)
+            }
+            OpRequest::Binary { op, lhs, rhs } => {
+                derive_binary_handle(op, &lhs, &rhs, program_id, salt)
+            }
+            OpRequest::Ternary { op, a, b, c } => {
+                derive_ternary_handle(op, &a, &b, &c, program_id, salt)
+            }
+            OpRequest::Pad { input, target_width } => {
+                require!(
+                    target_width > 0 && target_width <= FHE16_MAX_WIDTH,
+                    ExecutorError::InvalidWidth
+                );
+                derive_pad_handle(&input, target_width, program_id, salt)
+            }
+            OpRequest::Truncate { input, target_width } => {
+                require!(
+                    target_width > 0 && target_width < FHE16_MAX_WIDTH,
+                    ExecutorError::InvalidWidth
+                );
+                derive_truncate_handle(&input, target_width, program_id, salt)
+            }
+            OpRequest::Concat { lo, hi, half_width } => {
+                require!(
+                    half_width > 0 && half_width <= FHE16_MAX_WIDTH / 2,
+                    ExecutorError::InvalidWidth
+                );
+                derive_concat_handle(&lo, &hi, half_width, program_id, salt)
+            }
+        };
+        Ok(handle)
+    }
+
+    /// Per-arity instruction carrying this request (`request_op` derives
+    /// through the same path), as reported by `CuUsed`.
+    pub fn instruction_name(&self) -> &'static str {
+        match self {
+            OpRequest::Register { .. } => "register_input_handle",
+            OpRequest::Unary { .. } => "request_unary_op",
+            OpRequest::Binary { .. } => "request_binary_op",
+            OpRequest::Ternary { .. } => "request_ternary_op",
+            OpRequest::Pad { .. } => "request_pad",
+            OpRequest::Truncate { .. } => "request_truncate",
+            OpRequest::Concat { .. } => "request_concat",
+        }
+    }
+
+    /// Exact bytes whose SHA-256 is the result handle, so verifiers can
+    /// check a derivation with their own hash implementation. `None` for
+    /// registrations, whose handle is not derived.
+    pub fn derivation_preimage(&self, program_id: &Pubkey, salt: &DomainSalt) -> Option<Vec<u8>> {
+        let (domain, param, operands) = match *self {
+            OpRequest::Register { .. } => return None,
+            OpRequest::Unary { op, .. } => (HANDLE_DOMAIN_UNARY, op as u8, self.operands()),
+            OpRequest::Binary { op, .. } => (HANDLE_DOMAIN_BINARY, op as u8, self.operands()),
+            OpRequest::Ternary { op, .. } => (HANDLE_DOMAIN_TERNARY, op as u8, self.operands()),
+            OpRequest::Pad { target_width, .. } => {
+                (HANDLE_DOMAIN_PAD, target_width, self.operands())
+            }
+            OpRequest::Truncate { target_width, .. } => {
+                (HANDLE_DOMAIN_TRUNCATE, target_width, self.operands())
+            }
+            OpRequest::Concat { half_width, .. } => {
+                (HANDLE_DOMAIN_CONCAT, half_width, self.operands())
+            }
+        };
+        Some(op_preimage(domain, salt, program_id, param, &operands))
+    }
+}
+
+/// Ends a `dry_run` request after validation and derivation: the derived
+/// handles go to return data and the instruction fails with
+/// `DryRunComplete`, so a dry run can be simulated but never land. Nonces,
+/// counters and events are skipped before this point.
+pub fn finish_dry_run<T: AnchorSerialize, R>(derived: &T) -> Result<R> {
+    set_return_data(&derived.try_to_vec()?);
+    err!(ExecutorError::DryRunComplete)
+}
+
+/// Argument of the generic `request_op` instruction. Variant indices are
+/// part of the instruction ABI: a new request kind fills the next reserved
+/// slot instead of shifting existing ones. Until then reserved variants
+/// are rejected with `OpNotSupported`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OpRequestV1 {
+    Unary {
+        op: Fhe16UnaryOp,
+        input: Handle,
+    },
+    Binary {
+        op: Fhe16BinaryOp,
+        lhs: Handle,
+        rhs: Handle,
+    },
+    Ternary {
+        op: Fhe16TernaryOp,
+        a: Handle,
+        b: Handle,
+        c: Handle,
+    },
+    Pad {
+        input: Handle,
+        target_width: u8,
+    },
+    Truncate {
+        input: Handle,
+        target_width: u8,
+    },
+    Concat {
+        lo: Handle,
+        hi: Handle,
+        half_width: u8,
+    },
+    Reserved6,
+    Reserved7,
+}
+
+impl TryFrom<OpRequestV1> for OpRequest {
+    type Error = Error;
+
+    fn try_from(request: OpRequestV1) -> Result<Self> {
+        let request = match request {
+            OpRequestV1::Unary { op, input } => OpRequest::Unary { op, input },
+            OpRequestV1::Binary { op, lhs, rhs } => OpRequest::Binary { op, lhs, rhs },
+            OpRequestV1::Ternary { op, a, b, c } => OpRequest::Ternary { op, a, b, c },
+            OpRequestV1::Pad { input, target_width } => OpRequest::Pad { input, target_width },
+            OpRequestV1::Truncate { input, target_width } => {
+                OpRequest::Truncate { input, target_width }
+            }
+            OpRequestV1::Concat { lo, hi, half_width } => OpRequest::Concat { lo, hi, half_width },
+            OpRequestV1::Reserved6 | OpRequestV1::Reserved7 => {
+                return err!(ExecutorError::OpNotSupported);
+            }
+        };
+        Ok(request)
+    }
+}
+
+/// Accounts every single-op request instruction declares, borrowed out of
+/// the instruction's own `Accounts` struct (see `request_accounts!`).
+pub struct RequestAccounts<'a, 'info, 'ops> {
+    pub caller: Pubkey,
+    pub caller_signed: bool,
+    pub config: &'a Config,
+    pub work_cursor: &'a mut WorkCursor,
+    pub nonce_account: &'a mut Option<Account<'info, CallerNonce>>,
+    pub dedup_cache: &'a mut Option<Account<'info, DedupCache>>,
+    pub activity: &'a mut Option<Account<'info, CallerActivity>>,
+    pub instructions: Option<&'a AccountInfo<'info>>,
+    pub result_record: Option<&'a HandleRecord>,
+    /// Operand records to mark used, see `mark_operands_used`.
+    pub operand_records: &'a [AccountInfo<'ops>],
+}
+
+/// Shared body of the single-op request instructions: checks, derivation
+/// and derivation self-checks, then (unless `dry_run`) the nonce, operand
+/// records, dedup cache, work cursor and the request's per-arity event.
+pub fn process_request(
+    accounts: RequestAccounts,
+    program_id: &Pubkey,
+    request: OpRequest,
+    caller_nonce: Option<u64>,
+    dry_run: bool,
+) -> Result<Handle> {
+    let RequestAccounts { caller, config, .. } = accounts;
+    config.check_cpi_depth()?;
+    request.check_supported(config, caller)?;
+    request.validate(config)?;
+
+    // handle 생성 (immutable, deterministic)
+    let result_handle = measure(request.instruction_name(), || {
+        let result_handle = request.derive_result(program_id, &config.domain_salt)?;
+        check_derivation(
+            config,
+            caller,
+            &result_handle,
+            request.op_code(),
+            &request.operands(),
+            accounts.result_record,
+        )?;
+        Ok(result_handle)
+    })?;
+
+    if dry_run {
+        check_caller_nonce(accounts.nonce_account, caller_nonce)?;
+        return finish_dry_run(&result_handle);
+    }
+    config.check_caller_signed(accounts.caller_signed)?;
+    consume_caller_nonce(accounts.nonce_account, caller_nonce, accounts.caller_signed)?;
+    mark_operands_used(accounts.operand_records, &request.operands(), program_id)?;
+    if is_duplicate_request(accounts.dedup_cache, &result_handle)? {
+        if config.emit_events {
+            emit!(SuppressedDuplicate {
+                caller,
+                result_handle,
+                slot: Clock::get()?.slot,
+            });
+        }
+        return Ok(result_handle);
+    }
+    record_caller_activity(accounts.activity, config, 1, || {
+        throttle_key(caller, accounts.caller_signed, accounts.instructions, program_id)
+    })?;
+    let seq = accounts.work_cursor.record_requests(1)?;
+
+    // 이벤트 → executor 가 이 job 을 비동기 처리
+    if config.emit_events {
+        let origin = origin_program(accounts.instructions, program_id)?;
+        config.emit_request_meta(seq)?;
+        request.emit_requested(config, caller, result_handle, origin);
+    }
+
+    Ok(result_handle)
+}
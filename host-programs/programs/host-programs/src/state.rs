@@ -0,0 +1,791 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::get_stack_height;
+
+use crate::errors::ExecutorError;
+use crate::events::{
+    ConfigField, ConfigUpdated, Fhe16BinaryOpCategorized, Fhe16BinaryOpRequested, RequestMeta,
+};
+use crate::handle::{DOMAIN_SALT_LEN, NO_DOMAIN_SALT};
+use crate::math::bump_counter_by;
+use crate::opcode::OpCode;
+use crate::provenance::node_root;
+use crate::types::{Fhe16BinaryOp, Fhe16TernaryOp, Handle};
+
+// PDA seed scheme: every PDA starts with one of the namespace seeds below,
+// followed by fixed-width keys only:
+//
+//   config         [CONFIG_SEED]
+//   work cursor    [WORK_CURSOR_SEED]
+//   caller nonce   [CALLER_NONCE_SEED, caller]
+//   caller stats   [CALLER_STATS_SEED, caller]
+//   dedup cache    [DEDUP_CACHE_SEED, caller]
+//   activity       [CALLER_ACTIVITY_SEED, throttle key]
+//   handle record  [HANDLE_RECORD_SEED, handle]
+//   accumulator    [ACCUMULATOR_SEED, owner, tag]
+//   export record  [EXPORT_RECORD_SEED, handle]
+//
+// Seeds are hashed as a plain concatenation. No namespace is a prefix of
+// another, so the hashed bytes of two different kinds of PDA diverge
+// within the namespace seed. A caller-chosen handle only ever fills the
+// 32 bytes after HANDLE_RECORD_SEED. It cannot recreate the config,
+// work cursor or nonce preimage, whatever its value. New seeds must be
+// added to NAMESPACE_SEEDS.
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const CALLER_NONCE_SEED: &[u8] = b"caller_nonce";
+pub const CALLER_STATS_SEED: &[u8] = b"caller_stats";
+pub const DEDUP_CACHE_SEED: &[u8] = b"dedup_cache";
+pub const HANDLE_RECORD_SEED: &[u8] = b"handle_record";
+pub const WORK_CURSOR_SEED: &[u8] = b"work_cursor";
+pub const ACCUMULATOR_SEED: &[u8] = b"accumulator";
+pub const CALLER_ACTIVITY_SEED: &[u8] = b"caller_activity";
+pub const EXPORT_RECORD_SEED: &[u8] = b"export_record";
+
+pub const NAMESPACE_SEEDS: [&[u8]; 9] = [
+    CONFIG_SEED,
+    CALLER_NONCE_SEED,
+    CALLER_STATS_SEED,
+    DEDUP_CACHE_SEED,
+    HANDLE_RECORD_SEED,
+    WORK_CURSOR_SEED,
+    ACCUMULATOR_SEED,
+    CALLER_ACTIVITY_SEED,
+    EXPORT_RECORD_SEED,
+];
+
+const fn is_prefix(prefix: &[u8], of: &[u8]) -> bool {
+    if prefix.len() > of.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < prefix.len() {
+        if prefix[i] != of[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const _: () = {
+    let mut i = 0;
+    while i < NAMESPACE_SEEDS.len() {
+        let mut j = 0;
+        while j < NAMESPACE_SEEDS.len() {
+            assert!(i == j || !is_prefix(NAMESPACE_SEEDS[i], NAMESPACE_SEEDS[j]));
+            j += 1;
+        }
+        i += 1;
+    }
+};
+
+/// Deepest stack height a request may arrive at: user -> dapp -> host.
+pub const DEFAULT_MAX_CPI_DEPTH: u8 = 2;
+
+/// Solana's instruction stack limit (top-level = 1). The runtime aborts
+/// deeper CPIs with an opaque error, so `max_cpi_depth` may not exceed it.
+/// Its lower bound is 1, since 0 would reject every request.
+pub const MAX_STACK_HEIGHT: u8 = 5;
+
+/// Max number of parents a record can reference (ternary ops).
+pub const MAX_HANDLE_PARENTS: usize = 3;
+
+/// Length of a `client_tag` namespace prefix in the registration allowlist.
+pub const TAG_PREFIX_LEN: usize = 4;
+
+/// Max number of prefixes the registration allowlist can hold.
+pub const MAX_TAG_PREFIXES: usize = 8;
+
+/// Max number of handles the admin can have frozen at once. Every request
+/// scans the list once per operand, so its size is bounded by compute, not
+/// only by `Config` space (32 bytes per entry): at 32 entries the scan of
+/// a 16-operand dot product stays at a few thousand CU.
+pub const MAX_FROZEN_HANDLES: usize = 32;
+
+/// Result handles a dedup cache remembers within one slot.
+pub const DEDUP_CACHE_LEN: usize = 8;
+
+/// Length of the owner-chosen tag naming an accumulator.
+pub const ACCUMULATOR_TAG_LEN: usize = 16;
+
+/// Smallest `bond_release_slots`: a bond is never reclaimable in the slot
+/// it was posted in, so registering and releasing cannot share a transaction.
+pub const MIN_BOND_RELEASE_SLOTS: u64 = 1;
+
+/// `Config::event_format`: op events only, as older indexers expect.
+pub const EVENT_FORMAT_LEGACY: u8 = 0;
+/// `Config::event_format`: every op event is preceded by a `RequestMeta`.
+pub const EVENT_FORMAT_ENRICHED: u8 = 1;
+/// `Config::event_format`: the enriched format, with binary requests
+/// emitting `Fhe16BinaryOpCategorized` in place of the unified
+/// `Fhe16BinaryOpRequested`.
+pub const EVENT_FORMAT_CATEGORIZED: u8 = 2;
+
+/// Whether op events under `event_format` are preceded by a `RequestMeta`,
+/// as they are under every format but the legacy one.
+pub const fn has_request_meta(event_format: u8) -> bool {
+    event_format != EVENT_FORMAT_LEGACY
+}
+
+/// Deployment-wide enforcement level. Each profile resolves the request
+/// checks, caller signatures and the registration bond, throttle and tag
+/// allowlist into a `Policy`; see `Config::policy`.
+///
+/// An empty `tag_allowlist` means "unset" under every profile, so a fresh
+/// `Strict` deployment accepts registrations until the admin sets one. The
+/// registration bond is the only charge a profile adds; there is no
+/// per-request fee. Handle ownership is not enforced on op operands under
+/// any profile, since request instructions load no operand records.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StrictnessProfile {
+    /// Every check follows its own config flag (`strict_select`,
+    /// `strict_mode`, `anomaly_soft_fail`, `templates_enabled`), and the
+    /// bond, throttle and allowlist are exactly as configured.
+    Permissionless,
+    /// Suspicious SELECT wiring is always rejected and derivation anomalies
+    /// always fail the request; registrations escrow at least
+    /// `PROFILE_MIN_BOND_LAMPORTS`. The remaining checks follow their flags.
+    Standard,
+    /// `Standard`, plus degenerate operand wiring is always rejected,
+    /// template ops are never accepted, the caller must sign every request
+    /// (a calling program signs for its PDA with `invoke_signed`), and
+    /// callers are throttled to `STRICT_MAX_REQUESTS_PER_WINDOW` per
+    /// `STRICT_WINDOW_SLOTS` unless a throttle is configured.
+    Strict,
+}
+
+/// Profile `initialize_config` starts from: `Strict` in builds with the
+/// `mainnet` feature, `Permissionless` otherwise (devnet, localnet).
+#[cfg(feature = "mainnet")]
+pub const DEFAULT_STRICTNESS: StrictnessProfile = StrictnessProfile::Strict;
+#[cfg(not(feature = "mainnet"))]
+pub const DEFAULT_STRICTNESS: StrictnessProfile = StrictnessProfile::Permissionless;
+
+/// Smallest registration bond under `Standard` and `Strict`; a larger
+/// configured bond applies as is.
+pub const PROFILE_MIN_BOND_LAMPORTS: u64 = 1_000_000;
+/// Throttle window `Strict` applies while `max_requests_per_window` is 0.
+pub const STRICT_WINDOW_SLOTS: u64 = 150;
+/// Requests per `STRICT_WINDOW_SLOTS` `Strict` allows while
+/// `max_requests_per_window` is 0.
+pub const STRICT_MAX_REQUESTS_PER_WINDOW: u32 = 300;
+
+/// Enforcement settings the request checks consult, resolved from the
+/// strictness profile and the individual config fields.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Policy {
+    /// SELECT whose condition is also one of its branches is rejected.
+    pub reject_suspicious_select: bool,
+    /// Degenerate operand wiring (SUB(x, x), SELECT(c, a, a)) is rejected.
+    pub reject_degenerate_operands: bool,
+    /// Derivation anomalies fail the request instead of being reported
+    /// through `DerivationAnomaly`.
+    pub fail_on_anomaly: bool,
+    /// Template ops are accepted.
+    pub allow_templates: bool,
+    /// Requests from a caller that did not sign are rejected.
+    pub require_signed_caller: bool,
+    /// Lamports `register_input_handle` escrows (0 = no bond).
+    pub registration_bond_lamports: u64,
+    /// Length of a caller's throttling window.
+    pub window_slots: u64,
+    /// Requests a caller may make per window (0 = unthrottled).
+    pub max_requests_per_window: u32,
+    /// Client tags must match a `tag_allowlist` prefix; off while the list
+    /// is empty.
+    pub enforce_tag_allowlist: bool,
+}
+
+/// Program-wide settings, owned by `admin`.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    /// When false, op requests only derive the result handle (derive-only mode).
+    pub emit_events: bool,
+    /// Requests arriving at a higher stack height are rejected.
+    pub max_cpi_depth: u8,
+    /// Rejects degenerate operand wiring such as SUB(x, x).
+    pub strict_mode: bool,
+    /// `client_tag` prefixes allowed to register inputs. Empty = any tag.
+    #[max_len(MAX_TAG_PREFIXES)]
+    pub tag_allowlist: Vec<[u8; TAG_PREFIX_LEN]>,
+    /// Template ops are rejected with OpNotSupported unless enabled.
+    pub templates_enabled: bool,
+    /// Rejects SELECT whose condition is also one of its branches
+    /// (almost always swapped arguments). On by default.
+    pub strict_select: bool,
+    /// Mixed into op handle derivations; all zeros (the initial value)
+    /// means the original unsalted derivation. See `rotate_domain_salt`.
+    pub domain_salt: [u8; DOMAIN_SALT_LEN],
+    /// Report derivation anomalies through `DerivationAnomaly` instead of
+    /// failing the request. Off by default.
+    pub anomaly_soft_fail: bool,
+    /// Event layout indexers receive (`EVENT_FORMAT_*`).
+    pub event_format: u8,
+    /// Multi-request instructions emit `Fhe16PackedOpRequested` instead of
+    /// the per-arity request events.
+    pub compact_events: bool,
+    /// Frozen handles, with or without a `HandleRecord`, kept here so
+    /// requests can reject them as operands without loading any record.
+    #[max_len(MAX_FROZEN_HANDLES)]
+    pub frozen_handles: Vec<[u8; 32]>,
+    /// Lamports `register_input_handle` escrows in the handle's record
+    /// (0 = no bond). Discourages spam registrations in open mode.
+    /// `Standard` and `Strict` raise it to `PROFILE_MIN_BOND_LAMPORTS`.
+    pub registration_bond_lamports: u64,
+    /// Slots after which a bond can be reclaimed by its payer, at least
+    /// `MIN_BOND_RELEASE_SLOTS`.
+    pub bond_release_slots: u64,
+    /// Receives bonds forfeited when unused records are collected.
+    pub treasury: Pubkey,
+    /// Length of a caller's throttling window; 1 limits requests per slot.
+    pub window_slots: u64,
+    /// Requests a caller may make per window (0 = unthrottled). While
+    /// non-zero every request must pass its throttle key's `CallerActivity`.
+    /// `Strict` throttles to its own default while this is 0.
+    pub max_requests_per_window: u32,
+    /// Enforcement level the checks resolve through `policy()`.
+    pub strictness: StrictnessProfile,
+    pub bump: u8,
+}
+
+impl Config {
+    /// The single source of truth for which checks are enforced; request
+    /// checks read this rather than the individual flags.
+    pub fn policy(&self) -> Policy {
+        let permissionless = Policy {
+            reject_suspicious_select: self.strict_select,
+            reject_degenerate_operands: self.strict_mode,
+            fail_on_anomaly: !self.anomaly_soft_fail,
+            allow_templates: self.templates_enabled,
+            require_signed_caller: false,
+            registration_bond_lamports: self.registration_bond_lamports,
+            window_slots: self.window_slots,
+            max_requests_per_window: self.max_requests_per_window,
+            enforce_tag_allowlist: !self.tag_allowlist.is_empty(),
+        };
+        let bond = self.registration_bond_lamports.max(PROFILE_MIN_BOND_LAMPORTS);
+        match self.strictness {
+            StrictnessProfile::Permissionless => permissionless,
+            StrictnessProfile::Standard => Policy {
+                reject_suspicious_select: true,
+                fail_on_anomaly: true,
+                registration_bond_lamports: bond,
+                ..permissionless
+            },
+            StrictnessProfile::Strict => {
+                let (window_slots, max_requests_per_window) = if self.max_requests_per_window == 0 {
+                    (STRICT_WINDOW_SLOTS, STRICT_MAX_REQUESTS_PER_WINDOW)
+                } else {
+                    (self.window_slots, self.max_requests_per_window)
+                };
+                Policy {
+                    reject_suspicious_select: true,
+                    reject_degenerate_operands: true,
+                    fail_on_anomaly: true,
+                    allow_templates: false,
+                    require_signed_caller: true,
+                    registration_bond_lamports: bond,
+                    window_slots,
+                    max_requests_per_window,
+                    ..permissionless
+                }
+            }
+        }
+    }
+
+    /// Rejects requests nested deeper than `max_cpi_depth`. The host never
+    /// CPIs out, so a host -> ... -> host loop cannot form and this depth
+    /// cap is the only guard request instructions need.
+    pub fn check_cpi_depth(&self) -> Result<()> {
+        require!(
+            get_stack_height() <= self.max_cpi_depth as usize,
+            ExecutorError::CpiDepthExceeded
+        );
+        Ok(())
+    }
+
+    /// Emits the `RequestMeta` for the op event that follows; a no-op under
+    /// the legacy format.
+    pub fn emit_request_meta(&self, seq: u64) -> Result<()> {
+        if has_request_meta(self.event_format) {
+            emit!(RequestMeta {
+                seq,
+                timestamp: Clock::get()?.unix_timestamp,
+                stack_height: get_stack_height() as u8,
+            });
+        }
+        Ok(())
+    }
+
+    /// Emits the binary request event in the configured format.
+    pub fn emit_binary_requested(
+        &self,
+        caller: Pubkey,
+        op: Fhe16BinaryOp,
+        lhs_handle: Handle,
+        rhs_handle: Handle,
+        result_handle: Handle,
+        origin_program: Pubkey,
+    ) {
+        if self.event_format == EVENT_FORMAT_CATEGORIZED {
+            emit!(Fhe16BinaryOpCategorized {
+                category: op.category(),
+                caller,
+                op,
+                lhs_handle,
+                rhs_handle,
+                result_handle,
+                origin_program,
+            });
+        } else {
+            emit!(Fhe16BinaryOpRequested {
+                caller,
+                op,
+                lhs_handle,
+                rhs_handle,
+                result_handle,
+                origin_program,
+            });
+        }
+    }
+
+    pub fn check_not_frozen(&self, handles: &[Handle]) -> Result<()> {
+        require!(
+            !handles.iter().any(|handle| self.frozen_handles.contains(handle)),
+            ExecutorError::HandleFrozen
+        );
+        Ok(())
+    }
+
+    /// Rejects an unsigned caller under `require_signed_caller`.
+    pub fn check_caller_signed(&self, caller_signed: bool) -> Result<()> {
+        require!(
+            caller_signed || !self.policy().require_signed_caller,
+            ExecutorError::CallerNotSigned
+        );
+        Ok(())
+    }
+
+    pub fn check_client_tag(&self, client_tag: &[u8; 32]) -> Result<()> {
+        require!(
+            !self.policy().enforce_tag_allowlist
+                || self
+                    .tag_allowlist
+                    .iter()
+                    .any(|prefix| client_tag.starts_with(prefix)),
+            ExecutorError::TagNotAllowed
+        );
+        Ok(())
+    }
+
+    /// Under `reject_degenerate_operands`, three identical operands are
+    /// rejected for every ternary op (none has a use for them). SELECT(cond,
+    /// a, b) is also checked: `cond` equal to a branch is rejected under
+    /// `reject_suspicious_select`; `a == b` (a legitimate but pointless
+    /// select) only under `reject_degenerate_operands`.
+    pub fn check_ternary_operands(
+        &self,
+        op: Fhe16TernaryOp,
+        a: &Handle,
+        b: &Handle,
+        c: &Handle,
+    ) -> Result<()> {
+        let policy = self.policy();
+        if policy.reject_degenerate_operands {
+            require!(a != b || b != c, ExecutorError::DegenerateTernary);
+        }
+        if op != Fhe16TernaryOp::Select {
+            return Ok(());
+        }
+        if policy.reject_suspicious_select {
+            require!(a != b && a != c, ExecutorError::SuspiciousSelect);
+        }
+        if policy.reject_degenerate_operands {
+            require!(b != c, ExecutorError::SuspiciousSelect);
+        }
+        Ok(())
+    }
+
+    pub fn supports_binary_op(&self, op: Fhe16BinaryOp) -> bool {
+        self.policy().allow_templates || !op.is_template()
+    }
+
+    pub fn check_binary_operands(
+        &self,
+        op: Fhe16BinaryOp,
+        lhs: &Handle,
+        rhs: &Handle,
+    ) -> Result<()> {
+        if self.policy().reject_degenerate_operands {
+            require!(
+                lhs != rhs || !op.is_trivial_on_equal_operands(),
+                ExecutorError::TrivialSelfOp
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Emits `ConfigUpdated` for one field change.
+pub fn emit_config_updated<T: AnchorSerialize>(
+    admin: Pubkey,
+    field: ConfigField,
+    old: &T,
+    new: &T,
+) -> Result<()> {
+    emit!(ConfigUpdated {
+        field,
+        old: old.try_to_vec()?,
+        new: new.try_to_vec()?,
+        admin,
+    });
+    Ok(())
+}
+
+/// Single account executors can poll instead of holding a websocket: it
+/// only changes when new requests arrive, from any caller, and its
+/// sequence numbers order requests program-wide.
+#[account]
+#[derive(InitSpace)]
+pub struct WorkCursor {
+    pub total_requests: u64,
+    pub last_request_slot: u64,
+    pub bump: u8,
+}
+
+impl WorkCursor {
+    /// Reserves `count` consecutive sequence numbers with a single write and
+    /// returns the first. `total_requests` doubles as the next free sequence
+    /// number, so the `i`-th request of an instruction is `first + i`.
+    pub fn record_requests(&mut self, count: u64) -> Result<u64> {
+        let first_seq = self.total_requests;
+        bump_counter_by(&mut self.total_requests, count);
+        self.last_request_slot = Clock::get()?.slot;
+        Ok(first_seq)
+    }
+}
+
+/// Running encrypted total. Each accumulate replaces `current_handle` with
+/// ADD(current_handle, value). The account is writable in every accumulate,
+/// so the runtime serializes accumulations (even within one slot) and each
+/// one chains onto the handle stored by the previous one.
+#[account]
+#[derive(InitSpace)]
+pub struct Accumulator {
+    pub owner: Pubkey,
+    pub tag: [u8; ACCUMULATOR_TAG_LEN],
+    /// Starts at the canonical zero constant handle.
+    pub current_handle: [u8; 32],
+    /// When false, only `owner` may accumulate.
+    pub is_public: bool,
+    pub accumulate_count: u64,
+    pub bump: u8,
+}
+
+impl Accumulator {
+    pub fn check_caller(&self, caller: &Pubkey) -> Result<()> {
+        require!(
+            self.is_public || *caller == self.owner,
+            ExecutorError::AccumulatorNotPublic
+        );
+        Ok(())
+    }
+}
+
+/// Per-caller replay protection: the last nonce accepted for `caller`.
+#[account]
+#[derive(InitSpace)]
+pub struct CallerNonce {
+    pub caller: Pubkey,
+    pub last_nonce: u64,
+    pub bump: u8,
+}
+
+/// Per-caller usage counters for dashboards, so clients need not scan logs.
+/// Opt-in: only updated when passed to `register_input_handle`.
+#[account]
+#[derive(InitSpace)]
+pub struct CallerStats {
+    pub caller: Pubkey,
+    pub registered_inputs: u64,
+    pub bump: u8,
+}
+
+/// Opt-in guard against a dapp re-emitting the same request in a tight
+/// loop: remembers the last `DEDUP_CACHE_LEN` result handles of the
+/// current slot and is cleared whenever the slot changes.
+#[account]
+#[derive(InitSpace)]
+pub struct DedupCache {
+    pub caller: Pubkey,
+    pub slot: u64,
+    pub handles: [[u8; 32]; DEDUP_CACHE_LEN],
+    /// Valid entries for `slot` (at most `DEDUP_CACHE_LEN`).
+    pub len: u8,
+    /// Ring position the next handle is written to.
+    pub next: u8,
+    pub bump: u8,
+}
+
+impl DedupCache {
+    /// Records `handle` for `slot`. Returns false if it was already
+    /// requested in that slot.
+    pub fn insert(&mut self, handle: &Handle, slot: u64) -> bool {
+        if self.slot != slot {
+            self.slot = slot;
+            self.len = 0;
+            self.next = 0;
+        }
+        if self.handles[..self.len as usize].contains(handle) {
+            return false;
+        }
+        self.handles[self.next as usize] = *handle;
+        self.next = ((self.next as usize + 1) % DEDUP_CACHE_LEN) as u8;
+        self.len = (self.len as usize + 1).min(DEDUP_CACHE_LEN) as u8;
+        true
+    }
+}
+
+/// True if `handle` was already requested by this caller in the current
+/// slot. Without a cache account nothing is ever a duplicate.
+pub fn is_duplicate_request(
+    dedup_cache: &mut Option<Account<DedupCache>>,
+    handle: &Handle,
+) -> Result<bool> {
+    let Some(cache) = dedup_cache.as_mut() else {
+        return Ok(false);
+    };
+    Ok(!cache.insert(handle, Clock::get()?.slot))
+}
+
+/// Per-caller request throttle, kept for the signer or program requests
+/// are attributed to (`origin::throttle_key`). A window opens at the
+/// caller's first request after the previous one ended and snapshots the
+/// config limits, so reconfiguration applies from the caller's next window
+/// on.
+#[account]
+#[derive(InitSpace)]
+pub struct CallerActivity {
+    pub caller: Pubkey,
+    pub window_start_slot: u64,
+    pub window_slots: u64,
+    pub max_requests: u32,
+    /// Requests counted in the current window.
+    pub count: u64,
+    pub bump: u8,
+}
+
+impl CallerActivity {
+    /// Counts `requests` against the window containing `slot`, opening a
+    /// new one if the current window has elapsed.
+    pub fn record(&mut self, config: &Config, slot: u64, requests: u64) -> Result<()> {
+        if slot >= self.window_start_slot.saturating_add(self.window_slots) {
+            self.window_start_slot = slot;
+            let policy = config.policy();
+            self.window_slots = policy.window_slots;
+            self.max_requests = policy.max_requests_per_window;
+            self.count = 0;
+        }
+        require!(
+            self.max_requests == 0
+                || self.count.saturating_add(requests) <= self.max_requests as u64,
+            ExecutorError::RateLimited
+        );
+        bump_counter_by(&mut self.count, requests);
+        Ok(())
+    }
+}
+
+/// Applies the request throttle. Without an activity account this only
+/// passes while throttling is off; with one, the account must belong to
+/// the request's `throttle_key` (see `origin::throttle_key`).
+pub fn record_caller_activity(
+    activity: &mut Option<Account<CallerActivity>>,
+    config: &Config,
+    requests: u64,
+    throttle_key: impl FnOnce() -> Result<Pubkey>,
+) -> Result<()> {
+    let Some(activity) = activity.as_mut() else {
+        require!(
+            config.policy().max_requests_per_window == 0,
+            ExecutorError::MissingActivityAccount
+        );
+        return Ok(());
+    };
+    let key = throttle_key()?;
+    require_keys_eq!(activity.caller, key, ExecutorError::ActivityAccountMismatch);
+    activity.record(config, Clock::get()?.slot, requests)
+}
+
+/// Registry entry for a handle. Root records (no parents) are created
+/// explicitly and prove nothing about the handle; records with parents only
+/// come from `register_derived`, which re-derives the handle from them first.
+#[account]
+#[derive(InitSpace)]
+pub struct HandleRecord {
+    pub handle: [u8; 32],
+    pub owner: Pubkey,
+    /// Receives the rent back when the record is closed.
+    pub payer: Pubkey,
+    pub parents: [[u8; 32]; MAX_HANDLE_PARENTS],
+    pub parent_count: u8,
+    /// Op that derived the handle from `parents`; `None` for root records.
+    pub op_code: Option<OpCode>,
+    /// Domain salt `op_code` derived the handle under, kept so the
+    /// derivation can still be checked after the salt rotates. Unset
+    /// (`NO_DOMAIN_SALT`) for root records.
+    pub domain_salt: [u8; DOMAIN_SALT_LEN],
+    /// Number of live records using this handle as a parent. A record
+    /// can only be closed once nothing references it.
+    pub ref_count: u64,
+    pub created_slot: u64,
+    pub provenance: HandleProvenance,
+    /// Deployment that produced the handle (this program unless
+    /// `Imported`).
+    pub source_program: Pubkey,
+    /// Set by the admin during incident response: the handle can no longer
+    /// be an operand or the parent of a new record.
+    pub frozen: bool,
+    /// Registration bond held on top of rent; refundable to `payer` once
+    /// the handle has been used or `bond_release_slots` have passed.
+    pub bond_lamports: u64,
+    /// Slot the handle was first an operand of a successful request whose
+    /// accounts included this record; `None` while it never was.
+    pub first_used_slot: Option<u64>,
+    /// `provenance_root` of each parent record, in `parents` order.
+    pub parent_roots: [[u8; 32]; MAX_HANDLE_PARENTS],
+    /// Commitment to the handle's lineage, see `provenance::node_root`.
+    /// Provenance proofs are verified against this value.
+    pub provenance_root: [u8; 32],
+    pub bump: u8,
+}
+
+/// Written by `export_handle`: the exporting deployment's own statement
+/// that `owner` exported `handle`. Importers read it from the source
+/// program's PDA, since the `proof` hash alone can be computed by anyone.
+#[account]
+#[derive(InitSpace)]
+pub struct ExportRecord {
+    pub handle: [u8; 32],
+    pub owner: Pubkey,
+    pub proof: [u8; 32],
+    pub exported_slot: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum HandleProvenance {
+    /// Recorded here as given: roots and program constants.
+    Local,
+    /// Exported by another deployment, see `import_handle`.
+    Imported,
+    /// Re-derived here from `parents` and `op_code` by `register_derived`.
+    Derived,
+}
+
+impl HandleRecord {
+    /// Fills a fresh record for a program-owned constant (no parents).
+    pub fn init_constant(
+        &mut self,
+        handle: Handle,
+        program_id: Pubkey,
+        payer: Pubkey,
+        slot: u64,
+        bump: u8,
+    ) {
+        self.handle = handle;
+        self.owner = program_id;
+        self.payer = payer;
+        self.parent_count = 0;
+        self.op_code = None;
+        self.domain_salt = NO_DOMAIN_SALT;
+        self.ref_count = 0;
+        self.created_slot = slot;
+        self.provenance = HandleProvenance::Local;
+        self.source_program = program_id;
+        self.frozen = false;
+        self.bond_lamports = 0;
+        self.first_used_slot = None;
+        self.bump = bump;
+        self.seal_provenance();
+    }
+
+    pub fn parents(&self) -> &[Handle] {
+        &self.parents[..self.parent_count as usize]
+    }
+
+    pub fn parent_roots(&self) -> &[[u8; 32]] {
+        &self.parent_roots[..self.parent_count as usize]
+    }
+
+    /// Recomputes `provenance_root`; call after setting the lineage fields.
+    pub fn seal_provenance(&mut self) {
+        self.provenance_root = node_root(
+            &self.handle,
+            self.parents(),
+            self.created_slot,
+            &self.source_program,
+            self.parent_roots(),
+        );
+    }
+
+    /// Whether `bond_release_slots` have passed since the record was created.
+    pub fn release_delay_elapsed(&self, config: &Config, slot: u64) -> bool {
+        slot >= self.created_slot.saturating_add(config.bond_release_slots)
+    }
+
+    /// Whether the bond may go back to the payer: the handle has been an
+    /// operand of a successful request, or the release delay has passed.
+    /// Use as a parent does not count, since a spammer can cheaply derive
+    /// from their own handles without any request reaching an executor.
+    pub fn bond_releasable(&self, config: &Config, slot: u64) -> bool {
+        self.first_used_slot.is_some() || self.release_delay_elapsed(config, slot)
+    }
+}
+
+impl CallerNonce {
+    /// Accepts `nonce` only if it is strictly greater than the last one seen.
+    pub fn check(&self, nonce: u64) -> Result<()> {
+        require!(nonce > self.last_nonce, ExecutorError::NonceReused);
+        Ok(())
+    }
+
+    pub fn advance(&mut self, nonce: u64) -> Result<()> {
+        self.check(nonce)?;
+        self.last_nonce = nonce;
+        Ok(())
+    }
+}
+
+/// Like `consume_caller_nonce` but leaves the nonce account untouched.
+pub fn check_caller_nonce(
+    nonce_account: &Option<Account<CallerNonce>>,
+    caller_nonce: Option<u64>,
+) -> Result<()> {
+    let Some(nonce) = caller_nonce else {
+        return Ok(());
+    };
+    nonce_account
+        .as_ref()
+        .ok_or(ExecutorError::MissingNonceAccount)?
+        .check(nonce)
+}
+
+/// Checks an optional request nonce against the caller's nonce account.
+/// Only a signing caller (a wallet, or a program's PDA via `invoke_signed`)
+/// may advance its nonce, since the account is keyed by the caller address.
+pub fn consume_caller_nonce(
+    nonce_account: &mut Option<Account<CallerNonce>>,
+    caller_nonce: Option<u64>,
+    caller_signed: bool,
+) -> Result<()> {
+    let Some(nonce) = caller_nonce else {
+        return Ok(());
+    };
+    require!(caller_signed, ExecutorError::NonceRequiresSigner);
+    let nonce_account = nonce_account
+        .as_mut()
+        .ok_or(ExecutorError::MissingNonceAccount)?;
+    nonce_account.advance(nonce)
+}
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use solana_sha256_hasher::hashv;
+
+use crate::errors::ExecutorError;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum Fhe16UnaryOp {
@@ -7,43 +10,329 @@ pub enum Fhe16UnaryOp {
     Neg, // FHE16_NEG
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum Fhe16BinaryOp {
-    // Logic
-    And, // C_FHE16_AND
-    Or,  // C_FHE16_OR
-    Xor, // C_FHE16_XOR
-    // Arithmetic
-    Add,  // FHE16_ADD
-    Sub,  // FHE16_SUB
-    SDiv, // FHE16_SDIV
-    // Comparison
-    Eq,      // FHE16_EQ
-    Neq,     // FHE16_NEQ
-    Gt,      // FHE16_GT
-    Ge,      // FHE16_GE
-    Lt,      // FHE16_LT
-    Le,      // FHE16_LE
-    Max,     // FHE16_MAX
-    Min,     // FHE16_MIN
-    MaxOrMin, // FHE16_MAXorMIN
-    Compare, // FHE16_COMPARE
-    // Vector
-    OrVec,  // FHE16_ORVEC
-    AndVec, // FHE16_ANDVEC
-    XorVec, // FHE16_XORVEC
-    // Shift
-    LShiftL, // FHE16_LSHIFTL
-    // Other
-    SMulL,          // FHE16_SMULL
-    AddPowTwo,      // FHE16_ADD_POWTWO
-    SubPowTwo,      // FHE16_SUB_POWTWO
-    GateTemplete,   // FHE16_GATE_TEMPLETE
-    PrefixTemplete, // FHE16_PREFIX_Templete
-    AddPowTwoTemplete, // FHE16_ADD_POWTWO_TEMPLETE
-    // Combined
-    OrXor,  // C_FHE16_OR_XOR
-    AndXor, // C_FHE16_AND_XOR
+impl Fhe16UnaryOp {
+    /// Every variant, in discriminant order.
+    pub const ALL: [Self; 3] = [
+        Fhe16UnaryOp::Not,
+        Fhe16UnaryOp::Abs,
+        Fhe16UnaryOp::Neg,
+    ];
+
+    /// Canonical op name shared with non-Rust SDKs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Fhe16UnaryOp::Not => "NOT",
+            Fhe16UnaryOp::Abs => "ABS",
+            Fhe16UnaryOp::Neg => "NEG",
+        }
+    }
+}
+
+/// Binary op classes, so indexers can subscribe to e.g. comparisons only.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BinaryOpCategory {
+    Logic,
+    Arithmetic,
+    Comparison,
+    Vector,
+    Shift,
+    Other,
+    Combined,
+}
+
+pub use binary_op::Fhe16BinaryOp;
+
+// The misspelled `*Templete` ops and the vector ops that moved to
+// `Fhe16VectorOp` are deprecated for callers, but their discriminants are
+// hashed into handles, so the derives (expanded next to the enum, hence
+// the module) and the op tables still name them.
+#[allow(deprecated)]
+mod binary_op {
+    use anchor_lang::prelude::*;
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+    pub enum Fhe16BinaryOp {
+        // Logic
+        And, // C_FHE16_AND
+        Or,  // C_FHE16_OR
+        Xor, // C_FHE16_XOR
+        // Arithmetic
+        Add,  // FHE16_ADD
+        Sub,  // FHE16_SUB
+        SDiv, // FHE16_SDIV
+        // Comparison
+        Eq,      // FHE16_EQ
+        Neq,     // FHE16_NEQ
+        Gt,      // FHE16_GT
+        Ge,      // FHE16_GE
+        Lt,      // FHE16_LT
+        Le,      // FHE16_LE
+        Max,     // FHE16_MAX
+        Min,     // FHE16_MIN
+        MaxOrMin, // FHE16_MAXorMIN
+        Compare, // FHE16_COMPARE
+        // Vector
+        #[deprecated(note = "takes handle arrays; use request_vector_op with Fhe16VectorOp::Or")]
+        OrVec,  // FHE16_ORVEC
+        #[deprecated(note = "takes handle arrays; use request_vector_op with Fhe16VectorOp::And")]
+        AndVec, // FHE16_ANDVEC
+        #[deprecated(note = "takes handle arrays; use request_vector_op with Fhe16VectorOp::Xor")]
+        XorVec, // FHE16_XORVEC
+        // Shift
+        LShiftL, // FHE16_LSHIFTL
+        // Other
+        SMulL,          // FHE16_SMULL
+        AddPowTwo,      // FHE16_ADD_POWTWO
+        SubPowTwo,      // FHE16_SUB_POWTWO
+        #[deprecated(note = "misspelled; use Fhe16BinaryOp::GATE_TEMPLATE")]
+        GateTemplete,   // FHE16_GATE_TEMPLETE
+        #[deprecated(note = "misspelled; use Fhe16BinaryOp::PREFIX_TEMPLATE")]
+        PrefixTemplete, // FHE16_PREFIX_Templete
+        #[deprecated(note = "misspelled; use Fhe16BinaryOp::ADD_POW_TWO_TEMPLATE")]
+        AddPowTwoTemplete, // FHE16_ADD_POWTWO_TEMPLETE
+        // Combined
+        OrXor,  // C_FHE16_OR_XOR
+        AndXor, // C_FHE16_AND_XOR
+    }
+}
+
+#[allow(deprecated)]
+impl Fhe16BinaryOp {
+    /// Correctly spelled aliases for the `*Templete` variants.
+    pub const GATE_TEMPLATE: Self = Fhe16BinaryOp::GateTemplete;
+    pub const PREFIX_TEMPLATE: Self = Fhe16BinaryOp::PrefixTemplete;
+    pub const ADD_POW_TWO_TEMPLATE: Self = Fhe16BinaryOp::AddPowTwoTemplete;
+
+    /// Number of result handles the op produces: MAXorMIN yields both the
+    /// max and the min, everything else (including the multi-bit COMPARE
+    /// result) is a single ciphertext.
+    ///
+    /// ```
+    /// use host_programs::types::Fhe16BinaryOp;
+    ///
+    /// assert_eq!(Fhe16BinaryOp::MaxOrMin.result_count(), 2);
+    /// assert_eq!(Fhe16BinaryOp::Add.result_count(), 1);
+    /// assert_eq!(Fhe16BinaryOp::Compare.result_count(), 1);
+    /// // no other op expects a second result event
+    /// let dual = Fhe16BinaryOp::ALL.iter().filter(|op| op.result_count() != 1);
+    /// assert_eq!(dual.count(), 1);
+    /// ```
+    pub const fn result_count(&self) -> u8 {
+        match self {
+            Fhe16BinaryOp::MaxOrMin => 2,
+            _ => 1,
+        }
+    }
+
+    /// Ops whose pairwise reduction can be regrouped freely, i.e. those
+    /// `request_fold` accepts.
+    pub fn is_associative(&self) -> bool {
+        matches!(
+            self,
+            Fhe16BinaryOp::Add
+                | Fhe16BinaryOp::And
+                | Fhe16BinaryOp::Or
+                | Fhe16BinaryOp::Xor
+                | Fhe16BinaryOp::Max
+                | Fhe16BinaryOp::Min
+        )
+    }
+
+    /// Op class, following the grouping of the variants above.
+    pub fn category(&self) -> BinaryOpCategory {
+        use Fhe16BinaryOp::*;
+        match self {
+            And | Or | Xor => BinaryOpCategory::Logic,
+            Add | Sub | SDiv => BinaryOpCategory::Arithmetic,
+            Eq | Neq | Gt | Ge | Lt | Le | Max | Min | MaxOrMin | Compare => {
+                BinaryOpCategory::Comparison
+            }
+            OrVec | AndVec | XorVec => BinaryOpCategory::Vector,
+            LShiftL => BinaryOpCategory::Shift,
+            SMulL | AddPowTwo | SubPowTwo | GateTemplete | PrefixTemplete
+            | AddPowTwoTemplete => BinaryOpCategory::Other,
+            OrXor | AndXor => BinaryOpCategory::Combined,
+        }
+    }
+
+    /// The element-wise vector ops, which need handle arrays and are only
+    /// accepted through `request_vector_op`.
+    pub fn is_vector(&self) -> bool {
+        self.category() == BinaryOpCategory::Vector
+    }
+
+    /// Template ops, which executors do not implement yet.
+    pub fn is_template(&self) -> bool {
+        matches!(
+            self,
+            Fhe16BinaryOp::GateTemplete
+                | Fhe16BinaryOp::PrefixTemplete
+                | Fhe16BinaryOp::AddPowTwoTemplete
+        )
+    }
+
+    /// Every variant, in discriminant order.
+    pub const ALL: [Self; 28] = [
+        Fhe16BinaryOp::And,
+        Fhe16BinaryOp::Or,
+        Fhe16BinaryOp::Xor,
+        Fhe16BinaryOp::Add,
+        Fhe16BinaryOp::Sub,
+        Fhe16BinaryOp::SDiv,
+        Fhe16BinaryOp::Eq,
+        Fhe16BinaryOp::Neq,
+        Fhe16BinaryOp::Gt,
+        Fhe16BinaryOp::Ge,
+        Fhe16BinaryOp::Lt,
+        Fhe16BinaryOp::Le,
+        Fhe16BinaryOp::Max,
+        Fhe16BinaryOp::Min,
+        Fhe16BinaryOp::MaxOrMin,
+        Fhe16BinaryOp::Compare,
+        Fhe16BinaryOp::OrVec,
+        Fhe16BinaryOp::AndVec,
+        Fhe16BinaryOp::XorVec,
+        Fhe16BinaryOp::LShiftL,
+        Fhe16BinaryOp::SMulL,
+        Fhe16BinaryOp::AddPowTwo,
+        Fhe16BinaryOp::SubPowTwo,
+        Fhe16BinaryOp::GateTemplete,
+        Fhe16BinaryOp::PrefixTemplete,
+        Fhe16BinaryOp::AddPowTwoTemplete,
+        Fhe16BinaryOp::OrXor,
+        Fhe16BinaryOp::AndXor,
+    ];
+
+    /// Canonical op name shared with non-Rust SDKs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Fhe16BinaryOp::And => "AND",
+            Fhe16BinaryOp::Or => "OR",
+            Fhe16BinaryOp::Xor => "XOR",
+            Fhe16BinaryOp::Add => "ADD",
+            Fhe16BinaryOp::Sub => "SUB",
+            Fhe16BinaryOp::SDiv => "SDIV",
+            Fhe16BinaryOp::Eq => "EQ",
+            Fhe16BinaryOp::Neq => "NEQ",
+            Fhe16BinaryOp::Gt => "GT",
+            Fhe16BinaryOp::Ge => "GE",
+            Fhe16BinaryOp::Lt => "LT",
+            Fhe16BinaryOp::Le => "LE",
+            Fhe16BinaryOp::Max => "MAX",
+            Fhe16BinaryOp::Min => "MIN",
+            Fhe16BinaryOp::MaxOrMin => "MAXORMIN",
+            Fhe16BinaryOp::Compare => "COMPARE",
+            Fhe16BinaryOp::OrVec => "ORVEC",
+            Fhe16BinaryOp::AndVec => "ANDVEC",
+            Fhe16BinaryOp::XorVec => "XORVEC",
+            Fhe16BinaryOp::LShiftL => "LSHIFTL",
+            Fhe16BinaryOp::SMulL => "SMULL",
+            Fhe16BinaryOp::AddPowTwo => "ADD_POWTWO",
+            Fhe16BinaryOp::SubPowTwo => "SUB_POWTWO",
+            Fhe16BinaryOp::GateTemplete => "GATE_TEMPLATE",
+            Fhe16BinaryOp::PrefixTemplete => "PREFIX_TEMPLATE",
+            Fhe16BinaryOp::AddPowTwoTemplete => "ADD_POWTWO_TEMPLATE",
+            Fhe16BinaryOp::OrXor => "OR_XOR",
+            Fhe16BinaryOp::AndXor => "AND_XOR",
+        }
+    }
+
+    /// Ops whose result is a constant when both operands are the same
+    /// handle (e.g. SUB(x, x) = 0, GT(x, x) = false), so requesting them
+    /// that way is almost always a wiring bug. Idempotent ops (AND, OR,
+    /// MAX, MIN) and ADD(x, x) doubling stay allowed.
+    pub fn is_trivial_on_equal_operands(&self) -> bool {
+        matches!(
+            self,
+            Fhe16BinaryOp::Xor
+                | Fhe16BinaryOp::Sub
+                | Fhe16BinaryOp::SDiv
+                | Fhe16BinaryOp::Eq
+                | Fhe16BinaryOp::Neq
+                | Fhe16BinaryOp::Gt
+                | Fhe16BinaryOp::Ge
+                | Fhe16BinaryOp::Lt
+                | Fhe16BinaryOp::Le
+                | Fhe16BinaryOp::Compare
+        )
+    }
+}
+
+/// Prints the canonical name, so the corrected `*_TEMPLATE` spelling.
+impl core::fmt::Display for Fhe16BinaryOp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses canonical names and the legacy `*_TEMPLETE` spellings.
+impl core::str::FromStr for Fhe16BinaryOp {
+    type Err = ExecutorError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        let legacy = match s {
+            "GATE_TEMPLETE" => Some(Self::GATE_TEMPLATE),
+            "PREFIX_TEMPLETE" => Some(Self::PREFIX_TEMPLATE),
+            "ADD_POWTWO_TEMPLETE" => Some(Self::ADD_POW_TWO_TEMPLATE),
+            _ => None,
+        };
+        legacy
+            .or_else(|| Self::ALL.iter().copied().find(|op| op.name() == s))
+            .ok_or(ExecutorError::UnknownOpName)
+    }
+}
+
+/// Element-wise ops over two equally long handle arrays
+/// (`request_vector_op`); each element pair yields its own result.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fhe16VectorOp {
+    Or,  // FHE16_ORVEC
+    And, // FHE16_ANDVEC
+    Xor, // FHE16_XORVEC
+}
+
+impl Fhe16VectorOp {
+    /// Every variant, in discriminant order.
+    pub const ALL: [Self; 3] = [Fhe16VectorOp::Or, Fhe16VectorOp::And, Fhe16VectorOp::Xor];
+
+    /// Canonical op name shared with non-Rust SDKs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Fhe16VectorOp::Or => "ORVEC",
+            Fhe16VectorOp::And => "ANDVEC",
+            Fhe16VectorOp::Xor => "XORVEC",
+        }
+    }
+
+    /// Scalar op applied to each element pair, for the operand checks.
+    pub fn element_op(&self) -> Fhe16BinaryOp {
+        match self {
+            Fhe16VectorOp::Or => Fhe16BinaryOp::Or,
+            Fhe16VectorOp::And => Fhe16BinaryOp::And,
+            Fhe16VectorOp::Xor => Fhe16BinaryOp::Xor,
+        }
+    }
+}
+
+/// Weights of a dot product (`request_dot_product`): encrypted handles, or
+/// plaintext scalars that are hashed into the result handle.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum DotWeights {
+    Encrypted(Vec<Handle>),
+    Scalar(Vec<u16>),
+}
+
+impl DotWeights {
+    pub fn len(&self) -> usize {
+        match self {
+            DotWeights::Encrypted(weights) => weights.len(),
+            DotWeights::Scalar(weights) => weights.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
@@ -55,4 +344,194 @@ pub enum Fhe16TernaryOp {
     Select, // FHE16_SELECT
 }
 
+impl Fhe16TernaryOp {
+    /// Every variant, in discriminant order.
+    pub const ALL: [Self; 5] = [
+        Fhe16TernaryOp::Add3,
+        Fhe16TernaryOp::Eq3,
+        Fhe16TernaryOp::Maj3,
+        Fhe16TernaryOp::Xor3,
+        Fhe16TernaryOp::Select,
+    ];
+
+    /// Canonical op name shared with non-Rust SDKs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Fhe16TernaryOp::Add3 => "ADD3",
+            Fhe16TernaryOp::Eq3 => "EQ3",
+            Fhe16TernaryOp::Maj3 => "MAJ3",
+            Fhe16TernaryOp::Xor3 => "XOR3",
+            Fhe16TernaryOp::Select => "SELECT",
+        }
+    }
+}
+
+// ALL must list variants in discriminant order; op_table relies on it.
+macro_rules! assert_dense_discriminants {
+    ($op:ty) => {
+        const _: () = {
+            let mut i = 0;
+            while i < <$op>::ALL.len() {
+                assert!(<$op>::ALL[i] as usize == i);
+                i += 1;
+            }
+        };
+    };
+}
+assert_dense_discriminants!(Fhe16UnaryOp);
+assert_dense_discriminants!(Fhe16BinaryOp);
+assert_dense_discriminants!(Fhe16VectorOp);
+assert_dense_discriminants!(Fhe16TernaryOp);
+
+// Discriminants are hashed into handles; the deprecated spellings must keep
+// their codes even though their names changed.
+const _: () = {
+    assert!(Fhe16BinaryOp::GATE_TEMPLATE as u8 == 23);
+    assert!(Fhe16BinaryOp::PREFIX_TEMPLATE as u8 == 24);
+    assert!(Fhe16BinaryOp::ADD_POW_TWO_TEMPLATE as u8 == 25);
+};
+
+/// `(name, code, arity)` for every op, the source of truth for SDK codegen.
+/// `code` is the Borsh discriminant, i.e. the op byte hashed into handles.
+///
+/// The const checks above only see what `ALL` lists. Borsh knows every
+/// variant, so the first code past `ALL` must not decode:
+///
+/// ```
+/// use anchor_lang::AnchorDeserialize;
+/// use host_programs::types::*;
+///
+/// fn assert_complete<T: AnchorDeserialize>(all: &[T]) {
+///     for code in 0..all.len() {
+///         assert!(T::try_from_slice(&[code as u8]).is_ok());
+///     }
+///     let missing = T::try_from_slice(&[all.len() as u8]);
+///     assert!(missing.is_err(), "a variant is missing from ALL");
+/// }
+///
+/// assert_complete(&Fhe16UnaryOp::ALL);
+/// assert_complete(&Fhe16BinaryOp::ALL);
+/// assert_complete(&Fhe16VectorOp::ALL);
+/// assert_complete(&Fhe16TernaryOp::ALL);
+/// ```
+pub fn op_table() -> Vec<(&'static str, u8, u8)> {
+    let unary = Fhe16UnaryOp::ALL.iter().map(|op| (op.name(), *op as u8, 1));
+    let binary = Fhe16BinaryOp::ALL.iter().map(|op| (op.name(), *op as u8, 2));
+    let ternary = Fhe16TernaryOp::ALL.iter().map(|op| (op.name(), *op as u8, 3));
+    unary.chain(binary).chain(ternary).collect()
+}
+
 pub type Handle = [u8; 32];
+
+/// Byte length of the handles in use today.
+pub const HANDLE_LEN: usize = 32;
+
+/// Handle of `N` bytes, for code that must not assume today's length (a
+/// later scheme may need wider commitments). Without `N` it is `Handle`.
+pub type HandleBytes<const N: usize = HANDLE_LEN> = [u8; N];
+
+const _: fn(Handle) -> HandleBytes = |handle| handle;
+
+/// Widest plaintext (in bits) a FHE16 ciphertext can carry.
+pub const FHE16_MAX_WIDTH: u8 = 16;
+
+/// Constants pre-registered by `initialize` (see `constant_handle`).
+pub const FHE16_CONST_ZERO: u16 = 0;
+pub const FHE16_CONST_ONE: u16 = 1;
+pub const FHE16_CONST_MAX: u16 = u16::MAX;
+
+/// Why lamports moved, as reported by `LamportsMoved`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveReason {
+    Fee,
+    Refund,
+    Slash,
+    TreasuryWithdrawal,
+    /// Rent returned to the payer when a program account is closed.
+    RentReclaim,
+    /// Registration bond escrowed into a handle record.
+    Bond,
+}
+
+/// Returned by `export_handle`; everything `import_handle` needs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ExportReceipt {
+    pub handle: Handle,
+    pub source_program: Pubkey,
+    pub owner: Pubkey,
+    pub proof: [u8; 32],
+}
+
+const CLIENT_TAG_LABEL_DOMAIN: &[u8] = b"FHE16_CLIENT_TAG_LABEL_V1";
+
+/// `client_tag` attached to an input registration.
+///
+/// Prefix convention (same 32-byte wire format as `[u8; 32]`):
+/// - all zeros: no tag ([`ClientTag::NONE`])
+/// - `0xFF 0xFF 0xFF 0xFF ..`: reserved for protocol use, rejected
+/// - `0xFE ..`: hashed human label ([`ClientTag::from_str_label`])
+/// - anything else: opaque client-chosen bytes
+///
+/// ```
+/// use host_programs::types::ClientTag;
+///
+/// let mut tag = [0u8; 32];
+/// tag[..3].copy_from_slice(&[0xFF; 3]);
+/// assert!(!ClientTag(tag).is_reserved());
+/// tag[3] = 0xFF;
+/// assert!(ClientTag(tag).is_reserved());
+/// assert!(!ClientTag::NONE.is_reserved() && ClientTag::NONE.is_none());
+///
+/// // label hashing is part of the wire format: pinned, not just repeatable
+/// let label = ClientTag::from_str_label("order-42");
+/// assert!(label.is_labeled() && !label.is_reserved());
+/// assert_eq!(
+///     label.to_string(),
+///     "fe86c06f40278c7a5d6d5d65a49d6ddfc061656d05dcc82242ae0a7ac5bf7cf8"
+/// );
+/// assert!(label != ClientTag::from_str_label("order-43"));
+/// ```
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ClientTag(pub [u8; 32]);
+
+impl ClientTag {
+    pub const NONE: ClientTag = ClientTag([0u8; 32]);
+    pub const RESERVED_PREFIX: [u8; 4] = [0xFF; 4];
+    pub const LABEL_PREFIX: u8 = 0xFE;
+
+    /// Deterministic tag for a human-readable label such as `"order-42"`.
+    pub fn from_str_label(label: &str) -> Self {
+        let hash = hashv(&[CLIENT_TAG_LABEL_DOMAIN, label.as_bytes()]).to_bytes();
+        let mut tag = [0u8; 32];
+        tag[0] = Self::LABEL_PREFIX;
+        tag[1..].copy_from_slice(&hash[..31]);
+        ClientTag(tag)
+    }
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    pub fn is_reserved(&self) -> bool {
+        self.0.starts_with(&Self::RESERVED_PREFIX)
+    }
+
+    pub fn is_labeled(&self) -> bool {
+        self.0[0] == Self::LABEL_PREFIX
+    }
+}
+
+impl From<[u8; 32]> for ClientTag {
+    fn from(bytes: [u8; 32]) -> Self {
+        ClientTag(bytes)
+    }
+}
+
+impl core::fmt::Display for ClientTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
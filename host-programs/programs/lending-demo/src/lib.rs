@@ -1,11 +1,24 @@
 // ⚠️ WARNING: This is a minimal implementation for on-chain event logging testing.
 // This is NOT production code. Current design:
 // - Chained operations in single transaction
-// - CPI calls to host-programs for event emission
-// - Deterministic handle derivation (same as host-programs)
-// - Pure stateless event machine for testing
+// - Every op is a real request CPI into host-programs (cpi_builder); the
+//   demo keeps the result handles the host returns, and `withdraw_handles`
+//   reproduces withdraw's chain off-chain
+// - Near-stateless: only demo config, referral, attestation and position PDAs
+//   are stored
 //
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use host_programs::cpi::accounts::{RequestBinaryOp, RequestTernaryOp};
+use host_programs::cpi_builder::{BinaryCpi, TernaryCpi};
+use host_programs::handle::{
+    constant_handle, derive_binary_handle, derive_ternary_handle, DomainSalt, DOMAIN_SALT_LEN,
+};
+use host_programs::state::{
+    Config as HostConfig, WorkCursor as HostWorkCursor, CONFIG_SEED as HOST_CONFIG_SEED,
+    WORK_CURSOR_SEED as HOST_WORK_CURSOR_SEED,
+};
+use host_programs::types::{Fhe16BinaryOp, Fhe16TernaryOp, FHE16_CONST_ZERO};
 use solana_sha256_hasher::hashv;
 
 declare_id!("fJBJDymb2ZbFoQguniP5pDLDTJYqVMACktZW7ZEeGRt");
@@ -14,53 +27,104 @@ const HOST_PROGRAM_ID: Pubkey = pubkey!("FkLGYGk2bypUXgpGmcsCTmKZo6LCjHaXswbhY1L
 
 pub type Handle = [u8; 32];
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
-pub enum Fhe16BinaryOp {
-    Add,
-    Sub,
-    Ge,
+pub const DEMO_CONFIG_SEED: &[u8] = b"demo_config";
+pub const REFERRAL_SEED: &[u8] = b"referral";
+pub const ATTESTATION_SEED: &[u8] = b"attestation";
+pub const POSITION_SEED: &[u8] = b"position";
+
+/// host-programs handle derivation scheme the demo starts on (FHE16_*_V1).
+pub const INITIAL_DERIVATION_VERSION: u8 = 1;
+
+/// Bits of `DemoConfig::paused_instructions`; a set bit halts that instruction.
+pub const PAUSE_DEPOSIT: u16 = 1 << 0;
+pub const PAUSE_WITHDRAW: u16 = 1 << 1;
+pub const PAUSE_BORROW: u16 = 1 << 2;
+pub const PAUSE_REPAY: u16 = 1 << 3;
+pub const PAUSE_LIQUIDATE: u16 = 1 << 4;
+
+const TRACE_DOMAIN: &[u8] = b"LENDING_TRACE_V1";
+
+/// Handles `withdraw` derives when KYC is not required, in request order.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WithdrawHandles {
+    /// GE(usdc_balance, withdraw_amount)
+    pub ge: Handle,
+    /// SUB(usdc_balance, withdraw_amount)
+    pub sub: Handle,
+    /// SELECT(ge, sub, usdc_balance)
+    pub final_handle: Handle,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
-pub enum Fhe16TernaryOp {
-    Select,
+/// Reproduces the handle chain of `withdraw` so clients can show expected
+/// results before sending the transaction. With `kyc_required` set the
+/// on-chain SELECT is gated by AND(ge, EQ(attestation, expected)) instead,
+/// so only `ge` and `sub` carry over. `salt` is the host's domain salt, as
+/// recorded in `DemoConfig::domain_salt`.
+///
+/// ```
+/// use anchor_lang::prelude::Pubkey;
+/// use host_programs::handle;
+/// use host_programs::types::{Fhe16BinaryOp, Fhe16TernaryOp};
+///
+/// let host_pid = Pubkey::new_from_array([7u8; 32]);
+/// let salt = [9u8; 16]; // DemoConfig::domain_salt after a rotation
+/// let (balance, amount) = ([1u8; 32], [2u8; 32]);
+/// let handles = lending_demo::withdraw_handles(balance, amount, &host_pid, &salt);
+///
+/// let ge = handle::derive_binary_handle(Fhe16BinaryOp::Ge, &balance, &amount, &host_pid, &salt);
+/// let sub = handle::derive_binary_handle(Fhe16BinaryOp::Sub, &balance, &amount, &host_pid, &salt);
+/// let select = handle::derive_ternary_handle(
+///     Fhe16TernaryOp::Select, &ge, &sub, &balance, &host_pid, &salt,
+/// );
+/// assert_eq!(handles.ge, ge);
+/// assert_eq!(handles.sub, sub);
+/// assert_eq!(handles.final_handle, select);
+///
+/// // the salt is part of every handle
+/// let unsalted = lending_demo::withdraw_handles(balance, amount, &host_pid, &[0; 16]);
+/// assert!(unsalted.ge != handles.ge);
+/// ```
+pub fn withdraw_handles(
+    usdc_balance: Handle,
+    withdraw_amount: Handle,
+    host_pid: &Pubkey,
+    salt: &DomainSalt,
+) -> WithdrawHandles {
+    let (balance, amount) = (&usdc_balance, &withdraw_amount);
+    let ge = derive_binary_handle(Fhe16BinaryOp::Ge, balance, amount, host_pid, salt);
+    let sub = derive_binary_handle(Fhe16BinaryOp::Sub, balance, amount, host_pid, salt);
+    let final_handle =
+        derive_ternary_handle(Fhe16TernaryOp::Select, &ge, &sub, balance, host_pid, salt);
+    WithdrawHandles { ge, sub, final_handle }
 }
 
-const HANDLE_DOMAIN_BINARY: &[u8] = b"FHE16_BINARY_V1";
-const HANDLE_DOMAIN_TERNARY: &[u8] = b"FHE16_TERNARY_V1";
+/// Correlates the events of one composed instruction. The slot keeps
+/// repeated calls with the same inputs apart.
+fn pipeline_trace_id(caller: &Pubkey, inputs: &[&Handle], slot: u64) -> [u8; 16] {
+    let slot = slot.to_le_bytes();
+    let mut parts: Vec<&[u8]> = vec![TRACE_DOMAIN, caller.as_ref(), &slot];
+    parts.extend(inputs.iter().map(|handle| handle.as_slice()));
+    let hash = hashv(&parts).to_bytes();
+    let mut trace_id = [0u8; 16];
+    trace_id.copy_from_slice(&hash[..16]);
+    trace_id
+}
 
-pub fn derive_binary_handle(
-    op: Fhe16BinaryOp,
-    lhs: &[u8; 32],
-    rhs: &[u8; 32],
-    program_id: &Pubkey,
-) -> [u8; 32] {
-    let op_byte = [op as u8];
-    hashv(&[
-        HANDLE_DOMAIN_BINARY,
-        program_id.as_ref(),
-        &op_byte,
-        lhs,
-        rhs,
-    ]).to_bytes()
-}
-
-pub fn derive_ternary_handle(
-    op: Fhe16TernaryOp,
-    a: &[u8; 32],
-    b: &[u8; 32],
-    c: &[u8; 32],
-    program_id: &Pubkey,
-) -> [u8; 32] {
-    let op_byte = [op as u8];
-    hashv(&[
-        HANDLE_DOMAIN_TERNARY,
-        program_id.as_ref(),
-        &op_byte,
-        a,
-        b,
-        c,
-    ]).to_bytes()
+/// Borrows the host accounts out of an instruction's `Accounts` struct,
+/// which all declare them under the same names; `$caller` is the account
+/// the requests are made for.
+macro_rules! host_cpi {
+    ($accounts:expr, $caller:ident) => {
+        HostCpi {
+            host_programs: &$accounts.host_programs,
+            caller: &$accounts.$caller,
+            config: $accounts.host_config.as_ref(),
+            work_cursor: $accounts.host_work_cursor.as_ref(),
+            activity: $accounts.host_activity.as_deref(),
+            instructions: $accounts.instructions.as_deref(),
+            trace_id: None,
+        }
+    };
 }
 
 #[program]
@@ -75,71 +139,180 @@ pub mod lending_demo {
         Ok(())
     }
 
+    /// expected_attestation_handle: KYC 통과를 뜻하는 암호화된 상수
+    pub fn initialize_demo_config(
+        ctx: Context<InitializeDemoConfig>,
+        attestor: Pubkey,
+        expected_attestation_handle: [u8; 32],
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.demo_config;
+        config.admin = ctx.accounts.admin.key();
+        config.attestor = attestor;
+        config.expected_attestation_handle = expected_attestation_handle;
+        config.kyc_required = false;
+        config.paused_instructions = 0;
+        config.migration_authority = config.admin;
+        config.derivation_version = INITIAL_DERIVATION_VERSION;
+        config.domain_salt = ctx.accounts.host_config.domain_salt;
+        config.ltv_factor = [0u8; 32];
+        config.liquidation_factor = [0u8; 32];
+        config.liquidation_keeper = config.admin;
+        config.bump = ctx.bumps.demo_config;
+        Ok(())
+    }
+
+    pub fn set_kyc_required(ctx: Context<UpdateDemoConfig>, kyc_required: bool) -> Result<()> {
+        ctx.accounts.demo_config.kyc_required = kyc_required;
+        Ok(())
+    }
+
+    pub fn set_migration_authority(
+        ctx: Context<UpdateDemoConfig>,
+        migration_authority: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.demo_config.migration_authority = migration_authority;
+        Ok(())
+    }
+
+    /// compute_ltv 가 쓰는 암호화된 LTV 한도 비율
+    pub fn set_ltv_factor(ctx: Context<UpdateDemoConfig>, ltv_factor: [u8; 32]) -> Result<()> {
+        ctx.accounts.demo_config.ltv_factor = ltv_factor;
+        Ok(())
+    }
+
+    /// check_liquidatable 이 쓰는 암호화된 청산 기준 비율
+    pub fn set_liquidation_factor(
+        ctx: Context<UpdateDemoConfig>,
+        liquidation_factor: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.demo_config.liquidation_factor = liquidation_factor;
+        Ok(())
+    }
+
+    pub fn set_liquidation_keeper(
+        ctx: Context<UpdateDemoConfig>,
+        liquidation_keeper: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.demo_config.liquidation_keeper = liquidation_keeper;
+        Ok(())
+    }
+
+    /// host-programs derivation domain 이 바뀌면 올린다 (감소 불가). 새 version 은
+    /// host 의 현재 domain_salt 로 derive 하고, 이전 version 의 position 은
+    /// migrate_position 전까지 사용 불가
+    pub fn set_derivation_version(ctx: Context<SetDerivationVersion>, version: u8) -> Result<()> {
+        let config = &mut ctx.accounts.demo_config;
+        require!(
+            version > config.derivation_version,
+            LendingError::DerivationVersionMismatch
+        );
+        config.derivation_version = version;
+        config.domain_salt = ctx.accounts.host_config.domain_salt;
+        Ok(())
+    }
+
+    /// mask 비트 의미는 PAUSE_* 상수 참고
+    pub fn set_paused_mask(ctx: Context<UpdateDemoConfig>, mask: u16) -> Result<()> {
+        let config = &mut ctx.accounts.demo_config;
+        let old = config.paused_instructions;
+        config.paused_instructions = mask;
+
+        emit!(PauseMaskChanged { old, new: mask });
+
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // KYC: attestor 가 user 별 암호화된 attestation handle 을 기록
+    // -------------------------------------------------------------------
+    pub fn attest_user(
+        ctx: Context<AttestUser>,
+        user: Pubkey,
+        attestation_handle: [u8; 32],
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.user = user;
+        attestation.handle = attestation_handle;
+        attestation.bump = ctx.bumps.attestation;
+
+        emit!(UserAttested {
+            user,
+            attestor: ctx.accounts.attestor.key(),
+            attestation_handle,
+        });
+
+        Ok(())
+    }
+
     // -------------------------------------------------------------------
     // 1) Withdraw: Conditional USDC deduction
     // -------------------------------------------------------------------
     pub fn withdraw(
-        ctx: Context<LendingDemo>,
+        ctx: Context<Withdraw>,
         usdc_balance: [u8; 32],
         withdraw_amount: [u8; 32],
     ) -> Result<()> {
+        ctx.accounts.demo_config.check_not_paused(PAUSE_WITHDRAW)?;
         let caller = ctx.accounts.caller.key();
-        let host_pid = ctx.accounts.host_programs.key();
 
-        // GE(usdc_balance, withdraw_amount)
-        let ge_handle = derive_binary_handle(
-            Fhe16BinaryOp::Ge, 
-            &usdc_balance, 
-            &withdraw_amount, 
-            &host_pid
+        // GE, SUB, SELECT (+ EQ, AND under KYC)
+        let trace_id = pipeline_trace_id(
+            &caller,
+            &[&usdc_balance, &withdraw_amount],
+            Clock::get()?.slot,
         );
-        trigger_binary_cpi(
-            &ctx.accounts.host_programs,
-            &ctx.accounts.caller,
-            Fhe16BinaryOp::Ge,
-            usdc_balance,
-            withdraw_amount
-        )?;
+        let op_count = if ctx.accounts.demo_config.kyc_required { 5 } else { 3 };
+        emit!(PipelineStarted { trace_id, op_count });
+        let host = HostCpi {
+            trace_id: Some(trace_id),
+            ..host_cpi!(ctx.accounts, caller)
+        };
+
+        // GE(usdc_balance, withdraw_amount)
+        let ge_handle =
+            trigger_binary_cpi(&host, Fhe16BinaryOp::Ge, usdc_balance, withdraw_amount)?;
 
         // SUB(usdc_balance, withdraw_amount)
-        let sub_handle = derive_binary_handle(
-            Fhe16BinaryOp::Sub, 
-            &usdc_balance, 
-            &withdraw_amount, 
-            &host_pid
-        );
-        trigger_binary_cpi(
-            &ctx.accounts.host_programs,
-            &ctx.accounts.caller,
-            Fhe16BinaryOp::Sub,
-            usdc_balance,
-            withdraw_amount
-        )?;
+        let sub_handle =
+            trigger_binary_cpi(&host, Fhe16BinaryOp::Sub, usdc_balance, withdraw_amount)?;
 
-        // SELECT(ge_handle, sub_handle, usdc_balance)
-        let final_handle = derive_ternary_handle(
-            Fhe16TernaryOp::Select,
-            &ge_handle,
-            &sub_handle,
-            &usdc_balance,
-            &host_pid
-        );
-        trigger_ternary_cpi(
-            &ctx.accounts.host_programs,
-            &ctx.accounts.caller,
+        // KYC 필요 시: AND(ge_handle, EQ(attestation, expected)) 를 조건으로 사용
+        let (condition_handle, kyc_result_handle) = if ctx.accounts.demo_config.kyc_required {
+            let attestation = ctx
+                .accounts
+                .attestation
+                .as_ref()
+                .ok_or(LendingError::MissingAttestation)?;
+            require_keys_eq!(attestation.user, caller, LendingError::MissingAttestation);
+            let expected = ctx.accounts.demo_config.expected_attestation_handle;
+
+            let eq_handle =
+                trigger_binary_cpi(&host, Fhe16BinaryOp::Eq, attestation.handle, expected)?;
+            let and_handle =
+                trigger_binary_cpi(&host, Fhe16BinaryOp::And, ge_handle, eq_handle)?;
+            (and_handle, Some(eq_handle))
+        } else {
+            (ge_handle, None)
+        };
+
+        // SELECT(condition_handle, sub_handle, usdc_balance)
+        let final_handle = trigger_ternary_cpi(
+            &host,
             Fhe16TernaryOp::Select,
-            ge_handle,
+            condition_handle,
             sub_handle,
-            usdc_balance
+            usdc_balance,
         )?;
 
         emit!(WithdrawCompleted {
+            trace_id,
             caller,
             usdc_balance,
             withdraw_amount,
             ge_result_handle: ge_handle,
             sub_result_handle: sub_handle,
             final_handle,
+            kyc_result_handle,
         });
 
         Ok(())
@@ -149,30 +322,162 @@ pub mod lending_demo {
     // 2) Deposit: SOL balance addition
     // -------------------------------------------------------------------
     pub fn deposit(
-        ctx: Context<LendingDemo>,
+        ctx: Context<Deposit>,
         sol_balance: [u8; 32],
         deposit_amount: [u8; 32],
+        referrer: Option<Pubkey>,
     ) -> Result<()> {
+        ctx.accounts.demo_config.check_not_paused(PAUSE_DEPOSIT)?;
         let caller = ctx.accounts.caller.key();
-        let host_pid = ctx.accounts.host_programs.key();
+        let host = host_cpi!(ctx.accounts, caller);
 
-        let final_handle = derive_binary_handle(
-            Fhe16BinaryOp::Add,
-            &sol_balance,
-            &deposit_amount,
-            &host_pid
-        );
+        let final_handle =
+            trigger_binary_cpi(&host, Fhe16BinaryOp::Add, sol_balance, deposit_amount)?;
 
-        trigger_binary_cpi(
-            &ctx.accounts.host_programs,
-            &ctx.accounts.caller,
-            Fhe16BinaryOp::Add,
+        emit!(DepositCompleted {
+            caller,
             sol_balance,
-            deposit_amount
+            deposit_amount,
+            final_handle,
+        });
+
+        // 추천인 누적: ADD(ref_total, deposit_amount) → 금액은 암호화된 채로 유지
+        if let Some(referrer) = referrer {
+            require_keys_neq!(referrer, caller, LendingError::SelfReferral);
+            // 남의 이름으로 추천 금액을 부풀릴 수 없도록 depositor 서명 필요
+            require!(ctx.accounts.caller.is_signer, LendingError::ReferralRequiresSigner);
+            let referee = ctx.accounts.referee.as_ref().ok_or(LendingError::NotReferred)?;
+            require_keys_eq!(referee.referrer, referrer, LendingError::ReferralMismatch);
+            let stats = ctx
+                .accounts
+                .referral_stats
+                .as_mut()
+                .ok_or(LendingError::MissingReferralStats)?;
+            require_keys_eq!(stats.referrer, referrer, LendingError::ReferralMismatch);
+
+            let total_handle = trigger_binary_cpi(
+                &host,
+                Fhe16BinaryOp::Add,
+                stats.total_handle,
+                deposit_amount,
+            )?;
+            stats.total_handle = total_handle;
+            stats.count = stats.count.saturating_add(1);
+
+            emit!(ReferralCredited {
+                referrer,
+                count: stats.count,
+                total_handle,
+            });
+        }
+
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // Referral: per-referrer encrypted deposit total
+    // -------------------------------------------------------------------
+    /// 누적은 host-programs 가 미리 등록한 공용 0 handle 에서 시작
+    pub fn init_referral_stats(ctx: Context<InitReferralStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.referral_stats;
+        stats.referrer = ctx.accounts.referrer.key();
+        stats.count = 0;
+        stats.total_handle = constant_handle(FHE16_CONST_ZERO, &HOST_PROGRAM_ID);
+        stats.bump = ctx.bumps.referral_stats;
+        Ok(())
+    }
+
+    /// depositor 가 referrer 를 지정 (서명 필요). Referee PDA 는 (referrer,
+    /// depositor) 당 하나라 join 은 한 번뿐이고, count 는 이후 추천 deposit 마다 오른다
+    pub fn join_referral(ctx: Context<JoinReferral>) -> Result<()> {
+        let depositor = ctx.accounts.depositor.key();
+        let stats = &ctx.accounts.referral_stats;
+        require_keys_neq!(stats.referrer, depositor, LendingError::SelfReferral);
+
+        let referee = &mut ctx.accounts.referee;
+        referee.referrer = stats.referrer;
+        referee.depositor = depositor;
+        referee.bump = ctx.bumps.referee;
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // 3) Repay: Conditional debt reduction, clamped at zero
+    // -------------------------------------------------------------------
+    pub fn repay(
+        ctx: Context<LendingDemo>,
+        debt: [u8; 32],
+        repay_amount: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.demo_config.check_not_paused(PAUSE_REPAY)?;
+        let caller = ctx.accounts.caller.key();
+        let host = host_cpi!(ctx.accounts, caller);
+
+        // GE(debt, repay_amount)
+        let ge_handle = trigger_binary_cpi(&host, Fhe16BinaryOp::Ge, debt, repay_amount)?;
+
+        // SUB(debt, repay_amount)
+        let sub_handle = trigger_binary_cpi(&host, Fhe16BinaryOp::Sub, debt, repay_amount)?;
+
+        // SELECT(ge_handle, sub_handle, 0): 과상환이면 debt = 0
+        let zero_handle = constant_handle(FHE16_CONST_ZERO, &HOST_PROGRAM_ID);
+        let final_handle = trigger_ternary_cpi(
+            &host,
+            Fhe16TernaryOp::Select,
+            ge_handle,
+            sub_handle,
+            zero_handle,
         )?;
 
-        emit!(DepositCompleted {
+        emit!(RepayCompleted {
             caller,
+            debt,
+            repay_amount,
+            ge_result_handle: ge_handle,
+            sub_result_handle: sub_handle,
+            final_handle,
+        });
+
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // 4) Positions: stored balance/debt handles
+    // -------------------------------------------------------------------
+    /// balance / debt 모두 host-programs 가 미리 등록한 공용 0 handle 로 시작
+    pub fn open_position(ctx: Context<OpenPosition>) -> Result<()> {
+        let zero_handle = constant_handle(FHE16_CONST_ZERO, &HOST_PROGRAM_ID);
+        let position = &mut ctx.accounts.position;
+        position.owner = ctx.accounts.owner.key();
+        position.balance_handle = zero_handle;
+        position.debt_handle = zero_handle;
+        position.derivation_version = ctx.accounts.demo_config.derivation_version;
+        position.bump = ctx.bumps.position;
+        Ok(())
+    }
+
+    /// ADD(position.balance_handle, deposit_amount) 결과를 position 에 저장
+    pub fn deposit_to_position(
+        ctx: Context<PositionOp>,
+        deposit_amount: [u8; 32],
+    ) -> Result<()> {
+        let config = &ctx.accounts.demo_config;
+        config.check_not_paused(PAUSE_DEPOSIT)?;
+        let host = host_cpi!(ctx.accounts, owner);
+        let position = &mut ctx.accounts.position;
+        // 다른 derivation version 의 handle 과 섞이면 안 됨
+        require!(
+            position.derivation_version == config.derivation_version,
+            LendingError::DerivationVersionMismatch
+        );
+
+        let sol_balance = position.balance_handle;
+        let final_handle =
+            trigger_binary_cpi(&host, Fhe16BinaryOp::Add, sol_balance, deposit_amount)?;
+        position.balance_handle = final_handle;
+
+        emit!(DepositCompleted {
+            caller: position.owner,
             sol_balance,
             deposit_amount,
             final_handle,
@@ -180,31 +485,188 @@ pub mod lending_demo {
 
         Ok(())
     }
+
+    /// LTV 비교 handle: GE(debt, SMulL(balance, ltv_factor)), ltv_factor 는
+    /// admin 이 설정한 DemoConfig 값. position 을 바꾸지 않으므로 누구나 호출
+    /// 가능 (frontend 용 view)
+    pub fn compute_ltv(ctx: Context<ComputeLtv>) -> Result<[u8; 32]> {
+        let position = &ctx.accounts.position;
+        require!(
+            position.derivation_version == ctx.accounts.demo_config.derivation_version,
+            LendingError::DerivationVersionMismatch
+        );
+        let ltv_factor = ctx.accounts.demo_config.ltv_factor;
+        require!(ltv_factor != [0u8; 32], LendingError::RiskFactorNotSet);
+        let (scaled_collateral_handle, ltv_handle) =
+            request_ltv_comparison(&host_cpi!(ctx.accounts, caller), position, ltv_factor)?;
+
+        emit!(LtvComputed {
+            owner: position.owner,
+            collateral_handle: position.balance_handle,
+            debt_handle: position.debt_handle,
+            ltv_factor,
+            scaled_collateral_handle,
+            ltv_handle,
+        });
+
+        Ok(ltv_handle)
+    }
+
+    /// keeper 용 청산 신호: compute_ltv 와 같은 GE 비교를 admin 이 설정한
+    /// liquidation_factor 로 health handle 로 내보낸다. 값은 암호문이라 keeper
+    /// 가 복호화해야 하지만, event 자체가 검사가 이루어졌다는 신호이므로
+    /// liquidation_keeper 만 호출 가능
+    pub fn check_liquidatable(ctx: Context<CheckLiquidatable>) -> Result<[u8; 32]> {
+        ctx.accounts.demo_config.check_not_paused(PAUSE_LIQUIDATE)?;
+        let position = &ctx.accounts.position;
+        require!(
+            position.derivation_version == ctx.accounts.demo_config.derivation_version,
+            LendingError::DerivationVersionMismatch
+        );
+        let liquidation_factor = ctx.accounts.demo_config.liquidation_factor;
+        require!(liquidation_factor != [0u8; 32], LendingError::RiskFactorNotSet);
+        let (_, health_handle) = request_ltv_comparison(
+            &host_cpi!(ctx.accounts, liquidation_keeper),
+            position,
+            liquidation_factor,
+        )?;
+
+        emit!(LiquidationSignal {
+            owner: position.owner,
+            health_handle,
+        });
+
+        Ok(health_handle)
+    }
+
+    /// derivation version 업그레이드 후 position handle 을 새 scheme 으로 교체
+    pub fn migrate_position(
+        ctx: Context<MigratePosition>,
+        new_balance_handle: [u8; 32],
+        new_debt_handle: [u8; 32],
+        migration_proof: [u8; 32],
+    ) -> Result<()> {
+        let derivation_version = ctx.accounts.demo_config.derivation_version;
+        let position = &mut ctx.accounts.position;
+        require!(
+            position.derivation_version < derivation_version,
+            LendingError::AlreadyMigrated
+        );
+
+        let old_balance_handle = position.balance_handle;
+        let old_debt_handle = position.debt_handle;
+        position.balance_handle = new_balance_handle;
+        position.debt_handle = new_debt_handle;
+        position.derivation_version = derivation_version;
+
+        emit!(PositionMigrated {
+            owner: position.owner,
+            old_balance_handle,
+            old_debt_handle,
+            new_balance_handle,
+            new_debt_handle,
+            migration_proof,
+            derivation_version,
+        });
+
+        Ok(())
+    }
+}
+
+/// Host accounts every request CPI forwards, borrowed out of the
+/// instruction's accounts by `host_cpi!`.
+pub struct HostCpi<'a, 'info> {
+    pub host_programs: &'a AccountInfo<'info>,
+    pub caller: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+    pub work_cursor: &'a AccountInfo<'info>,
+    pub activity: Option<&'a AccountInfo<'info>>,
+    pub instructions: Option<&'a AccountInfo<'info>>,
+    /// Pipeline the requests belong to; when set, each request is followed
+    /// by a `PipelineOp` carrying it.
+    pub trace_id: Option<[u8; 16]>,
+}
+
+impl HostCpi<'_, '_> {
+    fn traced(&self, result_handle: [u8; 32]) -> Result<[u8; 32]> {
+        if let Some(trace_id) = self.trace_id {
+            emit!(PipelineOp {
+                trace_id,
+                result_handle,
+            });
+        }
+        Ok(result_handle)
+    }
 }
 
-pub fn trigger_binary_cpi<'info>(
-    _host_program: &AccountInfo<'info>,
-    _caller: &AccountInfo<'info>,
+/// `request_binary_op` CPI; returns the result handle the host derived.
+pub fn trigger_binary_cpi(
+    host: &HostCpi,
     op: Fhe16BinaryOp,
     lhs: [u8; 32],
     rhs: [u8; 32],
-) -> Result<()> {
-    msg!("CPI BinaryOp: {:?} LHS:{:?} RHS:{:?}", op, lhs, rhs);
-    Ok(())
+) -> Result<[u8; 32]> {
+    let result_handle = BinaryCpi::new(op).lhs(lhs).rhs(rhs).invoke(CpiContext::new(
+        host.host_programs.clone(),
+        RequestBinaryOp {
+            caller: host.caller.clone(),
+            config: host.config.clone(),
+            work_cursor: host.work_cursor.clone(),
+            nonce_account: None,
+            dedup_cache: None,
+            activity: host.activity.cloned(),
+            instructions: host.instructions.cloned(),
+            result_record: None,
+        },
+    ))?;
+    host.traced(result_handle)
 }
 
-pub fn trigger_ternary_cpi<'info>(
-    _host_program: &AccountInfo<'info>,
-    _caller: &AccountInfo<'info>,
+/// `request_ternary_op` CPI; returns the result handle the host derived.
+pub fn trigger_ternary_cpi(
+    host: &HostCpi,
     op: Fhe16TernaryOp,
     a: [u8; 32],
     b: [u8; 32],
     c: [u8; 32],
-) -> Result<()> {
-    msg!("CPI TernaryOp: {:?} A:{:?} B:{:?} C:{:?}", op, a, b, c);
-    Ok(())
+) -> Result<[u8; 32]> {
+    let result_handle = TernaryCpi::new(op).a(a).b(b).c(c).invoke(CpiContext::new(
+        host.host_programs.clone(),
+        RequestTernaryOp {
+            caller: host.caller.clone(),
+            config: host.config.clone(),
+            work_cursor: host.work_cursor.clone(),
+            nonce_account: None,
+            dedup_cache: None,
+            activity: host.activity.cloned(),
+            instructions: host.instructions.cloned(),
+            result_record: None,
+        },
+    ))?;
+    host.traced(result_handle)
 }
 
+/// SMulL(collateral, factor) then GE(debt, scaled): true once the debt
+/// reaches the factor's share of the collateral. Returns both handles.
+fn request_ltv_comparison(
+    host: &HostCpi,
+    position: &UserPosition,
+    factor: [u8; 32],
+) -> Result<([u8; 32], [u8; 32])> {
+    // SMulL(collateral, factor): 담보 중 대출 가능한 만큼
+    let scaled_collateral_handle =
+        trigger_binary_cpi(host, Fhe16BinaryOp::SMulL, position.balance_handle, factor)?;
+
+    // GE(debt, scaled_collateral): true 이면 한도 도달
+    let comparison_handle = trigger_binary_cpi(
+        host,
+        Fhe16BinaryOp::Ge,
+        position.debt_handle,
+        scaled_collateral_handle,
+    )?;
+
+    Ok((scaled_collateral_handle, comparison_handle))
+}
 
 // -----------------------------------------------------------------------
 // Accounts Definitions
@@ -218,22 +680,476 @@ pub struct LendingDemo<'info> {
     /// CHECK: user wallet, dapp program, PDA 등 모두 가능
     pub caller: UncheckedAccount<'info>,
     /// CHECK
-    #[account(address = HOST_PROGRAM_ID)]
+    #[account(address = HOST_PROGRAM_ID @ LendingError::WrongHostProgram)]
+    pub host_programs: UncheckedAccount<'info>,
+    #[account(
+        seeds = [HOST_CONFIG_SEED],
+        bump = host_config.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_config: Account<'info, HostConfig>,
+    #[account(
+        mut,
+        seeds = [HOST_WORK_CURSOR_SEED],
+        bump = host_work_cursor.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_work_cursor: Account<'info, HostWorkCursor>,
+    /// CHECK: 요청 주체의 host CallerActivity PDA (throttling 이 켜져 있으면 필수),
+    /// host-programs 가 검증
+    #[account(mut)]
+    pub host_activity: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar (주소로 검증), host 의 origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump)]
+    pub demo_config: Account<'info, DemoConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDemoConfig<'info> {
+    #[account(
+        seeds = [HOST_CONFIG_SEED],
+        bump = host_config.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_config: Account<'info, HostConfig>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + DemoConfig::INIT_SPACE,
+        seeds = [DEMO_CONFIG_SEED],
+        bump,
+    )]
+    pub demo_config: Account<'info, DemoConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDemoConfig<'info> {
+    #[account(mut, seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump, has_one = admin)]
+    pub demo_config: Account<'info, DemoConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDerivationVersion<'info> {
+    #[account(mut, seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump, has_one = admin)]
+    pub demo_config: Account<'info, DemoConfig>,
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [HOST_CONFIG_SEED],
+        bump = host_config.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_config: Account<'info, HostConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct AttestUser<'info> {
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump, has_one = attestor)]
+    pub demo_config: Account<'info, DemoConfig>,
+    #[account(
+        init,
+        payer = attestor,
+        space = 8 + Attestation::INIT_SPACE,
+        seeds = [ATTESTATION_SEED, user.as_ref()],
+        bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+    #[account(mut)]
+    pub attestor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump)]
+    pub demo_config: Account<'info, DemoConfig>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [POSITION_SEED, owner.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PositionOp<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, owner.key().as_ref()],
+        bump = position.bump,
+        has_one = owner,
+    )]
+    pub position: Account<'info, UserPosition>,
+    /// CHECK
+    #[account(address = HOST_PROGRAM_ID @ LendingError::WrongHostProgram)]
+    pub host_programs: UncheckedAccount<'info>,
+    #[account(
+        seeds = [HOST_CONFIG_SEED],
+        bump = host_config.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_config: Account<'info, HostConfig>,
+    #[account(
+        mut,
+        seeds = [HOST_WORK_CURSOR_SEED],
+        bump = host_work_cursor.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_work_cursor: Account<'info, HostWorkCursor>,
+    /// CHECK: 요청 주체의 host CallerActivity PDA (throttling 이 켜져 있으면 필수),
+    /// host-programs 가 검증
+    #[account(mut)]
+    pub host_activity: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar (주소로 검증), host 의 origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump)]
+    pub demo_config: Account<'info, DemoConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ComputeLtv<'info> {
+    /// CHECK: user wallet, dapp program, PDA 등 모두 가능
+    pub caller: UncheckedAccount<'info>,
+    #[account(seeds = [POSITION_SEED, position.owner.as_ref()], bump = position.bump)]
+    pub position: Account<'info, UserPosition>,
+    /// CHECK
+    #[account(address = HOST_PROGRAM_ID @ LendingError::WrongHostProgram)]
+    pub host_programs: UncheckedAccount<'info>,
+    #[account(
+        seeds = [HOST_CONFIG_SEED],
+        bump = host_config.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_config: Account<'info, HostConfig>,
+    #[account(
+        mut,
+        seeds = [HOST_WORK_CURSOR_SEED],
+        bump = host_work_cursor.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_work_cursor: Account<'info, HostWorkCursor>,
+    /// CHECK: 요청 주체의 host CallerActivity PDA (throttling 이 켜져 있으면 필수),
+    /// host-programs 가 검증
+    #[account(mut)]
+    pub host_activity: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar (주소로 검증), host 의 origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump)]
+    pub demo_config: Account<'info, DemoConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CheckLiquidatable<'info> {
+    pub liquidation_keeper: Signer<'info>,
+    #[account(seeds = [POSITION_SEED, position.owner.as_ref()], bump = position.bump)]
+    pub position: Account<'info, UserPosition>,
+    /// CHECK
+    #[account(address = HOST_PROGRAM_ID @ LendingError::WrongHostProgram)]
+    pub host_programs: UncheckedAccount<'info>,
+    #[account(
+        seeds = [HOST_CONFIG_SEED],
+        bump = host_config.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_config: Account<'info, HostConfig>,
+    #[account(
+        mut,
+        seeds = [HOST_WORK_CURSOR_SEED],
+        bump = host_work_cursor.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_work_cursor: Account<'info, HostWorkCursor>,
+    /// CHECK: 요청 주체의 host CallerActivity PDA (throttling 이 켜져 있으면 필수),
+    /// host-programs 가 검증
+    #[account(mut)]
+    pub host_activity: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar (주소로 검증), host 의 origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump, has_one = liquidation_keeper)]
+    pub demo_config: Account<'info, DemoConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump, has_one = migration_authority)]
+    pub demo_config: Account<'info, DemoConfig>,
+    #[account(mut, seeds = [POSITION_SEED, position.owner.as_ref()], bump = position.bump)]
+    pub position: Account<'info, UserPosition>,
+    pub migration_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: user wallet, dapp program, PDA 등 모두 가능
+    pub caller: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(address = HOST_PROGRAM_ID @ LendingError::WrongHostProgram)]
+    pub host_programs: UncheckedAccount<'info>,
+    #[account(
+        seeds = [HOST_CONFIG_SEED],
+        bump = host_config.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_config: Account<'info, HostConfig>,
+    #[account(
+        mut,
+        seeds = [HOST_WORK_CURSOR_SEED],
+        bump = host_work_cursor.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_work_cursor: Account<'info, HostWorkCursor>,
+    /// CHECK: 요청 주체의 host CallerActivity PDA (throttling 이 켜져 있으면 필수),
+    /// host-programs 가 검증
+    #[account(mut)]
+    pub host_activity: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar (주소로 검증), host 의 origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump)]
+    pub demo_config: Account<'info, DemoConfig>,
+    /// kyc_required 일 때만 필요
+    pub attestation: Option<Account<'info, Attestation>>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    /// CHECK: user wallet, dapp program, PDA 등 모두 가능
+    pub caller: UncheckedAccount<'info>,
+    /// CHECK
+    #[account(address = HOST_PROGRAM_ID @ LendingError::WrongHostProgram)]
     pub host_programs: UncheckedAccount<'info>,
+    #[account(
+        seeds = [HOST_CONFIG_SEED],
+        bump = host_config.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_config: Account<'info, HostConfig>,
+    #[account(
+        mut,
+        seeds = [HOST_WORK_CURSOR_SEED],
+        bump = host_work_cursor.bump,
+        seeds::program = HOST_PROGRAM_ID,
+    )]
+    pub host_work_cursor: Account<'info, HostWorkCursor>,
+    /// CHECK: 요청 주체의 host CallerActivity PDA (throttling 이 켜져 있으면 필수),
+    /// host-programs 가 검증
+    #[account(mut)]
+    pub host_activity: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar (주소로 검증), host 의 origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [DEMO_CONFIG_SEED], bump = demo_config.bump)]
+    pub demo_config: Account<'info, DemoConfig>,
+    /// referrer 가 있을 때만 필요
+    #[account(mut)]
+    pub referral_stats: Option<Account<'info, ReferralStats>>,
+    /// referrer 가 있을 때만 필요: caller 의 join_referral 기록
+    #[account(
+        seeds = [REFERRAL_SEED, referee.referrer.as_ref(), caller.key().as_ref()],
+        bump = referee.bump,
+    )]
+    pub referee: Option<Account<'info, Referee>>,
+}
+
+#[derive(Accounts)]
+pub struct InitReferralStats<'info> {
+    /// CHECK: 추천인 주소만 사용
+    pub referrer: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ReferralStats::INIT_SPACE,
+        seeds = [REFERRAL_SEED, referrer.key().as_ref()],
+        bump,
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct JoinReferral<'info> {
+    #[account(
+        seeds = [REFERRAL_SEED, referral_stats.referrer.as_ref()],
+        bump = referral_stats.bump,
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+    /// (referrer, depositor) 당 하나 → 존재 자체가 중복 join 방지
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + Referee::INIT_SPACE,
+        seeds = [REFERRAL_SEED, referral_stats.referrer.as_ref(), depositor.key().as_ref()],
+        bump,
+    )]
+    pub referee: Account<'info, Referee>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// -----------------------------------------------------------------------
+// State
+// -----------------------------------------------------------------------
+
+#[account]
+#[derive(InitSpace)]
+pub struct DemoConfig {
+    pub admin: Pubkey,
+    /// attest_user 를 호출할 수 있는 유일한 주소
+    pub attestor: Pubkey,
+    pub expected_attestation_handle: [u8; 32],
+    /// true 이면 withdraw 가 attestation 일치 여부를 SELECT 조건에 포함
+    pub kyc_required: bool,
+    /// Circuit breaker bitmask (PAUSE_*)
+    pub paused_instructions: u16,
+    /// migrate_position 을 호출할 수 있는 주소 (기본값 admin)
+    pub migration_authority: Pubkey,
+    /// 현재 host-programs handle derivation scheme version
+    pub derivation_version: u8,
+    /// derivation_version 의 handle 을 derive 하는 host Config.domain_salt
+    /// (initialize / set_derivation_version 시점에 복사)
+    pub domain_salt: [u8; DOMAIN_SALT_LEN],
+    /// compute_ltv 의 암호화된 LTV 비율 (set_ltv_factor 전에는 0 → 거부)
+    pub ltv_factor: [u8; 32],
+    /// check_liquidatable 의 암호화된 청산 기준 비율 (설정 전에는 0 → 거부)
+    pub liquidation_factor: [u8; 32],
+    /// check_liquidatable 을 호출할 수 있는 주소 (기본값 admin)
+    pub liquidation_keeper: Pubkey,
+    pub bump: u8,
+}
+
+impl DemoConfig {
+    pub fn check_not_paused(&self, bit: u16) -> Result<()> {
+        require!(
+            self.paused_instructions & bit == 0,
+            LendingError::InstructionPaused
+        );
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserPosition {
+    pub owner: Pubkey,
+    pub balance_handle: [u8; 32],
+    pub debt_handle: [u8; 32],
+    /// handle 들이 derive 된 scheme version; config 와 다르면 연산 거부
+    pub derivation_version: u8,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub user: Pubkey,
+    /// 암호화된 KYC attestation
+    pub handle: [u8; 32],
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ReferralStats {
+    pub referrer: Pubkey,
+    /// 추천된 deposit 횟수 (plaintext)
+    pub count: u64,
+    /// 추천된 deposit 금액의 암호화된 합계
+    pub total_handle: [u8; 32],
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Referee {
+    pub referrer: Pubkey,
+    pub depositor: Pubkey,
+    pub bump: u8,
+}
+
+// -----------------------------------------------------------------------
+// Errors
+// -----------------------------------------------------------------------
+
+#[error_code]
+pub enum LendingError {
+    #[msg("Depositor cannot refer themselves")]
+    SelfReferral,
+    #[msg("Referral stats account is required when a referrer is given")]
+    MissingReferralStats,
+    #[msg("Referral stats account belongs to a different referrer")]
+    ReferralMismatch,
+    #[msg("KYC is required but no attestation for the caller was provided")]
+    MissingAttestation,
+    #[msg("Instruction is paused by the demo admin")]
+    InstructionPaused,
+    #[msg("Position handles were derived under a different derivation version")]
+    DerivationVersionMismatch,
+    #[msg("Position is already at the current derivation version")]
+    AlreadyMigrated,
+    #[msg("host_programs is not the host program this demo was built against")]
+    WrongHostProgram,
+    #[msg("A referred deposit must be signed by the depositor")]
+    ReferralRequiresSigner,
+    #[msg("Depositor has not joined the referrer with join_referral")]
+    NotReferred,
+    #[msg("Risk factor has not been set by the demo admin")]
+    RiskFactorNotSet,
 }
 
 // -----------------------------------------------------------------------
 // Events
 // -----------------------------------------------------------------------
 
+/// Emitted before the first op request of a composed instruction. Each op
+/// request is followed by a `PipelineOp` and the instruction's own
+/// completion event repeats `trace_id`.
+#[event]
+pub struct PipelineStarted {
+    pub trace_id: [u8; 16],
+    /// Op requests (`trigger_binary_cpi` / `trigger_ternary_cpi` calls) the
+    /// instruction is about to make
+    pub op_count: u8,
+}
+
+/// One op request of a traced pipeline, emitted right after the host CPI.
+#[event]
+pub struct PipelineOp {
+    /// Same id as the instruction's `PipelineStarted`
+    pub trace_id: [u8; 16],
+    /// `result_handle` of the host's request event for this op
+    pub result_handle: [u8; 32],
+}
+
 #[event]
 pub struct WithdrawCompleted {
+    /// Same id as the instruction's `PipelineStarted`
+    pub trace_id: [u8; 16],
     pub caller: Pubkey,
     pub usdc_balance: [u8; 32],
     pub withdraw_amount: [u8; 32],
     pub ge_result_handle: [u8; 32],
     pub sub_result_handle: [u8; 32],
     pub final_handle: [u8; 32],
+    /// EQ(attestation, expected) when KYC is required
+    pub kyc_result_handle: Option<[u8; 32]>,
 }
 
 #[event]
@@ -242,4 +1158,64 @@ pub struct DepositCompleted {
     pub sol_balance: [u8; 32],
     pub deposit_amount: [u8; 32],
     pub final_handle: [u8; 32],
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct RepayCompleted {
+    pub caller: Pubkey,
+    pub debt: [u8; 32],
+    pub repay_amount: [u8; 32],
+    pub ge_result_handle: [u8; 32],
+    pub sub_result_handle: [u8; 32],
+    pub final_handle: [u8; 32],
+}
+
+#[event]
+pub struct ReferralCredited {
+    pub referrer: Pubkey,
+    pub count: u64,
+    pub total_handle: [u8; 32],
+}
+
+#[event]
+pub struct UserAttested {
+    pub user: Pubkey,
+    pub attestor: Pubkey,
+    pub attestation_handle: [u8; 32],
+}
+
+#[event]
+pub struct PauseMaskChanged {
+    pub old: u16,
+    pub new: u16,
+}
+
+#[event]
+pub struct PositionMigrated {
+    pub owner: Pubkey,
+    pub old_balance_handle: [u8; 32],
+    pub old_debt_handle: [u8; 32],
+    pub new_balance_handle: [u8; 32],
+    pub new_debt_handle: [u8; 32],
+    pub migration_proof: [u8; 32],
+    pub derivation_version: u8,
+}
+
+/// 청산 가능 여부 신호 (keeper 가 health_handle 을 복호화해 판단)
+#[event]
+pub struct LiquidationSignal {
+    pub owner: Pubkey,
+    /// GE(debt, SMulL(collateral, liquidation_factor))
+    pub health_handle: [u8; 32],
+}
+
+#[event]
+pub struct LtvComputed {
+    pub owner: Pubkey,
+    pub collateral_handle: [u8; 32],
+    pub debt_handle: [u8; 32],
+    pub ltv_factor: [u8; 32],
+    pub scaled_collateral_handle: [u8; 32],
+    /// GE(debt, scaled_collateral)
+    pub ltv_handle: [u8; 32],
+}
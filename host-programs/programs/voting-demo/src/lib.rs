@@ -0,0 +1,358 @@
+// ⚠️ WARNING: Reference implementation for boolean-aggregation dapps.
+// This is NOT production code. Current design:
+// - Encrypted ballots are registered via register_input_handle and range
+//   checked against 0 / 1 under encryption; a ballot outside that range is
+//   counted as 0 (SELECT(in_range, ballot, 0))
+// - The tally is a running ADD over the counted ballots, requested through
+//   real CPIs; the demo stores the handles the host returns, so it carries
+//   no derivation code of its own
+// - One Ballot PDA per (proposal, voter) prevents double votes
+// - Once the Clock passes the deadline, finalize_proposal freezes the tally
+//   and emits TallyFinalized for the decryptor to pick up
+//
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use host_programs::cpi_builder::{BinaryCpi, TernaryCpi};
+use host_programs::handle::constant_handle;
+use host_programs::types::{
+    Fhe16BinaryOp, Fhe16TernaryOp, Handle, FHE16_CONST_ONE, FHE16_CONST_ZERO,
+};
+
+declare_id!("GLPvMWVbohVSq9Xn4hZCvdivRHtE1U1JvygdhZis3fqR");
+
+const HOST_PROGRAM_ID: Pubkey = host_programs::ID;
+
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const BALLOT_SEED: &[u8] = b"ballot";
+
+pub const PROPOSAL_TAG_LEN: usize = 16;
+
+/// client_tag ballots are registered under.
+pub const BALLOT_CLIENT_TAG: [u8; 32] = [0; 32];
+
+#[program]
+pub mod voting_demo {
+    use super::*;
+
+    // -------------------------------------------------------------------
+    // 1) Proposal: tally starts at the shared encrypted zero
+    // -------------------------------------------------------------------
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        tag: [u8; PROPOSAL_TAG_LEN],
+        deadline: i64,
+    ) -> Result<()> {
+        require!(
+            deadline > Clock::get()?.unix_timestamp,
+            VotingError::DeadlineInPast
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.creator = ctx.accounts.creator.key();
+        proposal.tag = tag;
+        proposal.deadline = deadline;
+        proposal.tally_handle = constant_handle(FHE16_CONST_ZERO, &HOST_PROGRAM_ID);
+        proposal.vote_count = 0;
+        proposal.finalized = false;
+        proposal.bump = ctx.bumps.proposal;
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // 2) Vote: register the ballot, tally = ADD(tally, ballot)
+    // -------------------------------------------------------------------
+    /// ballot 은 이미 init 된 Ballot PDA 로 한 번만 가능 (두 번째는 init 실패).
+    /// `caller_nonce` 는 voter 의 host nonce 로 첫 요청 (range check) 에 쓰인다
+    pub fn cast_vote(
+        ctx: Context<CastVote>,
+        ballot_handle: [u8; 32],
+        caller_nonce: Option<u64>,
+    ) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            Clock::get()?.unix_timestamp < proposal.deadline,
+            VotingError::VotingClosed
+        );
+
+        // forwarded so host events name this program as the request origin
+        let instructions = ctx
+            .accounts
+            .instructions
+            .as_ref()
+            .map(|ix| ix.to_account_info());
+        let activity = ctx
+            .accounts
+            .host_activity
+            .as_ref()
+            .map(|activity| activity.to_account_info());
+
+        host_programs::cpi::register_input_handle(
+            CpiContext::new(
+                ctx.accounts.host_programs.to_account_info(),
+                host_programs::cpi::accounts::RegisterInputHandle {
+                    caller: ctx.accounts.voter.to_account_info(),
+                    config: ctx.accounts.host_config.to_account_info(),
+                    work_cursor: ctx.accounts.host_work_cursor.to_account_info(),
+                    stats: None,
+                    activity: activity.clone(),
+                    instructions: instructions.clone(),
+                    record: ctx
+                        .accounts
+                        .host_ballot_record
+                        .as_ref()
+                        .map(|record| record.to_account_info()),
+                    payer: Some(ctx.accounts.voter.to_account_info()),
+                    system_program: Some(ctx.accounts.system_program.to_account_info()),
+                },
+            ),
+            ballot_handle,
+            BALLOT_CLIENT_TAG,
+        )?;
+
+        // in_range = AND(GE(ballot, 0), LE(ballot, 1))
+        let zero: Handle = constant_handle(FHE16_CONST_ZERO, &HOST_PROGRAM_ID);
+        let one: Handle = constant_handle(FHE16_CONST_ONE, &HOST_PROGRAM_ID);
+        let in_range = host_programs::cpi::request_range_check(
+            CpiContext::new(
+                ctx.accounts.host_programs.to_account_info(),
+                host_programs::cpi::accounts::RequestRangeCheck {
+                    caller: ctx.accounts.voter.to_account_info(),
+                    config: ctx.accounts.host_config.to_account_info(),
+                    work_cursor: ctx.accounts.host_work_cursor.to_account_info(),
+                    nonce_account: ctx
+                        .accounts
+                        .host_nonce_account
+                        .as_ref()
+                        .map(|nonce| nonce.to_account_info()),
+                    activity: activity.clone(),
+                    instructions: instructions.clone(),
+                },
+            ),
+            ballot_handle,
+            zero,
+            one,
+            caller_nonce,
+            false,
+        )?
+        .get();
+
+        let counted = TernaryCpi::new(Fhe16TernaryOp::Select)
+            .a(in_range)
+            .b(ballot_handle)
+            .c(zero)
+            .invoke(CpiContext::new(
+                ctx.accounts.host_programs.to_account_info(),
+                host_programs::cpi::accounts::RequestTernaryOp {
+                    caller: ctx.accounts.voter.to_account_info(),
+                    config: ctx.accounts.host_config.to_account_info(),
+                    work_cursor: ctx.accounts.host_work_cursor.to_account_info(),
+                    nonce_account: None,
+                    dedup_cache: None,
+                    activity: activity.clone(),
+                    instructions: instructions.clone(),
+                    result_record: None,
+                },
+            ))?;
+
+        let tally_handle = BinaryCpi::new(Fhe16BinaryOp::Add)
+            .lhs(proposal.tally_handle)
+            .rhs(counted)
+            .invoke(CpiContext::new(
+                ctx.accounts.host_programs.to_account_info(),
+                host_programs::cpi::accounts::RequestBinaryOp {
+                    caller: ctx.accounts.voter.to_account_info(),
+                    config: ctx.accounts.host_config.to_account_info(),
+                    work_cursor: ctx.accounts.host_work_cursor.to_account_info(),
+                    nonce_account: None,
+                    dedup_cache: None,
+                    activity,
+                    instructions,
+                    result_record: ctx
+                        .accounts
+                        .host_result_record
+                        .as_ref()
+                        .map(|record| record.to_account_info()),
+                },
+            ))?;
+
+        let ballot = &mut ctx.accounts.ballot;
+        ballot.proposal = ctx.accounts.proposal.key();
+        ballot.voter = ctx.accounts.voter.key();
+        ballot.ballot_handle = ballot_handle;
+        ballot.bump = ctx.bumps.ballot;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.tally_handle = tally_handle;
+        proposal.vote_count = proposal.vote_count.saturating_add(1);
+
+        emit!(VoteCast {
+            proposal: proposal.key(),
+            voter: ballot.voter,
+            ballot_handle,
+            tally_handle,
+        });
+
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------
+    // 3) Finalize: anyone, once the deadline has passed
+    // -------------------------------------------------------------------
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.finalized, VotingError::AlreadyFinalized);
+        require!(
+            Clock::get()?.unix_timestamp >= proposal.deadline,
+            VotingError::VotingStillOpen
+        );
+        proposal.finalized = true;
+
+        emit!(TallyFinalized {
+            proposal: proposal.key(),
+            tally_handle: proposal.tally_handle,
+            vote_count: proposal.vote_count,
+        });
+
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------
+// Accounts Definitions
+// -----------------------------------------------------------------------
+
+#[derive(Accounts)]
+#[instruction(tag: [u8; PROPOSAL_TAG_LEN])]
+pub struct CreateProposal<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [PROPOSAL_SEED, creator.key().as_ref(), tag.as_ref()],
+        bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.creator.as_ref(), proposal.tag.as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + Ballot::INIT_SPACE,
+        seeds = [BALLOT_SEED, proposal.key().as_ref(), voter.key().as_ref()],
+        bump,
+    )]
+    pub ballot: Account<'info, Ballot>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    /// CHECK
+    #[account(address = HOST_PROGRAM_ID)]
+    pub host_programs: UncheckedAccount<'info>,
+    /// CHECK: host Config PDA, host-programs 가 검증
+    pub host_config: UncheckedAccount<'info>,
+    /// CHECK: host WorkCursor PDA, host-programs 가 검증
+    #[account(mut)]
+    pub host_work_cursor: UncheckedAccount<'info>,
+    /// CHECK: voter 의 host CallerNonce PDA (`caller_nonce` 를 쓸 때), host-programs 가 검증
+    #[account(mut)]
+    pub host_nonce_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: voter 의 host CallerActivity PDA (throttling 이 켜져 있으면 필수,
+    /// 모든 host 요청에 쓰인다)
+    #[account(mut)]
+    pub host_activity: Option<UncheckedAccount<'info>>,
+    /// CHECK: 새 tally handle 의 host HandleRecord (있으면), host-programs 가 검증
+    pub host_result_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: ballot handle 의 host HandleRecord PDA (registration bond 가 켜져 있으면 필수),
+    /// voter 가 bond 를 예치한다. host-programs 가 검증
+    #[account(mut)]
+    pub host_ballot_record: Option<UncheckedAccount<'info>>,
+    /// CHECK: instructions sysvar (주소로 검증), host 의 origin_program 계산용
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, proposal.creator.as_ref(), proposal.tag.as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+// -----------------------------------------------------------------------
+// State
+// -----------------------------------------------------------------------
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub creator: Pubkey,
+    pub tag: [u8; 16],
+    /// Unix timestamp; votes are accepted strictly before it
+    pub deadline: i64,
+    /// 암호화된 찬성표 합계 (ballot 들의 ADD 체인)
+    pub tally_handle: [u8; 32],
+    /// 투표 수 (plaintext)
+    pub vote_count: u64,
+    pub finalized: bool,
+    pub bump: u8,
+}
+
+/// (proposal, voter) 당 하나 → 존재 자체가 이중 투표 방지
+#[account]
+#[derive(InitSpace)]
+pub struct Ballot {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub ballot_handle: [u8; 32],
+    pub bump: u8,
+}
+
+// -----------------------------------------------------------------------
+// Errors
+// -----------------------------------------------------------------------
+
+#[error_code]
+pub enum VotingError {
+    #[msg("Proposal deadline must be in the future")]
+    DeadlineInPast,
+    #[msg("Voting on this proposal has closed")]
+    VotingClosed,
+    #[msg("Proposal cannot be finalized before its deadline")]
+    VotingStillOpen,
+    #[msg("Proposal is already finalized")]
+    AlreadyFinalized,
+}
+
+// -----------------------------------------------------------------------
+// Events
+// -----------------------------------------------------------------------
+
+#[event]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub ballot_handle: [u8; 32],
+    pub tally_handle: [u8; 32],
+}
+
+/// 최종 tally handle; decryptor 는 이 event 를 보고 공개 복호화를 수행
+#[event]
+pub struct TallyFinalized {
+    pub proposal: Pubkey,
+    pub tally_handle: [u8; 32],
+    pub vote_count: u64,
+}
@@ -2,9 +2,10 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, EventParser } from "@coral-xyz/anchor";
 import { HostPrograms } from "../target/types/host_programs";
 import { LendingDemo } from "../target/types/lending_demo";
+import { VotingDemo } from "../target/types/voting_demo";
 import idl from "../target/idl/host_programs.json";
 import { sha256 } from "@noble/hashes/sha256";
-import { PublicKey } from "@solana/web3.js";
+import { ComputeBudgetProgram, Keypair, PublicKey, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
 import { expect } from "chai";
 
 /**
@@ -43,17 +44,20 @@ function deriveUnaryHandle(
 
 /**
  * Rust의 derive_binary_handle과 동일한 로직으로 handle을 계산합니다.
+ * salt 가 all-zero (기본값) 이면 salt 바이트는 hash 에 들어가지 않습니다.
  */
 function deriveBinaryHandle(
   op: number,
   lhs: Uint8Array,
   rhs: Uint8Array,
-  programId: PublicKey
+  programId: PublicKey,
+  salt: Uint8Array = new Uint8Array(16)
 ): Uint8Array {
   const HANDLE_DOMAIN_BINARY = new TextEncoder().encode("FHE16_BINARY_V1");
   const opByte = new Uint8Array([op]);
   const hash = hashv([
     HANDLE_DOMAIN_BINARY,
+    salt.every((b) => b === 0) ? new Uint8Array(0) : salt,
     programId.toBuffer(),
     opByte,
     lhs,
@@ -85,6 +89,136 @@ function deriveTernaryHandle(
   return hash;
 }
 
+/**
+ * Rust의 derive_pad_handle과 동일한 로직으로 handle을 계산합니다.
+ */
+function derivePadHandle(
+  input: Uint8Array,
+  targetWidth: number,
+  programId: PublicKey
+): Uint8Array {
+  const HANDLE_DOMAIN_PAD = new TextEncoder().encode("FHE16_PAD_V1");
+  const widthByte = new Uint8Array([targetWidth]);
+  const hash = hashv([
+    HANDLE_DOMAIN_PAD,
+    programId.toBuffer(),
+    widthByte,
+    input,
+  ]);
+  return hash;
+}
+
+/**
+ * Rust의 derive_truncate_handle과 동일한 로직으로 handle을 계산합니다.
+ */
+function deriveTruncateHandle(
+  input: Uint8Array,
+  targetWidth: number,
+  programId: PublicKey
+): Uint8Array {
+  const HANDLE_DOMAIN_TRUNCATE = new TextEncoder().encode("FHE16_TRUNCATE_V1");
+  const widthByte = new Uint8Array([targetWidth]);
+  const hash = hashv([
+    HANDLE_DOMAIN_TRUNCATE,
+    programId.toBuffer(),
+    widthByte,
+    input,
+  ]);
+  return hash;
+}
+
+/**
+ * Rust의 derive_concat_handle과 동일한 로직으로 handle을 계산합니다.
+ * lo 가 hi 보다 먼저 해시되므로 순서를 바꾸면 다른 handle 이 나옵니다.
+ */
+function deriveConcatHandle(
+  lo: Uint8Array,
+  hi: Uint8Array,
+  halfWidth: number,
+  programId: PublicKey
+): Uint8Array {
+  const HANDLE_DOMAIN_CONCAT = new TextEncoder().encode("FHE16_CONCAT_V1");
+  const widthByte = new Uint8Array([halfWidth]);
+  return hashv([HANDLE_DOMAIN_CONCAT, programId.toBuffer(), widthByte, lo, hi]);
+}
+
+/**
+ * Rust의 derive_vector_handles와 동일한 로직으로 원소별 handle 을 계산합니다.
+ * op byte, 원소 개수, 원소 index 가 함께 해시됩니다.
+ */
+function deriveVectorHandles(
+  op: number,
+  lhs: Uint8Array[],
+  rhs: Uint8Array[],
+  programId: PublicKey
+): Uint8Array[] {
+  const HANDLE_DOMAIN_VECTOR = new TextEncoder().encode("FHE16_VECTOR_V1");
+  return lhs.map((l, i) => {
+    const params = new Uint8Array([op, lhs.length, i]);
+    return hashv([HANDLE_DOMAIN_VECTOR, programId.toBuffer(), params, l, rhs[i]]);
+  });
+}
+
+/**
+ * Rust의 derive_dot_product_handle과 동일한 로직으로 handle을 계산합니다.
+ * weights 가 number[] 이면 평문 u16 weight (kind 1), 아니면 handle (kind 0).
+ */
+function deriveDotProductHandle(
+  weights: Uint8Array[] | number[],
+  values: Uint8Array[],
+  programId: PublicKey
+): Uint8Array {
+  const HANDLE_DOMAIN_DOT = new TextEncoder().encode("FHE16_DOT_V1");
+  const scalar = typeof weights[0] === "number";
+  const weightBytes = scalar
+    ? (weights as number[]).map((w) => new Uint8Array([w & 0xff, w >> 8]))
+    : (weights as Uint8Array[]);
+  const params = new Uint8Array([scalar ? 1 : 0, values.length]);
+  return hashv([HANDLE_DOMAIN_DOT, programId.toBuffer(), params, ...weightBytes, ...values]);
+}
+
+/**
+ * Rust의 derive_input_handle과 동일한 로직으로 입력 handle을 계산합니다.
+ * scheme version 1 은 기존 sha256(ciphertext) 와 동일합니다.
+ */
+function deriveInputHandle(ciphertext: Uint8Array, schemeVersion = 1): Uint8Array {
+  if (schemeVersion === 1) {
+    return sha256(ciphertext);
+  }
+  const HANDLE_DOMAIN_INPUT = new TextEncoder().encode("FHE16_INPUT");
+  return hashv([HANDLE_DOMAIN_INPUT, new Uint8Array([schemeVersion]), ciphertext]);
+}
+
+/**
+ * Rust의 constant_handle과 동일한 로직으로 공용 상수 handle을 계산합니다.
+ */
+function constantHandle(value: number, programId: PublicKey): Uint8Array {
+  const HANDLE_DOMAIN_TRIVIAL = new TextEncoder().encode("FHE16_TRIVIAL_V1");
+  const valueBytes = new Uint8Array([value & 0xff, (value >> 8) & 0xff]);
+  return hashv([HANDLE_DOMAIN_TRIVIAL, programId.toBuffer(), valueBytes]);
+}
+
+/**
+ * Rust의 boolean_constant_handle과 동일한 로직으로 1-bit 상수 handle을 계산합니다.
+ */
+function booleanConstantHandle(value: boolean, programId: PublicKey): Uint8Array {
+  const HANDLE_DOMAIN_TRIVIAL = new TextEncoder().encode("FHE16_TRIVIAL_V1");
+  const valueBytes = new Uint8Array([value ? 1 : 0, 0]);
+  return hashv([HANDLE_DOMAIN_TRIVIAL, programId.toBuffer(), valueBytes, new Uint8Array([1])]);
+}
+
+/**
+ * Rust의 derive_export_proof와 동일한 로직으로 export proof를 계산합니다.
+ */
+function deriveExportProof(
+  handle: Uint8Array,
+  owner: PublicKey,
+  sourceProgram: PublicKey
+): Uint8Array {
+  const EXPORT_DOMAIN = new TextEncoder().encode("FHE16_EXPORT_V1");
+  return hashv([EXPORT_DOMAIN, sourceProgram.toBuffer(), owner.toBuffer(), handle]);
+}
+
 /**
  * IDL JSON에서 enum variant 순서를 찾아서 index를 계산합니다.
  * Borsh enum discriminant는 variant index이므로, IDL의 variants 배열에서 순서를 찾으면 됩니다.
@@ -216,6 +350,99 @@ async function getEvent(
   throw new Error(`Event ${eventName} not found in transaction ${txSig}`);
 }
 
+/**
+ * Rust envelope.rs 의 v1 인코딩과 동일하게 요청들을 직렬화합니다.
+ */
+type EnvelopeRequest =
+  | { kind: "register"; handle: Uint8Array; clientTag: Uint8Array }
+  | { kind: "unary"; op: number; input: Uint8Array }
+  | { kind: "binary"; op: number; lhs: Uint8Array; rhs: Uint8Array }
+  | { kind: "ternary"; op: number; a: Uint8Array; b: Uint8Array; c: Uint8Array }
+  | { kind: "pad"; targetWidth: number; input: Uint8Array };
+
+function encodeEnvelope(requests: EnvelopeRequest[]): Buffer {
+  const parts: Uint8Array[] = [new Uint8Array([1, requests.length])];
+  for (const r of requests) {
+    switch (r.kind) {
+      case "register":
+        parts.push(new Uint8Array([0]), r.handle, r.clientTag);
+        break;
+      case "unary":
+        parts.push(new Uint8Array([1, r.op]), r.input);
+        break;
+      case "binary":
+        parts.push(new Uint8Array([2, r.op]), r.lhs, r.rhs);
+        break;
+      case "ternary":
+        parts.push(new Uint8Array([3, r.op]), r.a, r.b, r.c);
+        break;
+      case "pad":
+        parts.push(new Uint8Array([4, r.targetWidth]), r.input);
+        break;
+    }
+  }
+  return Buffer.concat(parts.map((p) => Buffer.from(p)));
+}
+
+/**
+ * Rust의 ClientTag::from_str_label과 동일: 0xFE || sha256(domain, label)[..31]
+ */
+function clientTagFromLabel(label: string): Uint8Array {
+  const hash = hashv([
+    new TextEncoder().encode("FHE16_CLIENT_TAG_LABEL_V1"),
+    new TextEncoder().encode(label),
+  ]);
+  const tag = new Uint8Array(32);
+  tag[0] = 0xfe;
+  tag.set(hash.slice(0, 31), 1);
+  return tag;
+}
+
+/**
+ * Rust receipt.rs 와 동일: receipt = sha256("FHE16_RECEIPT_V2" || prev || borsh(ReceiptRequest))
+ * summary 는 ReceiptRequest 의 Borsh 인코딩 (Op 이면 0 + OpRequest 인코딩)
+ */
+function foldReceipts(summaries: Uint8Array[]): Uint8Array {
+  const RECEIPT_DOMAIN = new TextEncoder().encode("FHE16_RECEIPT_V2");
+  return summaries.reduce(
+    (receipt, summary) => hashv([RECEIPT_DOMAIN, receipt, summary]),
+    new Uint8Array(32)
+  );
+}
+
+/**
+ * accumulator PDA (seeds: ["accumulator", owner, tag])
+ */
+function accumulatorPda(owner: PublicKey, tag: Uint8Array, programId: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("accumulator"), owner.toBuffer(), Buffer.from(tag)],
+    programId
+  );
+  return pda;
+}
+
+/**
+ * upgradeable loader 의 ProgramData 계정 (seeds: [program_id])
+ */
+function programDataAddress(programId: PublicKey): PublicKey {
+  const [programData] = PublicKey.findProgramAddressSync(
+    [programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+  return programData;
+}
+
+/**
+ * handle registry record PDA (seeds: ["handle_record", handle])
+ */
+function handleRecordPda(handle: Uint8Array, programId: PublicKey): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("handle_record"), Buffer.from(handle)],
+    programId
+  );
+  return pda;
+}
+
 /**
  * 이벤트의 필드들을 한 번에 검증
  */
@@ -231,6 +458,8 @@ function assertEventFields(
     a_handle?: Uint8Array;
     b_handle?: Uint8Array;
     c_handle?: Uint8Array;
+    lo_handle?: Uint8Array;
+    hi_handle?: Uint8Array;
     result_handle?: Uint8Array;
     op?: { op: Record<string, unknown>; enumType: "Fhe16UnaryOp" | "Fhe16BinaryOp" | "Fhe16TernaryOp" };
   },
@@ -252,6 +481,8 @@ function assertEventFields(
     "a_handle",
     "b_handle",
     "c_handle",
+    "lo_handle",
+    "hi_handle",
     "result_handle",
   ] as const;
 
@@ -292,10 +523,77 @@ describe("host-programs", () => {
   const wallet = provider.wallet as anchor.Wallet;
 
   it("Initialize program", async () => {
+    const [zeroRecord, oneRecord, maxRecord] = [0, 1, 0xffff].map((v) =>
+      handleRecordPda(constantHandle(v, program.programId), program.programId)
+    );
+    const initialize = () =>
+      program.methods
+        .initialize()
+        .accounts({ program: program.programId, zeroRecord, oneRecord, maxRecord, payer: wallet.publicKey })
+        .rpc();
+
+    await initialize();
+    const zero = await program.account.handleRecord.fetch(zeroRecord);
+    expect(zero.owner.toString(), "상수 record 의 owner 가 program 이 아닙니다").to.equal(
+      program.programId.toString()
+    );
+
+    // 상수 등록은 한 번만 가능
+    let reinitialized = true;
+    try {
+      await initialize();
+    } catch (err) {
+      reinitialized = false;
+    }
+    expect(reinitialized, "initialize 가 두 번 실행되었습니다").to.be.false;
+  });
+
+  it("Constant handles are pinned for the declared program id", () => {
+    const pid = new PublicKey("FkLGYGk2bypUXgpGmcsCTmKZo6LCjHaXswbhY1LNGAKj");
+    const pinned: [number, string][] = [
+      [0, "c1024b3e53bb7e02971163a5f8c21adc4fc7700dc4b88cdd8573285162d7d8bf"],
+      [1, "6b256a4a78858cefdb7112d51d52d5d6535133fbeaffe21d57c33b8a618ff316"],
+      [0xffff, "caf1a258689368bfc64d5df3999efb9e9139012943e9965fd68c8247662e4ca6"],
+    ];
+    for (const [value, hex] of pinned) {
+      expect(Buffer.from(constantHandle(value, pid)).toString("hex"), `const(${value})`).to.equal(hex);
+    }
+  });
+
+  it("Initialize config: only the upgrade authority can claim admin", async () => {
+    const outsider = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(outsider.publicKey, 1_000_000_000);
+    await provider.connection.confirmTransaction(airdrop);
+
+    let rejected = false;
+    try {
+      await program.methods
+        .initializeConfig()
+        .accounts({
+          admin: outsider.publicKey,
+          programData: programDataAddress(program.programId),
+        })
+        .signers([outsider])
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("NotUpgradeAuthority");
+    }
+    expect(rejected, "upgrade authority 가 아닌 주소가 config 를 초기화했습니다").to.be.true;
+  });
+
+  it("Initialize config", async () => {
     await program.methods
-      .initialize()
-      .accounts({ program: program.programId })
+      .initializeConfig()
+      .accounts({ admin: wallet.publicKey, programData: programDataAddress(program.programId) })
       .rpc();
+
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    const config = await program.account.config.fetch(configPda);
+    expect(config.admin.toString()).to.equal(wallet.publicKey.toString());
+    expect(config.emitEvents).to.be.true;
   });
 
   it("Register input handle and verify event", async () => {
@@ -313,6 +611,43 @@ describe("host-programs", () => {
     assertEventFields(event, { caller: wallet.publicKey, handle, client_tag: clientTag }, wallet);
   });
 
+  it("Register input handle: labeled client_tag and reserved prefix", async () => {
+    const handle = new Uint8Array(32).fill(3);
+    const labeledTag = clientTagFromLabel("order-42");
+    // Rust doctest 와 같은 고정값: label hashing 이 바뀌면 양쪽이 함께 깨진다
+    expect(Buffer.from(labeledTag).toString("hex")).to.equal(
+      "fe86c06f40278c7a5d6d5d65a49d6ddfc061656d05dcc82242ae0a7ac5bf7cf8"
+    );
+
+    const tx = await program.methods
+      .registerInputHandle(Array.from(handle), Array.from(labeledTag))
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    const event = await getEvent(program, provider, tx, "InputHandleRegistered");
+    assertEventFields(event, { handle, client_tag: labeledTag }, wallet);
+    expect(event.data.isLabeled ?? event.data.is_labeled).to.be.true;
+
+    const reservedTag = new Uint8Array(32).fill(0xff);
+    let rejected = false;
+    try {
+      await program.methods
+        .registerInputHandle(Array.from(handle), Array.from(reservedTag))
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("ReservedClientTag");
+    }
+    expect(rejected, "예약된 client_tag prefix 가 거부되지 않았습니다").to.be.true;
+
+    // 예약 prefix 는 정확히 4 byte: 0xFF 3 개는 일반 tag
+    const almostReserved = new Uint8Array(32);
+    almostReserved.set([0xff, 0xff, 0xff], 0);
+    await program.methods
+      .registerInputHandle(Array.from(handle), Array.from(almostReserved))
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+  });
+
   it("Request unary operation with hash verification", async () => {
     const inputHandle = new Uint8Array(32);
     inputHandle.fill(10);
@@ -322,7 +657,7 @@ describe("host-programs", () => {
     const expectedResultHandle = deriveUnaryHandle(opNumericCode, inputHandle, program.programId);
 
     const tx = await program.methods
-      .requestUnaryOp(op, Array.from(inputHandle))
+      .requestUnaryOp(op, Array.from(inputHandle), null, false)
       .accounts({ caller: wallet.publicKey })
       .rpc();
 
@@ -355,7 +690,7 @@ describe("host-programs", () => {
     );
 
     const tx = await program.methods
-      .requestBinaryOp(op, Array.from(lhsHandle), Array.from(rhsHandle))
+      .requestBinaryOp(op, Array.from(lhsHandle), Array.from(rhsHandle), null, false)
       .accounts({ caller: wallet.publicKey })
       .rpc();
 
@@ -392,7 +727,7 @@ describe("host-programs", () => {
     );
 
     const tx = await program.methods
-      .requestTernaryOp(op, Array.from(aHandle), Array.from(bHandle), Array.from(cHandle))
+      .requestTernaryOp(op, Array.from(aHandle), Array.from(bHandle), Array.from(cHandle), null, false)
       .accounts({ caller: wallet.publicKey })
       .rpc();
 
@@ -433,7 +768,7 @@ describe("host-programs", () => {
     );
 
     const tx2 = await program.methods
-      .requestUnaryOp(notOp, Array.from(inputHandle))
+      .requestUnaryOp(notOp, Array.from(inputHandle), null, false)
       .accounts({ caller: wallet.publicKey })
       .rpc();
     const event2 = await getEvent(program, provider, tx2, "Fhe16UnaryOpRequested");
@@ -455,7 +790,7 @@ describe("host-programs", () => {
     );
 
     const tx3 = await program.methods
-      .requestBinaryOp(addOp, Array.from(notResultHandle), Array.from(rhsHandle))
+      .requestBinaryOp(addOp, Array.from(notResultHandle), Array.from(rhsHandle), null, false)
       .accounts({ caller: wallet.publicKey })
       .rpc();
     const event3 = await getEvent(program, provider, tx3, "Fhe16BinaryOpRequested");
@@ -480,7 +815,7 @@ describe("host-programs", () => {
     );
 
     const tx4 = await program.methods
-      .requestTernaryOp(add3Op, Array.from(addResultHandle), Array.from(bHandle), Array.from(cHandle))
+      .requestTernaryOp(add3Op, Array.from(addResultHandle), Array.from(bHandle), Array.from(cHandle), null, false)
       .accounts({ caller: wallet.publicKey })
       .rpc();
     const event4 = await getEvent(program, provider, tx4, "Fhe16TernaryOpRequested");
@@ -490,136 +825,2929 @@ describe("host-programs", () => {
       "result_handle이 TypeScript에서 계산한 값과 바이트 단위로 일치하지 않습니다"
     ).to.deep.equal(Buffer.from(expectedAdd3ResultHandle));
   });
-});
 
-describe("lending-demo", () => {
-  const provider = anchor.AnchorProvider.env();
-  anchor.setProvider(provider);
+  it("Request pad: different target widths yield different handles", async () => {
+    const inputHandle = new Uint8Array(32);
+    inputHandle.fill(70);
 
-  const lendingProgram = anchor.workspace.LendingDemo as Program<LendingDemo>;
-  const hostProgram = anchor.workspace.HostPrograms as Program<HostPrograms>;
-  const wallet = provider.wallet as anchor.Wallet;
+    const results: Buffer[] = [];
+    for (const targetWidth of [8, 16]) {
+      const tx = await program.methods
+        .requestPad(Array.from(inputHandle), targetWidth, null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
 
-  // Rust enum 순서에 따른 OpCode 매핑
-  // pub enum Fhe16BinaryOp { Add, Sub, Ge }
-  const LENDING_BIN_OPS = {
-    Add: 0,
-    Sub: 1,
-    Ge: 2,
-  };
-  // pub enum Fhe16TernaryOp { Select }
-  const LENDING_TER_OPS = {
-    Select: 0,
-  };
+      const event = await getEvent(program, provider, tx, "Fhe16PadRequested");
+      expect(event.data.targetWidth ?? event.data.target_width).to.equal(targetWidth);
+      assertEventFields(
+        event,
+        {
+          caller: wallet.publicKey,
+          input_handle: inputHandle,
+          result_handle: derivePadHandle(inputHandle, targetWidth, program.programId),
+        },
+        wallet
+      );
+      results.push(Buffer.from(safeGetUint8Array(event.data, "result_handle")));
+    }
 
-  it("Initialize Lending Demo", async () => {
-    await lendingProgram.methods.initialize().rpc();
+    expect(results[0]).to.not.deep.equal(results[1]);
   });
 
-  it("Deposit: SOL + Amount -> Final Handle Verification", async () => {
-    const solBalance = new Uint8Array(32);
-    solBalance.fill(10);
-    const depositAmount = new Uint8Array(32);
-    depositAmount.fill(5);
-
-    const expectedFinalHandle = deriveBinaryHandle(
-      LENDING_BIN_OPS.Add,
-      solBalance,
-      depositAmount,
-      hostProgram.programId 
-    );
+  it("Request truncate: target width is hashed in, result differs from input", async () => {
+    const inputHandle = new Uint8Array(32).fill(173);
 
-    const tx = await lendingProgram.methods
-      .deposit(Array.from(solBalance), Array.from(depositAmount))
-      .accounts({
-        caller: wallet.publicKey,
-      })
-      .rpc();
+    const results: Buffer[] = [];
+    for (const targetWidth of [8, 4]) {
+      const tx = await program.methods
+        .requestTruncate(Array.from(inputHandle), targetWidth, null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
 
-    await provider.connection.confirmTransaction(tx, "confirmed");
-    const txInfo = await provider.connection.getTransaction(tx, { 
-      commitment: "confirmed",
-      maxSupportedTransactionVersion: 0,
-    });
-    
-    if (!txInfo) {
-      throw new Error(`Transaction ${tx} not found`);
-    }
-    
-    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
-    let eventFound = false;
-    const allEvents: string[] = [];
-    
-    for (const event of eventParser.parseLogs(txInfo.meta?.logMessages ?? [])) {
-      allEvents.push(event.name);
-      const camelCaseName = "depositCompleted";
-      if (event.name === "DepositCompleted" || event.name === camelCaseName) {
-        eventFound = true;
-        const finalHandle = safeGetUint8Array(event.data, "final_handle");
-        
-        expect(Buffer.from(finalHandle)).to.deep.equal(
-          Buffer.from(expectedFinalHandle), 
-          "Deposit 결과 핸들이 예상값과 다릅니다."
-        );
-        break;
-      }
+      const event = await getEvent(program, provider, tx, "Fhe16TruncateRequested");
+      expect(event.data.targetWidth ?? event.data.target_width).to.equal(targetWidth);
+      assertEventFields(
+        event,
+        {
+          caller: wallet.publicKey,
+          input_handle: inputHandle,
+          result_handle: deriveTruncateHandle(inputHandle, targetWidth, program.programId),
+        },
+        wallet
+      );
+      results.push(Buffer.from(safeGetUint8Array(event.data, "result_handle")));
     }
-    
-    if (!eventFound) {
-      void console.log(`Available events: ${allEvents.join(", ")}`);
+
+    expect(results[0]).to.not.deep.equal(results[1]);
+    expect(results[0], "truncate 결과가 입력 handle 과 같습니다").to.not.deep.equal(
+      Buffer.from(inputHandle)
+    );
+    expect(results[0], "truncate 와 pad 가 같은 handle 을 만듭니다").to.not.deep.equal(
+      Buffer.from(derivePadHandle(inputHandle, 8, program.programId))
+    );
+
+    let rejected = false;
+    try {
+      await program.methods
+        .requestTruncate(Array.from(inputHandle), 16, null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("InvalidWidth");
     }
-    
-    expect(eventFound, `DepositCompleted event not found. Available events: ${allEvents.join(", ")}`).to.be.true;
+    expect(rejected, "최대 폭으로의 truncate 가 거부되지 않았습니다").to.be.true;
   });
 
-  it("Withdraw: Chained Operations (GE -> SUB -> SELECT)", async () => {
-    const usdcBalance = new Uint8Array(32);
-    usdcBalance.fill(100);
-    const withdrawAmount = new Uint8Array(32);
-    withdrawAmount.fill(30);
+  it("Request concat: order-sensitive, deterministic, emits the joined width", async () => {
+    const lo = new Uint8Array(32).fill(201);
+    const hi = new Uint8Array(32).fill(202);
 
-    const expectedGeHandle = deriveBinaryHandle(
-      LENDING_BIN_OPS.Ge,
-      usdcBalance,
-      withdrawAmount,
-      hostProgram.programId
-    );
+    const concat = async (first: Uint8Array, second: Uint8Array) => {
+      const tx = await program.methods
+        .requestConcat(Array.from(first), Array.from(second), 8, null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+      const event = await getEvent(program, provider, tx, "Fhe16ConcatRequested");
+      expect(event.data.resultWidth ?? event.data.result_width).to.equal(16);
+      assertEventFields(
+        event,
+        {
+          caller: wallet.publicKey,
+          lo_handle: first,
+          hi_handle: second,
+          result_handle: deriveConcatHandle(first, second, 8, program.programId),
+        },
+        wallet
+      );
+      return Buffer.from(safeGetUint8Array(event.data, "result_handle"));
+    };
 
-    const expectedSubHandle = deriveBinaryHandle(
-      LENDING_BIN_OPS.Sub,
-      usdcBalance,
-      withdrawAmount,
-      hostProgram.programId
+    const loHi = await concat(lo, hi);
+    expect(await concat(lo, hi), "같은 입력의 concat 결과가 다릅니다").to.deep.equal(loHi);
+    expect(await concat(hi, lo), "(lo, hi) 와 (hi, lo) 가 같은 handle 입니다").to.not.deep.equal(
+      loHi
     );
 
-    const expectedFinalHandle = deriveTernaryHandle(
-      LENDING_TER_OPS.Select,
-      expectedGeHandle,
-      expectedSubHandle,
-      usdcBalance,
-      hostProgram.programId
+    let rejected = false;
+    try {
+      await program.methods
+        .requestConcat(Array.from(lo), Array.from(hi), 9, null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("InvalidWidth");
+    }
+    expect(rejected, "16 비트를 넘는 concat 이 거부되지 않았습니다").to.be.true;
+  });
+
+  it("Caller nonce: increasing nonce succeeds, reused nonce fails", async () => {
+    const [nonceAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("caller_nonce"), wallet.publicKey.toBuffer()],
+      program.programId
     );
+    await program.methods
+      .initCallerNonce()
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
 
-    const tx = await lendingProgram.methods
-      .withdraw(Array.from(usdcBalance), Array.from(withdrawAmount))
-      .accounts({
-        caller: wallet.publicKey,
-      })
+    const lhsHandle = new Uint8Array(32);
+    lhsHandle.fill(80);
+    const rhsHandle = new Uint8Array(32);
+    rhsHandle.fill(90);
+    const op = { sub: {} };
+
+    const request = (nonce: number) =>
+      program.methods
+        .requestBinaryOp(op, Array.from(lhsHandle), Array.from(rhsHandle), new anchor.BN(nonce), false)
+        .accounts({ caller: wallet.publicKey, nonceAccount })
+        .rpc();
+
+    await request(1);
+    await request(2);
+
+    let reused = false;
+    try {
+      await request(2);
+    } catch (err) {
+      reused = String(err).includes("NonceReused");
+    }
+    expect(reused, "재사용된 nonce 가 거부되지 않았습니다").to.be.true;
+
+    const state = await program.account.callerNonce.fetch(nonceAccount);
+    expect(state.lastNonce.toNumber()).to.equal(2);
+  });
+
+  it("Caller nonce: a non-signer cannot advance another caller's nonce", async () => {
+    const victim = Keypair.generate();
+    const [nonceAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("caller_nonce"), victim.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initCallerNonce()
+      .accounts({ caller: victim.publicKey, payer: wallet.publicKey })
       .rpc();
 
-    await provider.connection.confirmTransaction(tx, "confirmed");
-    const txInfo = await provider.connection.getTransaction(tx, { 
-      commitment: "confirmed",
-      maxSupportedTransactionVersion: 0,
-    });
+    const lhsHandle = new Uint8Array(32);
+    lhsHandle.fill(81);
+    const rhsHandle = new Uint8Array(32);
+    rhsHandle.fill(91);
 
-    if (!txInfo) {
-      throw new Error(`Transaction ${tx} not found`);
+    // victim 은 서명하지 않음 → 수수료 지불자(wallet)만 서명
+    let rejected = false;
+    try {
+      await program.methods
+        .requestBinaryOp({ sub: {} }, Array.from(lhsHandle), Array.from(rhsHandle), new anchor.BN(1_000_000), false)
+        .accounts({ caller: victim.publicKey, nonceAccount })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("NonceRequiresSigner");
     }
+    expect(rejected, "서명 없는 caller 의 nonce 가 전진했습니다").to.be.true;
 
-    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
-    let eventFound = false;
-    const allEvents: string[] = [];
+    const state = await program.account.callerNonce.fetch(nonceAccount);
+    expect(state.lastNonce.toNumber(), "거부된 요청이 nonce 를 바꿨습니다").to.equal(0);
+  });
+
+  it("Event discriminators match Anchor's sha256(\"event:<Name>\") prefix", () => {
+    const names = [
+      "InputHandleRegistered",
+      "Fhe16UnaryOpRequested",
+      "Fhe16BinaryOpRequested",
+      "Fhe16TernaryOpRequested",
+      "Fhe16PadRequested",
+    ];
+    for (const name of names) {
+      const event = (idl.events ?? []).find((e: any) => e.name === name);
+      expect(event, `${name} 이벤트가 IDL 에 없습니다`).to.not.be.undefined;
+      const expected = sha256(new TextEncoder().encode(`event:${name}`)).slice(0, 8);
+      expect(Buffer.from(event.discriminator)).to.deep.equal(Buffer.from(expected));
+    }
+  });
+
+  it("Derive-only mode: no event, handle still in return data", async () => {
+    await program.methods.setEmitEvents(false).accounts({ admin: wallet.publicKey }).rpc();
+
+    const lhsHandle = new Uint8Array(32);
+    lhsHandle.fill(110);
+    const rhsHandle = new Uint8Array(32);
+    rhsHandle.fill(120);
+    const op = { xor: {} };
+    const expectedResultHandle = deriveBinaryHandle(
+      enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", op),
+      lhsHandle,
+      rhsHandle,
+      program.programId
+    );
+
+    try {
+      const txSig = await program.methods
+        .requestBinaryOp(op, Array.from(lhsHandle), Array.from(rhsHandle), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+      await provider.connection.confirmTransaction(txSig, "confirmed");
+      const tx = await provider.connection.getTransaction(txSig, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+
+      const eventParser = new EventParser(program.programId, program.coder);
+      const events = [...eventParser.parseLogs(tx?.meta?.logMessages ?? [])];
+      expect(events, "derive-only 모드에서 이벤트가 발생했습니다").to.have.length(0);
+
+      const returnData = tx?.meta?.returnData;
+      expect(returnData?.programId.toString()).to.equal(program.programId.toString());
+      expect(Buffer.from(returnData?.data[0] ?? "", "base64")).to.deep.equal(
+        Buffer.from(expectedResultHandle)
+      );
+    } finally {
+      await program.methods.setEmitEvents(true).accounts({ admin: wallet.publicKey }).rpc();
+    }
+  });
+
+  it("Handle registry: shared parent ref_count blocks premature close", async () => {
+    const a = new Uint8Array(32).fill(130);
+    const b = new Uint8Array(32).fill(131);
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const c = deriveBinaryHandle(addOp, a, b, program.programId);
+    const d = deriveBinaryHandle(addOp, a, c, program.programId);
+    const [aPda, bPda, cPda, dPda] = [a, b, c, d].map((h) => handleRecordPda(h, program.programId));
+    const parentAccounts = (pdas: PublicKey[]) =>
+      pdas.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+    const createRoot = (handle: Uint8Array) =>
+      program.methods
+        .createHandleRecord(Array.from(handle))
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .rpc();
+    const registerAdd = (lhs: Uint8Array, rhs: Uint8Array, result: Uint8Array) =>
+      program.methods
+        .registerDerived(
+          { binary: { op: { add: {} }, lhs: Array.from(lhs), rhs: Array.from(rhs) } } as any,
+          Array.from(result)
+        )
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .remainingAccounts(
+          parentAccounts([lhs, rhs].map((h) => handleRecordPda(h, program.programId)))
+        )
+        .rpc();
+
+    await createRoot(a);
+    await createRoot(b);
+    // c = ADD(a, b), d = ADD(a, c): a 는 두 consumer 가 공유
+    await registerAdd(a, b, c);
+    await registerAdd(a, c, d);
+
+    expect((await program.account.handleRecord.fetch(aPda)).refCount.toNumber()).to.equal(2);
+    expect((await program.account.handleRecord.fetch(cPda)).refCount.toNumber()).to.equal(1);
+
+    let blocked = false;
+    try {
+      await program.methods
+        .closeHandleRecord()
+        .accounts({ record: aPda, payer: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      blocked = String(err).includes("HandleInUse");
+    }
+    expect(blocked, "참조 중인 record 가 close 되었습니다").to.be.true;
+
+    await program.methods
+      .closeHandleRecord()
+      .accounts({ record: dPda, payer: wallet.publicKey })
+      .remainingAccounts(parentAccounts([aPda, cPda]))
+      .rpc();
+    expect((await program.account.handleRecord.fetch(aPda)).refCount.toNumber()).to.equal(1);
+    expect((await program.account.handleRecord.fetch(cPda)).refCount.toNumber()).to.equal(0);
+  });
+
+  it("Handle registry: signing caller, reserved constants, squatted results reclaimed", async () => {
+    const rejectedWith = async (attempt: () => Promise<string>, name: string) => {
+      try {
+        await attempt();
+      } catch (err) {
+        return String(err).includes(name);
+      }
+      return false;
+    };
+    const outsider = Keypair.generate();
+
+    // caller 서명 없이 다른 주소를 owner 로 하는 record 는 만들 수 없다
+    const root = new Uint8Array(32).fill(134);
+    let unsigned = false;
+    try {
+      await program.methods
+        .createHandleRecord(Array.from(root))
+        .accounts({ caller: outsider.publicKey, payer: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      unsigned = true;
+    }
+    expect(unsigned, "서명 없는 caller 로 record 가 생성되었습니다").to.be.true;
+    expect(
+      await provider.connection.getAccountInfo(handleRecordPda(root, program.programId))
+    ).to.be.null;
+
+    // 공용 상수는 program 만 등록
+    for (const constant of [
+      constantHandle(0, program.programId),
+      booleanConstantHandle(true, program.programId),
+    ]) {
+      expect(
+        await rejectedWith(
+          () =>
+            program.methods
+              .createHandleRecord(Array.from(constant))
+              .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+              .rpc(),
+          "ReservedHandle"
+        ),
+        "상수 handle 의 record 가 생성되었습니다"
+      ).to.be.true;
+    }
+
+    // derive 결과를 root record 로 선점해도 register_derived 가 대체한다
+    const lhs = new Uint8Array(32).fill(135);
+    const rhs = new Uint8Array(32).fill(136);
+    const subOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { sub: {} });
+    const result = deriveBinaryHandle(subOp, lhs, rhs, program.programId);
+    for (const parent of [lhs, rhs]) {
+      await program.methods
+        .createHandleRecord(Array.from(parent))
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .rpc();
+    }
+    await program.methods
+      .createHandleRecord(Array.from(result))
+      .accounts({ caller: outsider.publicKey, payer: wallet.publicKey })
+      .signers([outsider])
+      .rpc();
+    const register = () =>
+      program.methods
+        .registerDerived(
+          { binary: { op: { sub: {} }, lhs: Array.from(lhs), rhs: Array.from(rhs) } } as any,
+          Array.from(result)
+        )
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .remainingAccounts(
+          [lhs, rhs].map((h) => ({
+            pubkey: handleRecordPda(h, program.programId),
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+        .rpc();
+    await register();
+    const record = await program.account.handleRecord.fetch(
+      handleRecordPda(result, program.programId)
+    );
+    expect(record.owner.toString(), "선점한 record 가 대체되지 않았습니다").to.equal(
+      wallet.publicKey.toString()
+    );
+    expect(record.parentCount).to.equal(2);
+
+    // 이미 parents 가 있는 record 는 다시 등록할 수 없다
+    expect(await rejectedWith(register, "HandleRecordConflict")).to.be.true;
+  });
+
+  it("Register derived: correct claimed handle is recorded, wrong one is rejected", async () => {
+    const lhs = new Uint8Array(32).fill(203);
+    const rhs = new Uint8Array(32).fill(204);
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const parentPdas = [lhs, rhs].map((h) => handleRecordPda(h, program.programId));
+    const parentAccounts = parentPdas.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+    for (const parent of [lhs, rhs]) {
+      await program.methods
+        .createHandleRecord(Array.from(parent))
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .rpc();
+    }
+
+    const request = { binary: { op: { add: {} }, lhs: Array.from(lhs), rhs: Array.from(rhs) } };
+    const register = (claimed: Uint8Array) =>
+      program.methods
+        .registerDerived(request as any, Array.from(claimed))
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .remainingAccounts(parentAccounts)
+        .rpc();
+
+    const claimed = deriveBinaryHandle(addOp, lhs, rhs, program.programId);
+    await register(claimed);
+    const record = await program.account.handleRecord.fetch(
+      handleRecordPda(claimed, program.programId)
+    );
+    expect(
+      record.parents.slice(0, record.parentCount).map((p: number[]) => Buffer.from(p)),
+      "record 의 parents 가 operands 와 다릅니다"
+    ).to.deep.equal([Buffer.from(lhs), Buffer.from(rhs)]);
+    expect(Object.keys(record.provenance)[0], "검증된 record 로 표시되지 않았습니다").to.equal(
+      "derived"
+    );
+    const root = await program.account.handleRecord.fetch(parentPdas[0]);
+    expect(Object.keys(root.provenance)[0]).to.equal("local");
+    expect(root.refCount.toNumber()).to.equal(1);
+
+    const swapped = deriveBinaryHandle(addOp, rhs, lhs, program.programId);
+    let mismatched = false;
+    try {
+      await register(swapped);
+    } catch (err) {
+      mismatched = String(err).includes("HandleMismatch");
+    }
+    expect(mismatched, "잘못 계산된 handle 이 등록되었습니다").to.be.true;
+    expect(
+      await provider.connection.getAccountInfo(handleRecordPda(swapped, program.programId)),
+      "거부된 handle 의 record 가 생성되었습니다"
+    ).to.be.null;
+  });
+
+  it("Assert condition source: SELECT condition matches or is rejected", async () => {
+    const cond = new Uint8Array(32).fill(174);
+    const ifTrue = new Uint8Array(32).fill(175);
+    const ifFalse = new Uint8Array(32).fill(176);
+    const other = new Uint8Array(32).fill(177);
+    const selectOp = enumDiscriminantFromIdl(idl, "Fhe16TernaryOp", { select: {} });
+    const selectHandle = deriveTernaryHandle(selectOp, cond, ifTrue, ifFalse, program.programId);
+    const parentPdas = [cond, ifTrue, ifFalse].map((h) => handleRecordPda(h, program.programId));
+
+    for (const parent of [cond, ifTrue, ifFalse]) {
+      await program.methods
+        .createHandleRecord(Array.from(parent))
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .rpc();
+    }
+    const [a, b, c] = [cond, ifTrue, ifFalse].map((h) => Array.from(h));
+    const select = { ternary: { op: { select: {} }, a, b, c } };
+    await program.methods
+      .registerDerived(select as any, Array.from(selectHandle))
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .remainingAccounts(parentPdas.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+      .rpc();
+
+    const tx = await program.methods
+      .assertConditionSource(Array.from(selectHandle), Array.from(cond))
+      .rpc();
+    const event = await getEvent(program, provider, tx, "ConditionSourceConfirmed");
+    expect(Buffer.from(safeGetUint8Array(event.data, "condition_handle"))).to.deep.equal(
+      Buffer.from(cond)
+    );
+
+    let mismatched = false;
+    try {
+      await program.methods
+        .assertConditionSource(Array.from(selectHandle), Array.from(other))
+        .rpc();
+    } catch (err) {
+      mismatched = String(err).includes("ConditionSourceMismatch");
+    }
+    expect(mismatched, "다른 condition 이 확인되었습니다").to.be.true;
+
+    // salt 를 바꿔도 record 에 저장된 salt 로 다시 derive 한다
+    await program.methods
+      .rotateDomainSalt(Array.from(new Uint8Array(16).fill(0x3c)))
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+    try {
+      await program.methods
+        .assertConditionSource(Array.from(selectHandle), Array.from(cond))
+        .rpc();
+    } finally {
+      await program.methods
+        .rotateDomainSalt(Array.from(new Uint8Array(16)))
+        .accounts({ admin: wallet.publicKey })
+        .rpc();
+    }
+  });
+
+  it("Submit envelope: mixed requests derive like the single instructions", async () => {
+    const x = new Uint8Array(32).fill(140);
+    const y = new Uint8Array(32).fill(141);
+    const tag = new Uint8Array(32).fill(142);
+    const z = new Uint8Array(32).fill(143);
+    const notOp = enumDiscriminantFromIdl(idl, "Fhe16UnaryOp", { not: {} });
+    const subOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { sub: {} });
+    const selectOp = enumDiscriminantFromIdl(idl, "Fhe16TernaryOp", { select: {} });
+
+    const payload = encodeEnvelope([
+      { kind: "register", handle: x, clientTag: tag },
+      { kind: "unary", op: notOp, input: x },
+      { kind: "binary", op: subOp, lhs: x, rhs: y },
+      { kind: "ternary", op: selectOp, a: x, b: y, c: z },
+      { kind: "pad", targetWidth: 16, input: y },
+    ]);
+    const expected = [
+      x,
+      deriveUnaryHandle(notOp, x, program.programId),
+      deriveBinaryHandle(subOp, x, y, program.programId),
+      deriveTernaryHandle(selectOp, x, y, z, program.programId),
+      derivePadHandle(y, 16, program.programId),
+    ];
+
+    const tx = await program.methods
+      .submitEnvelope(payload, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    const event = await getEvent(program, provider, tx, "Fhe16EnvelopeRequested");
+    const results = (event.data.resultHandles ?? event.data.result_handles) as number[][];
+    expect(results.map((h) => Buffer.from(h))).to.deep.equal(expected.map((h) => Buffer.from(h)));
+
+    let malformed = false;
+    try {
+      await program.methods
+        .submitEnvelope(payload.subarray(0, payload.length - 1), false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      malformed = String(err).includes("MalformedEnvelope");
+    }
+    expect(malformed, "잘린 envelope 이 거부되지 않았습니다").to.be.true;
+  });
+
+  it("Strict mode: SUB(x, x) rejected, ADD(x, x) allowed", async () => {
+    const x = new Uint8Array(32).fill(160);
+    await program.methods.setStrictMode(true).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      let rejected = false;
+      try {
+        await program.methods
+          .requestBinaryOp({ sub: {} }, Array.from(x), Array.from(x), null, false)
+          .accounts({ caller: wallet.publicKey })
+          .rpc();
+      } catch (err) {
+        rejected = String(err).includes("TrivialSelfOp");
+      }
+      expect(rejected, "SUB(x, x) 가 strict mode 에서 거부되지 않았습니다").to.be.true;
+
+      const tx = await program.methods
+        .requestBinaryOp({ add: {} }, Array.from(x), Array.from(x), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+      const event = await getEvent(program, provider, tx, "Fhe16BinaryOpRequested");
+      assertEventFields(event, { lhs_handle: x, rhs_handle: x }, wallet);
+    } finally {
+      await program.methods.setStrictMode(false).accounts({ admin: wallet.publicKey }).rpc();
+    }
+  });
+
+  it("CPI depth guard: max_cpi_depth must stay within 1..=MAX_STACK_HEIGHT", async () => {
+    const inputHandle = new Uint8Array(32).fill(150);
+    const setDepth = async (depth: number) => {
+      try {
+        await program.methods.setMaxCpiDepth(depth).accounts({ admin: wallet.publicKey }).rpc();
+        return true;
+      } catch (err) {
+        expect(String(err)).to.include("MaxCpiDepthOutOfRange");
+        return false;
+      }
+    };
+
+    // 0 이면 top-level 요청까지 모두 거부되므로 설정 자체가 거부된다
+    expect(await setDepth(0), "max_cpi_depth 0 이 설정되었습니다").to.be.false;
+    expect(await setDepth(6), "stack 한계를 넘는 max_cpi_depth 가 설정되었습니다").to.be.false;
+    try {
+      expect(await setDepth(1), "하한 1 이 거부되었습니다").to.be.true;
+      // top-level 호출의 stack height 는 1 → 하한에서도 통과
+      await program.methods
+        .requestUnaryOp({ neg: {} }, Array.from(inputHandle), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+      expect(await setDepth(5), "상한 MAX_STACK_HEIGHT 가 거부되었습니다").to.be.true;
+    } finally {
+      await program.methods.setMaxCpiDepth(2).accounts({ admin: wallet.publicKey }).rpc();
+    }
+  });
+
+  it("Export handle: receipt proof matches, forged proof rejected on import", async () => {
+    const h = new Uint8Array(32).fill(170);
+    const recordPda = handleRecordPda(h, program.programId);
+    await program.methods
+      .createHandleRecord(Array.from(h))
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
+
+    const [exportRecord] = PublicKey.findProgramAddressSync(
+      [Buffer.from("export_record"), Buffer.from(h)],
+      program.programId
+    );
+    const tx = await program.methods
+      .exportHandle()
+      .accounts({ record: recordPda, exportRecord, owner: wallet.publicKey })
+      .rpc();
+    const event = await getEvent(program, provider, tx, "HandleExported");
+    const expectedProof = deriveExportProof(h, wallet.publicKey, program.programId);
+    expect(
+      Buffer.from(safeGetUint8Array(event.data, "proof")),
+      "export proof 가 로컬 계산값과 다릅니다"
+    ).to.deep.equal(Buffer.from(expectedProof));
+    const stored = await program.account.exportRecord.fetch(exportRecord);
+    expect(Buffer.from(stored.proof), "export record 의 proof 가 다릅니다").to.deep.equal(
+      Buffer.from(expectedProof)
+    );
+
+    // 다른 handle 로 proof 를 재사용하면 거부되어야 함
+    const forged = new Uint8Array(32).fill(171);
+    let rejected = false;
+    try {
+      await program.methods
+        .importHandle(
+          Array.from(forged),
+          program.programId,
+          wallet.publicKey,
+          Array.from(expectedProof)
+        )
+        .accounts({ sourceRecord: recordPda, sourceExport: exportRecord, payer: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("InvalidExportProof");
+    }
+    expect(rejected, "위조된 export proof 가 거부되지 않았습니다").to.be.true;
+
+    // proof 는 누구나 계산 가능: source 배포의 PDA 가 아닌 계정으로는 import 불가
+    const claimedSource = Keypair.generate().publicKey;
+    const minted = new Uint8Array(32).fill(173);
+    let minting = false;
+    try {
+      await program.methods
+        .importHandle(
+          Array.from(minted),
+          claimedSource,
+          wallet.publicKey,
+          Array.from(deriveExportProof(minted, wallet.publicKey, claimedSource))
+        )
+        .accounts({ sourceRecord: recordPda, sourceExport: exportRecord, payer: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      minting = String(err).includes("InvalidSourceRecord");
+    }
+    expect(minting, "source 배포의 record 없이 import 되었습니다").to.be.true;
+  });
+  it("Tag allowlist: listed prefix allowed, unlisted rejected, empty allows all", async () => {
+    const handle = new Uint8Array(32).fill(180);
+    const allowedTag = new Uint8Array(32).fill(0xaa);
+    const otherTag = new Uint8Array(32).fill(0xbb);
+    const register = (tag: Uint8Array) =>
+      program.methods
+        .registerInputHandle(Array.from(handle), Array.from(tag))
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+
+    // 기본값: 빈 allowlist → 모든 tag 허용
+    await register(otherTag);
+
+    await program.methods
+      .setTagAllowlist([[0xaa, 0xaa, 0xaa, 0xaa]])
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+    try {
+      await register(allowedTag);
+
+      let rejected = false;
+      try {
+        await register(otherTag);
+      } catch (err) {
+        rejected = String(err).includes("TagNotAllowed");
+      }
+      expect(rejected, "allowlist 에 없는 client_tag 가 거부되지 않았습니다").to.be.true;
+    } finally {
+      await program.methods.setTagAllowlist([]).accounts({ admin: wallet.publicKey }).rpc();
+    }
+
+    await register(otherTag);
+  });
+  it("Events carry origin_program of the top-level instruction", async () => {
+    const inputHandle = new Uint8Array(32).fill(190);
+    const tx = await program.methods
+      .requestUnaryOp({ abs: {} }, Array.from(inputHandle), null, false)
+      .accounts({ caller: wallet.publicKey, instructions: SYSVAR_INSTRUCTIONS_PUBKEY })
+      .rpc();
+    const event = await getEvent(program, provider, tx, "Fhe16UnaryOpRequested");
+    // 직접 호출 → origin 은 host program 자신
+    const origin = (event.data.originProgram ?? event.data.origin_program) as PublicKey;
+    expect(
+      origin.toString(),
+      "origin_program 이 top-level program 과 다릅니다"
+    ).to.equal(program.programId.toString());
+
+    // instructions sysvar 를 위조하면 origin 을 꾸밀 수 없어야 함
+    let rejected = false;
+    try {
+      await program.methods
+        .requestUnaryOp({ abs: {} }, Array.from(inputHandle), null, false)
+        .accounts({ caller: wallet.publicKey, instructions: Keypair.generate().publicKey })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("ConstraintAddress");
+    }
+    expect(rejected, "위조된 instructions sysvar 가 거부되지 않았습니다").to.be.true;
+  });
+  it("Admin override: registers for admin, rejects non-admin", async () => {
+    const handle = new Uint8Array(32).fill(200);
+    const clientTag = new Uint8Array(32).fill(0xff); // reserved prefix 도 허용됨
+    const outsider = Keypair.generate();
+
+    let rejected = false;
+    try {
+      await program.methods
+        .adminRegisterInputHandle(Array.from(handle), Array.from(clientTag))
+        .accounts({ caller: wallet.publicKey, admin: outsider.publicKey })
+        .signers([outsider])
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("ConstraintHasOne");
+    }
+    expect(rejected, "admin 이 아닌 서명자가 override 를 사용했습니다").to.be.true;
+
+    const tx = await program.methods
+      .adminRegisterInputHandle(Array.from(handle), Array.from(clientTag))
+      .accounts({ caller: wallet.publicKey, admin: wallet.publicKey })
+      .rpc();
+    const registered = await getEvent(program, provider, tx, "InputHandleRegistered");
+    assertEventFields(registered, { handle, client_tag: clientTag }, wallet);
+    await getEvent(program, provider, tx, "AdminOverrideUsed");
+  });
+  it("Input handle: scheme versions of the same ciphertext never collide", () => {
+    const ciphertext = new Uint8Array(64).map((_, i) => i);
+    const v1 = deriveInputHandle(ciphertext);
+    const v2 = deriveInputHandle(ciphertext, 2);
+    // v1 은 기존 클라이언트 계산 (sha256(ct)) 과 동일해야 함
+    expect(Buffer.from(v1)).to.deep.equal(Buffer.from(sha256(ciphertext)));
+    expect(Buffer.from(v1), "scheme version 이 달라도 handle 이 같습니다").to.not.deep.equal(
+      Buffer.from(v2)
+    );
+  });
+  it("Length limits: envelope and tag allowlist at max, max+1 and zero, parents at max", async () => {
+    const MAX_ENVELOPE_BYTES = 900;
+    const MAX_HANDLE_PARENTS = 3;
+    const MAX_TAG_PREFIXES = 8;
+    const failsWith = async (call: () => Promise<unknown>, error: string) => {
+      try {
+        await call();
+      } catch (err) {
+        return String(err).includes(error);
+      }
+      return false;
+    };
+    const submit = (payload: Buffer) =>
+      program.methods.submitEnvelope(payload, false).accounts({ caller: wallet.publicKey }).rpc();
+
+    // envelope: 1 binary + 6 register + 13 unary = 2 + 66 + 6*65 + 13*34 = 900 bytes
+    const x = new Uint8Array(32).fill(210);
+    const tag = new Uint8Array(32).fill(211);
+    const atMax = encodeEnvelope([
+      { kind: "binary", op: 3, lhs: x, rhs: x },
+      ...Array.from({ length: 6 }, () => ({ kind: "register" as const, handle: x, clientTag: tag })),
+      ...Array.from({ length: 13 }, () => ({ kind: "unary" as const, op: 0, input: x })),
+    ]);
+    expect(atMax.length).to.equal(MAX_ENVELOPE_BYTES);
+    await submit(atMax);
+    expect(await failsWith(() => submit(Buffer.alloc(MAX_ENVELOPE_BYTES + 1)), "EnvelopeTooLarge")).to.be
+      .true;
+    expect(await failsWith(() => submit(Buffer.alloc(0)), "MalformedEnvelope")).to.be.true;
+
+    // parents 는 operands 에서 derive: 최대 arity (ternary) 가 MAX_HANDLE_PARENTS
+    const parents = [212, 213, 214].map((seed) => new Uint8Array(32).fill(seed));
+    for (const parent of parents) {
+      await program.methods
+        .createHandleRecord(Array.from(parent))
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .rpc();
+    }
+    const selectOp = enumDiscriminantFromIdl(idl, "Fhe16TernaryOp", { select: {} });
+    const widest = deriveTernaryHandle(
+      selectOp,
+      parents[0],
+      parents[1],
+      parents[2],
+      program.programId
+    );
+    const [a, b, c] = parents.map((p) => Array.from(p));
+    await program.methods
+      .registerDerived({ ternary: { op: { select: {} }, a, b, c } } as any, Array.from(widest))
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .remainingAccounts(
+        parents.map((p) => ({
+          pubkey: handleRecordPda(p, program.programId),
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
+      .rpc();
+    const widestRecord = await program.account.handleRecord.fetch(
+      handleRecordPda(widest, program.programId)
+    );
+    expect(widestRecord.parentCount).to.equal(MAX_HANDLE_PARENTS);
+
+    // tag allowlist: max 허용, max+1 거부, 0 은 기본값
+    const prefixes = (n: number) => Array.from({ length: n }, (_, i) => [0xa0 + i, 0, 0, 0]);
+    const setAllowlist = (list: number[][]) =>
+      program.methods.setTagAllowlist(list).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      await setAllowlist(prefixes(MAX_TAG_PREFIXES));
+      expect(
+        await failsWith(() => setAllowlist(prefixes(MAX_TAG_PREFIXES + 1)), "TooManyTagPrefixes")
+      ).to.be.true;
+    } finally {
+      await setAllowlist([]);
+    }
+  });
+
+  it("Length limits: fold, vector, dot products and envelope count at max, max+1 and zero", async () => {
+    const MAX_NARY_INPUTS = 8;
+    const MAX_VECTOR_LEN = 8;
+    const MAX_BATCH_OPS = 32;
+    const failsWith = async (call: () => Promise<unknown>, error: string) => {
+      try {
+        await call();
+      } catch (err) {
+        return String(err).includes(error);
+      }
+      return false;
+    };
+    const handles = (n: number, base: number) =>
+      Array.from({ length: n }, (_, i) => Array.from(new Uint8Array(32).fill(base + i)));
+    const caller = { caller: wallet.publicKey };
+    const cases: [string, (n: number) => Promise<unknown>, number, string, string][] = [
+      [
+        "fold",
+        (n) =>
+          program.methods
+            .requestFold({ add: {} }, handles(n, 160), null, false)
+            .accounts(caller)
+            .rpc(),
+        MAX_NARY_INPUTS,
+        "TooManyFoldInputs",
+        "NotEnoughFoldInputs",
+      ],
+      [
+        "vector",
+        (n) =>
+          program.methods
+            .requestVectorOp({ xor: {} }, handles(n, 160), handles(n, 180), null, false)
+            .accounts(caller)
+            .rpc(),
+        MAX_VECTOR_LEN,
+        "InvalidVectorLength",
+        "InvalidVectorLength",
+      ],
+      [
+        "dot product",
+        (n) =>
+          program.methods
+            .requestDotProduct(handles(n, 160), handles(n, 180), null, false)
+            .accounts(caller)
+            .rpc(),
+        MAX_VECTOR_LEN,
+        "InvalidDotProductLength",
+        "InvalidDotProductLength",
+      ],
+      [
+        "scalar dot product",
+        (n) =>
+          program.methods
+            .requestScalarDotProduct(
+              Array.from({ length: n }, (_, i) => i + 1),
+              handles(n, 180),
+              null,
+              false
+            )
+            .accounts(caller)
+            .rpc(),
+        MAX_VECTOR_LEN,
+        "InvalidDotProductLength",
+        "InvalidDotProductLength",
+      ],
+    ];
+    for (const [name, call, max, tooMany, empty] of cases) {
+      await call(max);
+      expect(await failsWith(() => call(max + 1), tooMany), `${name}: max+1 이 거부되지 않았습니다`).to
+        .be.true;
+      expect(await failsWith(() => call(0), empty), `${name}: 0 개가 거부되지 않았습니다`).to.be.true;
+    }
+
+    // envelope 요청 수: max 개는 MAX_ENVELOPE_BYTES 안에 들어가지 않아 encode_envelope doctest 가
+    // 다루고, 여기서는 header 의 count 만으로 max+1 과 0 을 확인
+    const submit = (payload: Buffer) =>
+      program.methods.submitEnvelope(payload, false).accounts(caller).rpc();
+    expect(await failsWith(() => submit(Buffer.from([1, MAX_BATCH_OPS + 1])), "MalformedEnvelope")).to
+      .be.true;
+    expect(await failsWith(() => submit(Buffer.from([1, 0])), "MalformedEnvelope")).to.be.true;
+  });
+
+  it("Length limits: random envelope sizes fail cleanly, never deep in Borsh", async () => {
+    const MAX_ENVELOPE_BYTES = 900;
+    for (let i = 0; i < 12; i++) {
+      const length = Math.floor(Math.random() * (MAX_ENVELOPE_BYTES + 60));
+      const payload = Buffer.from(new Uint8Array(length).map(() => Math.floor(Math.random() * 256)));
+      let error = "";
+      try {
+        await program.methods.submitEnvelope(payload, false).accounts({ caller: wallet.publicKey }).rpc();
+      } catch (err) {
+        error = String(err);
+      }
+      const expected = length > MAX_ENVELOPE_BYTES ? ["EnvelopeTooLarge"] : ["MalformedEnvelope", ""];
+      expect(
+        expected.some((e) => (e === "" ? error === "" : error.includes(e))),
+        `length ${length}: 예상치 못한 오류 ${error}`
+      ).to.be.true;
+    }
+  });
+  it("Template op with templates disabled: OpNotSupported plus watchdog event", async () => {
+    const x = new Uint8Array(32).fill(220);
+    const y = new Uint8Array(32).fill(221);
+    let error = "";
+    let logs: string[] = [];
+    try {
+      await program.methods
+        .requestBinaryOp({ gateTemplete: {} }, Array.from(x), Array.from(y), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      error = String(err);
+      logs = (err as { logs?: string[] }).logs ?? [];
+    }
+    expect(error.includes("OpNotSupported"), "template op 이 거부되지 않았습니다").to.be.true;
+
+    const eventParser = new EventParser(program.programId, program.coder);
+    const warning = [...eventParser.parseLogs(logs)].find(
+      (e) => e.name === "UnimplementedOpRequested" || e.name === "unimplementedOpRequested"
+    );
+    expect(warning, "UnimplementedOpRequested event not found").to.not.be.undefined;
+    // OpCode(u16): binary family 0x0100 + enum discriminant
+    const opCode = (warning!.data.opCode ?? warning!.data.op_code) as Record<number, number>;
+    expect(opCode[0]).to.equal(
+      0x100 + enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { gateTemplete: {} })
+    );
+
+    await program.methods.setTemplatesEnabled(true).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      await program.methods
+        .requestBinaryOp({ gateTemplete: {} }, Array.from(x), Array.from(y), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } finally {
+      await program.methods.setTemplatesEnabled(false).accounts({ admin: wallet.publicKey }).rpc();
+    }
+  });
+  it("Prove provenance: full with all ancestors, partial when one is missing", async () => {
+    const [p, q] = [225, 226].map((seed) => new Uint8Array(32).fill(seed));
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const r = deriveBinaryHandle(addOp, p, q, program.programId);
+    const [pPda, qPda, rPda] = [p, q, r].map((h) => handleRecordPda(h, program.programId));
+    const readonly = (pdas: PublicKey[]) =>
+      pdas.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }));
+    for (const handle of [p, q]) {
+      await program.methods
+        .createHandleRecord(Array.from(handle))
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .rpc();
+    }
+    await program.methods
+      .registerDerived(
+        { binary: { op: { add: {} }, lhs: Array.from(p), rhs: Array.from(q) } } as any,
+        Array.from(r)
+      )
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .remainingAccounts(
+        [pPda, qPda].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+      )
+      .rpc();
+
+    const full = await program.methods
+      .proveProvenance()
+      .accounts({ record: rPda })
+      .remainingAccounts(readonly([qPda, pPda]))
+      .view();
+    expect(full.partial, "모든 조상이 있는데 partial 로 표시되었습니다").to.be.false;
+    expect(full.nodes.map((n: { handle: number[] }) => Buffer.from(n.handle))).to.deep.equal(
+      [r, p, q].map((h) => Buffer.from(h))
+    );
+
+    const partial = await program.methods
+      .proveProvenance()
+      .accounts({ record: rPda })
+      .remainingAccounts(readonly([pPda]))
+      .view();
+    expect(partial.partial, "조상이 빠졌는데 partial 이 아닙니다").to.be.true;
+    expect(partial.nodes.length).to.equal(2);
+    // 빠진 q 는 on-chain record 의 provenance_root 로 대신한다
+    const qRecord = await program.account.handleRecord.fetch(qPda);
+    expect(partial.nodes[0].prunedRoots).to.deep.equal([qRecord.provenanceRoot]);
+  });
+  it("Work cursor: every request advances total_requests, envelopes by request count", async () => {
+    const [workCursor] = PublicKey.findProgramAddressSync(
+      [Buffer.from("work_cursor")],
+      program.programId
+    );
+    const total = async () =>
+      (await program.account.workCursor.fetch(workCursor)).totalRequests.toNumber();
+    const x = new Uint8Array(32).fill(230);
+    const y = new Uint8Array(32).fill(231);
+    const z = new Uint8Array(32).fill(232);
+
+    const before = await total();
+    await program.methods
+      .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    await program.methods
+      .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    await program.methods
+      .requestTernaryOp({ select: {} }, Array.from(x), Array.from(y), Array.from(z), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    const afterSingles = await total();
+    expect(afterSingles - before).to.equal(3);
+
+    // 같은 세 요청을 envelope 로 보내도 cursor 는 똑같이 3 증가
+    const payload = encodeEnvelope([
+      { kind: "unary", op: enumDiscriminantFromIdl(idl, "Fhe16UnaryOp", { not: {} }), input: x },
+      { kind: "binary", op: enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} }), lhs: x, rhs: y },
+      {
+        kind: "ternary",
+        op: enumDiscriminantFromIdl(idl, "Fhe16TernaryOp", { select: {} }),
+        a: x,
+        b: y,
+        c: z,
+      },
+    ]);
+    await program.methods.submitEnvelope(payload, false).accounts({ caller: wallet.publicKey }).rpc();
+    expect((await total()) - afterSingles).to.equal(3);
+
+    // 처음 보는 caller 의 요청도 사전 init 없이 같은 cursor 를 움직인다
+    const other = Keypair.generate();
+    const beforeOther = await total();
+    const otherTx = await program.methods
+      .requestUnaryOp({ not: {} }, Array.from(z), null, false)
+      .accounts({ caller: other.publicKey })
+      .rpc();
+    expect(await total(), "다른 caller 의 요청이 cursor 를 움직이지 않았습니다").to.equal(
+      beforeOther + 1
+    );
+    await provider.connection.confirmTransaction(otherTx, "confirmed");
+    const cursor = await program.account.workCursor.fetch(workCursor);
+    expect(cursor.lastRequestSlot.toNumber()).to.be.greaterThan(0);
+  });
+  it("Fold: ADD over [a, b, c, d] matches the explicit balanced tree", async () => {
+    const [a, b, c, d] = [240, 241, 242, 243].map((seed) => new Uint8Array(32).fill(seed));
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const ab = deriveBinaryHandle(addOp, a, b, program.programId);
+    const cd = deriveBinaryHandle(addOp, c, d, program.programId);
+    const expected = [ab, cd, deriveBinaryHandle(addOp, ab, cd, program.programId)];
+
+    const tx = await program.methods
+      .requestFold({ add: {} }, [a, b, c, d].map((h) => Array.from(h)), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(program.programId, program.coder);
+    const steps = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])]
+      .filter((e) => e.name === "Fhe16BinaryOpRequested" || e.name === "fhe16BinaryOpRequested")
+      .map((e) => Buffer.from(safeGetUint8Array(e.data, "result_handle")));
+    expect(steps, "fold 의 중간/최종 handle 이 pairwise tree 와 다릅니다").to.deep.equal(
+      expected.map((h) => Buffer.from(h))
+    );
+
+    let rejected = false;
+    try {
+      await program.methods
+        .requestFold({ sub: {} }, [a, b, c].map((h) => Array.from(h)), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("NonAssociativeOp");
+    }
+    expect(rejected, "SUB fold 가 거부되지 않았습니다").to.be.true;
+  });
+  it("Strict select: condition equal to a branch rejected, equal branches only in strict mode", async () => {
+    const cond = new Uint8Array(32).fill(245);
+    const a = new Uint8Array(32).fill(246);
+    const b = new Uint8Array(32).fill(247);
+    const ternary = (op: Record<string, object>, x: Uint8Array, y: Uint8Array, z: Uint8Array) =>
+      program.methods
+        .requestTernaryOp(op, Array.from(x), Array.from(y), Array.from(z), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    const suspicious = async (call: () => Promise<unknown>) => {
+      try {
+        await call();
+      } catch (err) {
+        return String(err).includes("SuspiciousSelect");
+      }
+      return false;
+    };
+
+    // 기본값 strict_select = true
+    expect(await suspicious(() => ternary({ select: {} }, cond, a, cond)), "SELECT(c, a, c)").to.be.true;
+    expect(await suspicious(() => ternary({ select: {} }, cond, cond, b)), "SELECT(c, c, b)").to.be.true;
+    // 다른 ternary op 은 영향 없음
+    await ternary({ maj3: {} }, cond, cond, b);
+
+    // 같은 branch 는 strict_mode 에서만 거부
+    await ternary({ select: {} }, cond, a, a);
+    await program.methods.setStrictMode(true).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      expect(await suspicious(() => ternary({ select: {} }, cond, a, a)), "SELECT(c, a, a)").to.be.true;
+    } finally {
+      await program.methods.setStrictMode(false).accounts({ admin: wallet.publicKey }).rpc();
+    }
+
+    // opt-out
+    await program.methods.setStrictSelect(false).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      await ternary({ select: {} }, cond, a, cond);
+    } finally {
+      await program.methods.setStrictSelect(true).accounts({ admin: wallet.publicKey }).rpc();
+    }
+  });
+
+  it("Seed namespaces: handle-seeded PDAs never collide with config or work cursor", async () => {
+    const namespaces = ["config", "caller_nonce", "handle_record", "work_cursor"];
+    for (const a of namespaces) {
+      for (const b of namespaces) {
+        if (a !== b) expect(b.startsWith(a), `${a} 가 ${b} 의 prefix 입니다`).to.be.false;
+      }
+    }
+
+    const [configPda] = PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+    const [workCursorPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("work_cursor")],
+      program.programId
+    );
+    const reserved = [configPda.toBase58(), workCursorPda.toBase58()];
+
+    // 공격자가 고를 법한 handle: 예약 PDA 자체, seed 문자열, 극단값
+    const padded = (text: string) => {
+      const out = new Uint8Array(32);
+      out.set(Buffer.from(text));
+      return out;
+    };
+    const crafted = [
+      configPda.toBytes(),
+      workCursorPda.toBytes(),
+      padded("config"),
+      padded("work_cursor"),
+      new Uint8Array(32),
+      new Uint8Array(32).fill(255),
+    ];
+    for (const handle of crafted) {
+      const pda = handleRecordPda(handle, program.programId).toBase58();
+      expect(reserved, "handle record PDA 가 예약 PDA 와 충돌").to.not.include(pda);
+    }
+
+    // config PDA bytes 를 handle 로 등록해도 config 는 그대로
+    await program.methods
+      .createHandleRecord(Array.from(configPda.toBytes()))
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
+    const config = await program.account.config.fetch(configPda);
+    expect(config.admin.toString()).to.equal(wallet.publicKey.toString());
+  });
+
+  it("Accumulator: three accumulations chain ADD, foreign caller needs public", async () => {
+    const tag = new Uint8Array(16).fill(7);
+    const accumulator = accumulatorPda(wallet.publicKey, tag, program.programId);
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const values = [250, 251, 252].map((seed) => new Uint8Array(32).fill(seed));
+
+    await program.methods
+      .createAccumulator(Array.from(tag))
+      .accounts({ owner: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
+
+    let expected = constantHandle(0, program.programId);
+    for (const value of values) {
+      const tx = await program.methods
+        .accumulate(Array.from(value))
+        .accounts({ caller: wallet.publicKey, accumulator })
+        .rpc();
+      const event = await getEvent(program, provider, tx, "Fhe16BinaryOpRequested");
+      const lhs = safeGetUint8Array(event.data, "lhs_handle");
+      expect(Buffer.from(lhs), "이전 누적값에 이어지지 않았습니다").to.deep.equal(Buffer.from(expected));
+      expected = deriveBinaryHandle(addOp, expected, value, program.programId);
+      expect(Buffer.from(safeGetUint8Array(event.data, "result_handle"))).to.deep.equal(
+        Buffer.from(expected)
+      );
+    }
+
+    const stored = await program.methods.readAccumulator().accounts({ accumulator }).view();
+    expect(Buffer.from(stored), "저장된 handle 이 ADD chain 결과와 다릅니다").to.deep.equal(
+      Buffer.from(expected)
+    );
+
+    const outsider = Keypair.generate();
+    const foreignAccumulate = () =>
+      program.methods
+        .accumulate(Array.from(values[0]))
+        .accounts({ caller: outsider.publicKey, accumulator })
+        .signers([outsider])
+        .rpc();
+
+    let rejected = false;
+    try {
+      await foreignAccumulate();
+    } catch (err) {
+      rejected = String(err).includes("AccumulatorNotPublic");
+    }
+    expect(rejected, "owner 가 아닌 caller 가 누적했습니다").to.be.true;
+
+    await program.methods
+      .setAccumulatorPublic(true)
+      .accounts({ accumulator, owner: wallet.publicKey })
+      .rpc();
+    await foreignAccumulate();
+    const account = await program.account.accumulator.fetch(accumulator);
+    expect(account.accumulateCount.toNumber()).to.equal(4);
+  });
+
+  it("Caller stats: two registrations by one caller report a count of two", async () => {
+    const caller = Keypair.generate().publicKey;
+    const [stats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("caller_stats"), caller.toBuffer()],
+      program.programId
+    );
+    await program.methods.initCallerStats().accounts({ caller, payer: wallet.publicKey }).rpc();
+
+    for (const seed of [253, 254]) {
+      await program.methods
+        .registerInputHandle(Array.from(new Uint8Array(32).fill(seed)), Array.from(new Uint8Array(32)))
+        .accounts({ caller, stats })
+        .rpc();
+    }
+
+    const count = await program.methods.getCallerStats().accounts({ stats }).view();
+    expect(count.toNumber(), "등록 수가 2 가 아닙니다").to.equal(2);
+  });
+
+  it("Templete variants: deprecated names keep their frozen wire discriminants", () => {
+    const frozen: [Record<string, object>, number][] = [
+      [{ gateTemplete: {} }, 23],
+      [{ prefixTemplete: {} }, 24],
+      [{ addPowTwoTemplete: {} }, 25],
+    ];
+    for (const [op, code] of frozen) {
+      expect(enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", op), Object.keys(op)[0]).to.equal(code);
+    }
+  });
+
+  it("Domain salt: derivation uses the configured salt, rotation changes outputs", async () => {
+    const x = new Uint8Array(32).fill(160);
+    const y = new Uint8Array(32).fill(161);
+    const salt = new Uint8Array(16).fill(0x5a);
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const requestAdd = async () => {
+      const tx = await program.methods
+        .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+      const event = await getEvent(program, provider, tx, "Fhe16BinaryOpRequested");
+      return Buffer.from(safeGetUint8Array(event.data, "result_handle"));
+    };
+
+    const unsalted = deriveBinaryHandle(addOp, x, y, program.programId);
+    expect(await requestAdd(), "기본 salt 는 기존 derivation 과 같아야 합니다").to.deep.equal(
+      Buffer.from(unsalted)
+    );
+
+    const rotateTx = await program.methods
+      .rotateDomainSalt(Array.from(salt))
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+    try {
+      const rotated = await getEvent(program, provider, rotateTx, "DomainSaltRotated");
+      expect(Buffer.from(safeGetUint8Array(rotated.data, "new_salt"))).to.deep.equal(Buffer.from(salt));
+
+      const salted = await requestAdd();
+      expect(salted).to.deep.equal(Buffer.from(deriveBinaryHandle(addOp, x, y, program.programId, salt)));
+      expect(salted, "rotation 후에도 handle 이 같습니다").to.not.deep.equal(Buffer.from(unsalted));
+    } finally {
+      await program.methods
+        .rotateDomainSalt(Array.from(new Uint8Array(16)))
+        .accounts({ admin: wallet.publicKey })
+        .rpc();
+    }
+    expect(await requestAdd()).to.deep.equal(Buffer.from(unsalted));
+  });
+
+  it("LamportsMoved: closing a handle record reports the reclaimed rent", async () => {
+    const handle = new Uint8Array(32).fill(172);
+    const record = handleRecordPda(handle, program.programId);
+    await program.methods
+      .createHandleRecord(Array.from(handle))
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
+    const rent = await provider.connection.getBalance(record, "confirmed");
+
+    const tx = await program.methods
+      .closeHandleRecord()
+      .accounts({ record, payer: wallet.publicKey })
+      .rpc();
+    const event = await getEvent(program, provider, tx, "LamportsMoved");
+    expect(Object.keys(event.data.reason as object)[0]).to.equal("rentReclaim");
+    expect((event.data.from as PublicKey).toString()).to.equal(record.toString());
+    expect((event.data.to as PublicKey).toString()).to.equal(wallet.publicKey.toString());
+    expect((event.data.amount as anchor.BN).toNumber(), "회수된 rent 와 금액이 다릅니다").to.equal(rent);
+    expect(Buffer.from(safeGetUint8Array(event.data, "related_handle"))).to.deep.equal(Buffer.from(handle));
+  });
+
+  it("Dry run: simulation returns the derived handle, landing always fails", async () => {
+    const x = new Uint8Array(32).fill(163);
+    const y = new Uint8Array(32).fill(164);
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const [workCursor] = PublicKey.findProgramAddressSync(
+      [Buffer.from("work_cursor")],
+      program.programId
+    );
+    const total = async () =>
+      (await program.account.workCursor.fetch(workCursor)).totalRequests.toNumber();
+    const before = await total();
+
+    const tx = await program.methods
+      .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, true)
+      .accounts({ caller: wallet.publicKey })
+      .transaction();
+    tx.feePayer = wallet.publicKey;
+    tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+    const sim = await provider.connection.simulateTransaction(tx);
+    const logs = sim.value.logs ?? [];
+    expect(sim.value.err, "dry run 이 성공으로 끝났습니다").to.not.be.null;
+    expect(logs.some((l) => l.includes("DryRunComplete"))).to.be.true;
+    expect(logs.some((l) => l.startsWith("Program data:")), "dry run 이 event 를 남겼습니다").to.be.false;
+    const returned = Buffer.from(sim.value.returnData?.data[0] ?? "", "base64");
+    expect(returned, "return data 에 derive 된 handle 이 없습니다").to.deep.equal(
+      Buffer.from(deriveBinaryHandle(addOp, x, y, program.programId))
+    );
+
+    let landed = true;
+    try {
+      await program.methods
+        .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, true)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      landed = !String(err).includes("DryRunComplete");
+    }
+    expect(landed, "dry run transaction 이 land 되었습니다").to.be.false;
+    expect(await total(), "dry run 이 work cursor 를 움직였습니다").to.equal(before);
+  });
+
+  it("ConfigUpdated: admin setter emits field, old and new values", async () => {
+    const tx = await program.methods.setMaxCpiDepth(3).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      const event = await getEvent(program, provider, tx, "ConfigUpdated");
+      expect(Object.keys(event.data.field as object)[0]).to.equal("maxCpiDepth");
+      expect(Array.from(event.data.old as Buffer), "이전 값이 다릅니다").to.deep.equal([2]);
+      expect(Array.from(event.data.new as Buffer), "새 값이 다릅니다").to.deep.equal([3]);
+      expect((event.data.admin as PublicKey).toString()).to.equal(wallet.publicKey.toString());
+    } finally {
+      await program.methods.setMaxCpiDepth(2).accounts({ admin: wallet.publicKey }).rpc();
+    }
+  });
+
+  it("Dedup cache: identical requests in one slot yield one event and one suppression", async () => {
+    const x = new Uint8Array(32).fill(165);
+    const y = new Uint8Array(32).fill(166);
+    const [dedupCache] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dedup_cache"), wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initDedupCache()
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
+
+    // 같은 transaction 에 넣어 같은 slot 을 보장
+    const request = () =>
+      program.methods
+        .requestBinaryOp({ xor: {} }, Array.from(x), Array.from(y), null, false)
+        .accounts({ caller: wallet.publicKey, dedupCache })
+        .instruction();
+    const tx = new anchor.web3.Transaction().add(await request(), await request());
+    const sig = await provider.sendAndConfirm(tx, [], { commitment: "confirmed" });
+
+    const txInfo = await provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(program.programId, program.coder);
+    const names = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].map((e) => e.name);
+    const count = (name: string) =>
+      names.filter((n) => n === name || n === name.charAt(0).toLowerCase() + name.slice(1)).length;
+    expect(count("Fhe16BinaryOpRequested"), "실제 요청 event 수").to.equal(1);
+    expect(count("SuppressedDuplicate"), "중복 억제 event 수").to.equal(1);
+  });
+
+  it("Tx receipt: off-chain fold reproduces it, reordering requests changes it", async () => {
+    const x = new Uint8Array(32).fill(167);
+    const y = new Uint8Array(32).fill(168);
+    const tag = new Uint8Array(32);
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const notOp = enumDiscriminantFromIdl(idl, "Fhe16UnaryOp", { not: {} });
+
+    const register = () =>
+      program.methods
+        .registerInputHandle(Array.from(x), Array.from(tag))
+        .accounts({ caller: wallet.publicKey })
+        .instruction();
+    const add = () =>
+      program.methods
+        .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .instruction();
+    const not = () =>
+      program.methods
+        .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .instruction();
+    const receiptAfter = async (ixs: anchor.web3.TransactionInstruction[]) =>
+      Buffer.from(
+        await program.methods
+          .txReceipt()
+          .accounts({ instructions: SYSVAR_INSTRUCTIONS_PUBKEY })
+          .preInstructions(ixs)
+          .view()
+      );
+
+    const fold = () =>
+      program.methods
+        .requestFold({ add: {} }, [Array.from(x), Array.from(y)], null, false)
+        .accounts({ caller: wallet.publicKey })
+        .instruction();
+
+    const summaries = {
+      register: Uint8Array.from([0, 0, ...x, ...tag]),
+      add: Uint8Array.from([0, 2, addOp, ...x, ...y]),
+      not: Uint8Array.from([0, 1, notOp, ...x]),
+      // fold 는 단계별이 아니라 instruction 인자 그대로 한 entry
+      fold: Uint8Array.from([1, addOp, 2, 0, 0, 0, ...x, ...y]),
+    };
+    const inOrder = await receiptAfter([await register(), await add(), await not()]);
+    expect(inOrder, "off-chain receipt 와 다릅니다").to.deep.equal(
+      Buffer.from(foldReceipts([summaries.register, summaries.add, summaries.not]))
+    );
+
+    const reordered = await receiptAfter([await register(), await not(), await add()]);
+    expect(reordered).to.deep.equal(
+      Buffer.from(foldReceipts([summaries.register, summaries.not, summaries.add]))
+    );
+    expect(reordered, "순서를 바꿔도 receipt 가 같습니다").to.not.deep.equal(inOrder);
+
+    const withFold = await receiptAfter([await add(), await fold()]);
+    expect(withFold, "fold 가 receipt 에 빠졌습니다").to.deep.equal(
+      Buffer.from(foldReceipts([summaries.add, summaries.fold]))
+    );
+  });
+
+  it("Derivation anomaly: mismatching result record fails, or is reported in soft mode", async () => {
+    const x = new Uint8Array(32).fill(178);
+    const notOp = enumDiscriminantFromIdl(idl, "Fhe16UnaryOp", { not: {} });
+    const result = deriveUnaryHandle(notOp, x, program.programId);
+    const resultRecord = handleRecordPda(result, program.programId);
+
+    // NOT(x) 의 record 를 parent 없이 만들어 provenance 불일치를 흉내낸다
+    await program.methods
+      .createHandleRecord(Array.from(result))
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
+    const requestNot = () =>
+      program.methods
+        .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+        .accounts({ caller: wallet.publicKey, resultRecord })
+        .rpc();
+
+    let mismatched = false;
+    try {
+      await requestNot();
+    } catch (err) {
+      mismatched = String(err).includes("ProvenanceMismatch");
+    }
+    expect(mismatched, "provenance 불일치가 거부되지 않았습니다").to.be.true;
+
+    // 다른 handle 의 record 는 soft mode 와 무관하게 거부
+    let foreign = false;
+    try {
+      await program.methods
+        .requestUnaryOp({ abs: {} }, Array.from(x), null, false)
+        .accounts({ caller: wallet.publicKey, resultRecord })
+        .rpc();
+    } catch (err) {
+      foreign = String(err).includes("ResultRecordMismatch");
+    }
+    expect(foreign, "다른 handle 의 result record 가 통과했습니다").to.be.true;
+
+    await program.methods.setAnomalySoftFail(true).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      const tx = await requestNot();
+      const event = await getEvent(program, provider, tx, "DerivationAnomaly");
+      expect(Object.keys(event.data.kind as object)[0]).to.equal("provenanceMismatch");
+      const opCode = (event.data.opCode ?? event.data.op_code) as Record<number, number>;
+      expect(opCode[0], "unary family 의 not 이어야 합니다").to.equal(
+        enumDiscriminantFromIdl(idl, "Fhe16UnaryOp", { not: {} })
+      );
+      expect(Buffer.from(safeGetUint8Array(event.data, "result_handle"))).to.deep.equal(
+        Buffer.from(result)
+      );
+    } finally {
+      await program.methods.setAnomalySoftFail(false).accounts({ admin: wallet.publicKey }).rpc();
+    }
+  });
+
+  it("Sequence reservation: a three-step fold takes three consecutive seqs in one write", async () => {
+    const [workCursor] = PublicKey.findProgramAddressSync(
+      [Buffer.from("work_cursor")],
+      program.programId
+    );
+    const total = async () =>
+      (await program.account.workCursor.fetch(workCursor)).totalRequests.toNumber();
+    const handles = [181, 182, 183, 184].map((seed) => new Uint8Array(32).fill(seed));
+
+    const before = await total();
+    const tx = await program.methods
+      .requestFold({ add: {} }, handles.map((h) => Array.from(h)), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    expect((await total()) - before).to.equal(3);
+
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(program.programId, program.coder);
+    const events = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])];
+    const reserved = events.filter(
+      (e) => e.name === "SequenceReserved" || e.name === "sequenceReserved"
+    );
+    expect(reserved.length, "sequence 예약은 instruction 당 한 번이어야 합니다").to.equal(1);
+    const data = reserved[0].data as any;
+    expect((data.firstSeq ?? data.first_seq).toNumber()).to.equal(before);
+    expect(data.count.toNumber()).to.equal(3);
+
+    // 예약 event 뒤에 seq 순서대로 세 binary 요청이 이어진다
+    const names = events.map((e) => e.name.replace(/^./, (c) => c.toLowerCase()));
+    const at = names.indexOf("sequenceReserved");
+    expect(names.slice(at + 1)).to.deep.equal(Array(3).fill("fhe16BinaryOpRequested"));
+  });
+
+  it("Event format: legacy emits op events only, enriched adds RequestMeta", async () => {
+    const [workCursor] = PublicKey.findProgramAddressSync(
+      [Buffer.from("work_cursor")],
+      program.programId
+    );
+    const x = new Uint8Array(32).fill(188);
+    const eventsOf = async (tx: string) => {
+      await provider.connection.confirmTransaction(tx, "confirmed");
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new EventParser(program.programId, program.coder);
+      return [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].map((e) => ({
+        name: e.name.replace(/^./, (c) => c.toLowerCase()),
+        data: e.data as any,
+      }));
+    };
+    const requestNot = () =>
+      program.methods
+        .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+
+    const legacy = await eventsOf(await requestNot());
+    expect(legacy.map((e) => e.name)).to.deep.equal(["fhe16UnaryOpRequested"]);
+
+    await program.methods.setEventFormat(1).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      const seq = (await program.account.workCursor.fetch(workCursor)).totalRequests.toNumber();
+      const enriched = await eventsOf(await requestNot());
+      expect(enriched.map((e) => e.name)).to.deep.equal(["requestMeta", "fhe16UnaryOpRequested"]);
+      const meta = enriched[0].data;
+      expect(meta.seq.toNumber()).to.equal(seq);
+      expect(meta.timestamp.toNumber()).to.be.greaterThan(0);
+      expect(meta.stackHeight ?? meta.stack_height, "top-level 호출의 depth").to.equal(1);
+    } finally {
+      await program.methods.setEventFormat(0).accounts({ admin: wallet.publicKey }).rpc();
+    }
+
+    let rejected = false;
+    try {
+      await program.methods.setEventFormat(3).accounts({ admin: wallet.publicKey }).rpc();
+    } catch (err) {
+      rejected = String(err).includes("UnknownEventFormat");
+    }
+    expect(rejected, "알 수 없는 event format 이 설정되었습니다").to.be.true;
+  });
+
+  it("Generic request_op: same handles and event bytes as the legacy instructions", async () => {
+    const [x, y, z] = [189, 191, 192].map((seed) => new Uint8Array(32).fill(seed));
+    const eventData = async (tx: string) => {
+      await provider.connection.confirmTransaction(tx, "confirmed");
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return (txInfo?.meta?.logMessages ?? []).filter((log) => log.startsWith("Program data: "));
+    };
+    const caller = { caller: wallet.publicKey };
+    const cases: [string, () => Promise<string>, object][] = [
+      [
+        "unary",
+        () =>
+          program.methods
+            .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+            .accounts(caller)
+            .rpc(),
+        { unary: { op: { not: {} }, input: Array.from(x) } },
+      ],
+      [
+        "binary",
+        () =>
+          program.methods
+            .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, false)
+            .accounts(caller)
+            .rpc(),
+        { binary: { op: { add: {} }, lhs: Array.from(x), rhs: Array.from(y) } },
+      ],
+      [
+        "ternary",
+        () =>
+          program.methods
+            .requestTernaryOp({ add3: {} }, Array.from(x), Array.from(y), Array.from(z), null, false)
+            .accounts(caller)
+            .rpc(),
+        { ternary: { op: { add3: {} }, a: Array.from(x), b: Array.from(y), c: Array.from(z) } },
+      ],
+      [
+        "pad",
+        () => program.methods.requestPad(Array.from(x), 16, null, false).accounts(caller).rpc(),
+        { pad: { input: Array.from(x), targetWidth: 16 } },
+      ],
+      [
+        "truncate",
+        () => program.methods.requestTruncate(Array.from(x), 8, null, false).accounts(caller).rpc(),
+        { truncate: { input: Array.from(x), targetWidth: 8 } },
+      ],
+      [
+        "concat",
+        () =>
+          program.methods
+            .requestConcat(Array.from(x), Array.from(y), 8, null, false)
+            .accounts(caller)
+            .rpc(),
+        { concat: { lo: Array.from(x), hi: Array.from(y), halfWidth: 8 } },
+      ],
+    ];
+
+    for (const [kind, legacy, request] of cases) {
+      const legacyEvents = await eventData(await legacy());
+      const genericEvents = await eventData(
+        await program.methods.requestOp(request as any, null, false).accounts(caller).rpc()
+      );
+      expect(legacyEvents.length, `${kind}: event 수`).to.equal(1);
+      expect(genericEvents, `${kind}: request_op 의 event 가 legacy 와 다릅니다`).to.deep.equal(
+        legacyEvents
+      );
+    }
+
+    let rejected = false;
+    try {
+      await program.methods.requestOp({ reserved6: {} } as any, null, false).accounts(caller).rpc();
+    } catch (err) {
+      rejected = String(err).includes("OpNotSupported");
+    }
+    expect(rejected, "reserved variant 가 거부되지 않았습니다").to.be.true;
+  });
+
+  it("Event sizes: pinned per event, packed fold events are at least 25% smaller", async () => {
+    const x = new Uint8Array(32).fill(193);
+    const y = new Uint8Array(32).fill(194);
+    const z = new Uint8Array(32).fill(195);
+    const caller = { caller: wallet.publicKey };
+    const sequenceReserved = hashv([new TextEncoder().encode("event:SequenceReserved")]).slice(0, 8);
+    const requestEventData = async (tx: string) => {
+      await provider.connection.confirmTransaction(tx, "confirmed");
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return (txInfo?.meta?.logMessages ?? [])
+        .filter((log) => log.startsWith("Program data: "))
+        .map((log) => Buffer.from(log.slice("Program data: ".length), "base64"))
+        .filter((data) => !data.subarray(0, 8).equals(Buffer.from(sequenceReserved)));
+    };
+    const eventSize = async (tx: Promise<string>) => {
+      const data = await requestEventData(await tx);
+      expect(data.length).to.equal(1);
+      return data[0].length;
+    };
+
+    // 현재 layout 의 event 크기 (discriminator 포함)
+    const sizes = {
+      register: await eventSize(
+        program.methods
+          .registerInputHandle(Array.from(z), Array.from(new Uint8Array(32)))
+          .accounts(caller)
+          .rpc()
+      ),
+      unary: await eventSize(
+        program.methods.requestUnaryOp({ not: {} }, Array.from(x), null, false).accounts(caller).rpc()
+      ),
+      binary: await eventSize(
+        program.methods
+          .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, false)
+          .accounts(caller)
+          .rpc()
+      ),
+      ternary: await eventSize(
+        program.methods
+          .requestTernaryOp({ add3: {} }, Array.from(x), Array.from(y), Array.from(z), null, false)
+          .accounts(caller)
+          .rpc()
+      ),
+      pad: await eventSize(
+        program.methods.requestPad(Array.from(x), 16, null, false).accounts(caller).rpc()
+      ),
+      truncate: await eventSize(
+        program.methods.requestTruncate(Array.from(x), 8, null, false).accounts(caller).rpc()
+      ),
+    };
+    expect(sizes).to.deep.equal({
+      register: 137,
+      unary: 137,
+      binary: 169,
+      ternary: 201,
+      pad: 137,
+      truncate: 137,
+    });
+
+    // 6 handle fold = 한 트랜잭션의 5 binary 요청
+    const handles = [193, 194, 195, 196, 197, 198].map((seed) => new Uint8Array(32).fill(seed));
+    const fold = () =>
+      program.methods
+        .requestFold({ add: {} }, handles.map((h) => Array.from(h)), null, false)
+        .accounts(caller)
+        .rpc();
+    const eventParser = new EventParser(program.programId, program.coder);
+    const parse = async (tx: string) => {
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].map((e) => e.data as any);
+    };
+
+    const legacyTx = await fold();
+    const legacyData = await requestEventData(legacyTx);
+    await program.methods.setCompactEvents(true).accounts({ admin: wallet.publicKey }).rpc();
+    let packedTx: string;
+    try {
+      packedTx = await fold();
+    } finally {
+      await program.methods.setCompactEvents(false).accounts({ admin: wallet.publicKey }).rpc();
+    }
+    const packedData = await requestEventData(packedTx);
+    const total = (data: Buffer[]) => data.reduce((sum, d) => sum + d.length, 0);
+    expect(legacyData.length).to.equal(5);
+    expect(packedData.length).to.equal(5);
+    expect(total(packedData), "packed event 가 25% 이상 작지 않습니다").to.be.at.most(
+      total(legacyData) * 0.75
+    );
+
+    // packed event 에서 원래 binary event 를 복원
+    const legacy = (await parse(legacyTx)).filter((e) => e.lhsHandle ?? e.lhs_handle);
+    let last: { caller: PublicKey; origin: PublicKey } | null = null;
+    const restored = (await parse(packedTx))
+      .filter((e) => e.request)
+      .map((e) => {
+        const request = Buffer.from(e.request);
+        if (e.caller) {
+          last = { caller: e.caller, origin: e.originProgram ?? e.origin_program };
+        }
+        const opCode = ((e.opCode ?? e.op_code) as Record<number, number>)[0];
+        expect(opCode >> 8, "binary op family").to.equal(1);
+        return {
+          caller: last!.caller.toBase58(),
+          op: opCode & 0xff,
+          lhs: request.subarray(0, 32),
+          rhs: request.subarray(32, 64),
+          result: Buffer.from(safeGetUint8Array(e, "result_handle")),
+          origin: last!.origin.toBase58(),
+        };
+      });
+    expect(restored).to.deep.equal(
+      legacy.map((e) => ({
+        caller: e.caller.toBase58(),
+        op: enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", e.op),
+        lhs: Buffer.from(safeGetUint8Array(e, "lhs_handle")),
+        rhs: Buffer.from(safeGetUint8Array(e, "rhs_handle")),
+        result: Buffer.from(safeGetUint8Array(e, "result_handle")),
+        origin: (e.originProgram ?? e.origin_program).toBase58(),
+      }))
+    );
+  });
+
+  it("Hash domain: program_id changes the handle for every arity", async () => {
+    const x = new Uint8Array(32).fill(233);
+    const y = new Uint8Array(32).fill(234);
+    const z = new Uint8Array(32).fill(235);
+    const otherProgramId = Keypair.generate().publicKey;
+    const notOp = enumDiscriminantFromIdl(idl, "Fhe16UnaryOp", { not: {} });
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const add3Op = enumDiscriminantFromIdl(idl, "Fhe16TernaryOp", { add3: {} });
+    const caller = { caller: wallet.publicKey };
+    const resultOf = async (tx: string, eventName: string) =>
+      Buffer.from(
+        safeGetUint8Array((await getEvent(program, provider, tx, eventName)).data, "result_handle")
+      );
+
+    const unary = await resultOf(
+      await program.methods
+        .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+        .accounts(caller)
+        .rpc(),
+      "Fhe16UnaryOpRequested"
+    );
+    const binary = await resultOf(
+      await program.methods
+        .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, false)
+        .accounts(caller)
+        .rpc(),
+      "Fhe16BinaryOpRequested"
+    );
+    const ternary = await resultOf(
+      await program.methods
+        .requestTernaryOp({ add3: {} }, Array.from(x), Array.from(y), Array.from(z), null, false)
+        .accounts(caller)
+        .rpc(),
+      "Fhe16TernaryOpRequested"
+    );
+
+    // on-chain 결과는 host program id 로 계산한 값과 같고, 다른 program id 와는 달라야 한다
+    const cases = [
+      {
+        arity: "unary",
+        actual: unary,
+        derive: (id: PublicKey) => deriveUnaryHandle(notOp, x, id),
+      },
+      {
+        arity: "binary",
+        actual: binary,
+        derive: (id: PublicKey) => deriveBinaryHandle(addOp, x, y, id),
+      },
+      {
+        arity: "ternary",
+        actual: ternary,
+        derive: (id: PublicKey) => deriveTernaryHandle(add3Op, x, y, z, id),
+      },
+    ];
+    for (const { arity, actual, derive } of cases) {
+      expect(actual, `${arity}: host program id 로 계산한 handle 과 다릅니다`).to.deep.equal(
+        Buffer.from(derive(program.programId))
+      );
+      expect(
+        actual,
+        `${arity}: program id 가 바뀌어도 handle 이 같습니다`
+      ).to.not.deep.equal(Buffer.from(derive(otherProgramId)));
+    }
+  });
+
+  it("Categorized format: binary events carry the op category tag", async () => {
+    const x = new Uint8Array(32).fill(236);
+    const y = new Uint8Array(32).fill(237);
+    const requestBinary = (op: Record<string, object>) =>
+      program.methods
+        .requestBinaryOp(op, Array.from(x), Array.from(y), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    const categoryByte = async (tx: string) => {
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      // RequestMeta 가 앞서므로 op event 는 마지막 data log
+      const log = (txInfo?.meta?.logMessages ?? [])
+        .filter((l) => l.startsWith("Program data: "))
+        .pop();
+      return Buffer.from(log!.slice("Program data: ".length), "base64")[8];
+    };
+    const comparison = enumDiscriminantFromIdl(idl, "BinaryOpCategory", { comparison: {} });
+
+    await program.methods.setEventFormat(2).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      const geTx = await requestBinary({ ge: {} });
+      const ge = await getEvent(program, provider, geTx, "Fhe16BinaryOpCategorized");
+      expect(Object.keys(ge.data.category as object)[0]).to.equal("comparison");
+      expect(Buffer.from(safeGetUint8Array(ge.data, "result_handle"))).to.deep.equal(
+        Buffer.from(
+          deriveBinaryHandle(
+            enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { ge: {} }),
+            x,
+            y,
+            program.programId
+          )
+        )
+      );
+      // discriminator 바로 뒤 byte 로 decode 없이 거를 수 있어야 한다
+      expect(await categoryByte(geTx), "category byte 위치").to.equal(comparison);
+      await getEvent(program, provider, geTx, "RequestMeta");
+
+      const add = await getEvent(
+        program,
+        provider,
+        await requestBinary({ add: {} }),
+        "Fhe16BinaryOpCategorized"
+      );
+      expect(Object.keys(add.data.category as object)[0]).to.equal("arithmetic");
+    } finally {
+      await program.methods.setEventFormat(0).accounts({ admin: wallet.publicKey }).rpc();
+    }
+
+    // 기본 format 은 통합 event 를 그대로 유지
+    await getEvent(program, provider, await requestBinary({ ge: {} }), "Fhe16BinaryOpRequested");
+  });
+
+  it("Derivation preimage: sha256 of the returned bytes is the derived handle", async () => {
+    const [x, y, z] = [241, 242, 243].map((seed) => new Uint8Array(32).fill(seed));
+    const op = (name: string, enumType: string) =>
+      enumDiscriminantFromIdl(idl, enumType, { [name]: {} });
+    const cases: [string, object, Uint8Array][] = [
+      [
+        "unary",
+        { unary: { op: { neg: {} }, input: Array.from(x) } },
+        deriveUnaryHandle(op("neg", "Fhe16UnaryOp"), x, program.programId),
+      ],
+      [
+        "binary",
+        { binary: { op: { lt: {} }, lhs: Array.from(x), rhs: Array.from(y) } },
+        deriveBinaryHandle(op("lt", "Fhe16BinaryOp"), x, y, program.programId),
+      ],
+      [
+        "ternary",
+        { ternary: { op: { select: {} }, a: Array.from(x), b: Array.from(y), c: Array.from(z) } },
+        deriveTernaryHandle(op("select", "Fhe16TernaryOp"), x, y, z, program.programId),
+      ],
+      [
+        "pad",
+        { pad: { input: Array.from(x), targetWidth: 12 } },
+        derivePadHandle(x, 12, program.programId),
+      ],
+      [
+        "truncate",
+        { truncate: { input: Array.from(x), targetWidth: 4 } },
+        deriveTruncateHandle(x, 4, program.programId),
+      ],
+    ];
+
+    for (const [kind, request, expected] of cases) {
+      const preimage: Buffer = await program.methods.derivationPreimage(request as any).view();
+      expect(
+        Buffer.from(sha256(preimage)),
+        `${kind}: sha256(preimage) 가 handle 과 다릅니다`
+      ).to.deep.equal(Buffer.from(expected));
+    }
+  });
+
+  it("Freeze handle: frozen input blocks downstream requests until unfrozen", async () => {
+    const x = new Uint8Array(32).fill(248);
+    const y = new Uint8Array(32).fill(249);
+    const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+    const sum = deriveBinaryHandle(addOp, x, y, program.programId);
+    const xRecord = handleRecordPda(x, program.programId);
+    const reasonHash = new Uint8Array(32).fill(0xee);
+    await program.methods
+      .createHandleRecord(Array.from(x))
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
+    const requestAdd = () =>
+      program.methods
+        .requestBinaryOp({ add: {} }, Array.from(x), Array.from(y), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    await program.methods
+      .createHandleRecord(Array.from(y))
+      .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+      .rpc();
+    const createChild = () =>
+      program.methods
+        .registerDerived(
+          { binary: { op: { add: {} }, lhs: Array.from(x), rhs: Array.from(y) } } as any,
+          Array.from(sum)
+        )
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .remainingAccounts(
+          [xRecord, handleRecordPda(y, program.programId)].map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+        .rpc();
+
+    const freezeTx = await program.methods
+      .freezeHandle(Array.from(x), Array.from(reasonHash))
+      .accountsPartial({ admin: wallet.publicKey, record: xRecord })
+      .rpc();
+    const frozen = await getEvent(program, provider, freezeTx, "HandleFrozen");
+    expect(Buffer.from(safeGetUint8Array(frozen.data, "reason_hash"))).to.deep.equal(
+      Buffer.from(reasonHash)
+    );
+    expect((await program.account.handleRecord.fetch(xRecord)).frozen).to.be.true;
+
+    for (const [label, attempt] of [
+      ["operand 요청", requestAdd],
+      ["파생 record 생성", createChild],
+    ] as [string, () => Promise<string>][]) {
+      let rejected = false;
+      try {
+        await attempt();
+      } catch (err) {
+        rejected = String(err).includes("HandleFrozen");
+      }
+      expect(rejected, `frozen handle 의 ${label}이 거부되지 않았습니다`).to.be.true;
+    }
+
+    const unfreezeTx = await program.methods
+      .unfreezeHandle(Array.from(x))
+      .accountsPartial({ admin: wallet.publicKey, record: xRecord })
+      .rpc();
+    await getEvent(program, provider, unfreezeTx, "HandleUnfrozen");
+    expect((await program.account.handleRecord.fetch(xRecord)).frozen).to.be.false;
+
+    // 해제 후 정상 처리
+    const event = await getEvent(program, provider, await requestAdd(), "Fhe16BinaryOpRequested");
+    expect(Buffer.from(safeGetUint8Array(event.data, "result_handle"))).to.deep.equal(
+      Buffer.from(sum)
+    );
+    await createChild();
+
+    // record 가 없는 handle 도 config 목록만으로 freeze 된다
+    const z = new Uint8Array(32).fill(250);
+    await program.methods
+      .freezeHandle(Array.from(z), Array.from(reasonHash))
+      .accountsPartial({ admin: wallet.publicKey, record: null })
+      .rpc();
+    let rejected = false;
+    try {
+      await program.methods
+        .requestUnaryOp({ not: {} }, Array.from(z), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("HandleFrozen");
+    }
+    expect(rejected, "record 없는 frozen handle 의 요청이 거부되지 않았습니다").to.be.true;
+    await program.methods
+      .unfreezeHandle(Array.from(z))
+      .accountsPartial({ admin: wallet.publicKey, record: null })
+      .rpc();
+  });
+
+  it("Registration bond: refund on first use or after the delay, forfeit on collection", async () => {
+    const bond = 1_000_000;
+    const [used, delayed, spam, other, usedSpam] = [11, 12, 13, 14, 15].map((seed) =>
+      new Uint8Array(32).fill(seed)
+    );
+    const treasury = Keypair.generate().publicKey;
+    const admin = { admin: wallet.publicKey };
+    const register = (handle: Uint8Array, withRecord = true) =>
+      program.methods
+        .registerInputHandle(Array.from(handle), Array.from(new Uint8Array(32)))
+        .accountsPartial({
+          caller: wallet.publicKey,
+          record: withRecord ? handleRecordPda(handle, program.programId) : null,
+          payer: withRecord ? wallet.publicKey : null,
+          systemProgram: withRecord ? anchor.web3.SystemProgram.programId : null,
+        })
+        .rpc();
+    const lamportsMoved = async (tx: string) => {
+      await provider.connection.confirmTransaction(tx, "confirmed");
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new EventParser(program.programId, program.coder);
+      return [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])]
+        .filter((e) => e.name.toLowerCase() === "lamportsmoved")
+        .map((e) => ({
+          reason: Object.keys(e.data.reason as object)[0],
+          to: (e.data.to as PublicKey).toBase58(),
+          amount: (e.data.amount as anchor.BN).toNumber(),
+        }));
+    };
+    const rejectedWith = async (attempt: () => Promise<unknown>, name: string) => {
+      try {
+        await attempt();
+      } catch (err) {
+        return String(err).includes(name);
+      }
+      return false;
+    };
+
+    const usedRecord = handleRecordPda(used, program.programId);
+    const releaseUsed = () =>
+      program.methods
+        .releaseBond()
+        .accounts({ record: usedRecord, payer: wallet.publicKey })
+        .rpc();
+
+    expect(
+      await rejectedWith(
+        () =>
+          program.methods
+            .setRegistrationBond(new anchor.BN(bond), new anchor.BN(0))
+            .accounts(admin)
+            .rpc(),
+        "BondReleaseTooShort"
+      ),
+      "release 지연 0 이 설정되었습니다"
+    ).to.be.true;
+
+    await program.methods.setTreasury(treasury).accounts(admin).rpc();
+    await program.methods
+      .setRegistrationBond(new anchor.BN(bond), new anchor.BN(1_000_000))
+      .accounts(admin)
+      .rpc();
+    try {
+      expect(
+        await rejectedWith(() => register(other, false), "MissingBondAccounts"),
+        "record 없이 bond 등록이 통과했습니다"
+      ).to.be.true;
+
+      // 1) parent 로 쓰여도 잠겨 있고, 요청 operand 로 처음 쓰이면 지연 전에도 반환된다
+      const registerTx = await register(used);
+      expect(await lamportsMoved(registerTx)).to.deep.equal([
+        { reason: "bond", to: usedRecord.toBase58(), amount: bond },
+      ]);
+      expect(await rejectedWith(releaseUsed, "BondLocked"), "사용 전 bond 가 반환되었습니다").to
+        .be.true;
+      const notOp = enumDiscriminantFromIdl(idl, "Fhe16UnaryOp", { not: {} });
+      await program.methods
+        .registerDerived(
+          { unary: { op: { not: {} }, input: Array.from(used) } } as any,
+          Array.from(deriveUnaryHandle(notOp, used, program.programId))
+        )
+        .accounts({ caller: wallet.publicKey, payer: wallet.publicKey })
+        .remainingAccounts([{ pubkey: usedRecord, isSigner: false, isWritable: true }])
+        .rpc();
+      expect(await rejectedWith(releaseUsed, "BondLocked"), "parent 사용만으로 bond 가 반환되었습니다")
+        .to.be.true;
+
+      const useTx = await program.methods
+        .requestUnaryOp({ not: {} }, Array.from(used), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .remainingAccounts([{ pubkey: usedRecord, isSigner: false, isWritable: true }])
+        .rpc();
+      await provider.connection.confirmTransaction(useTx, "confirmed");
+      const usedState = await program.account.handleRecord.fetch(usedRecord);
+      expect(usedState.firstUsedSlot, "operand 사용이 기록되지 않았습니다").to.not.be.null;
+      expect(await lamportsMoved(await releaseUsed()), "첫 사용 후 bond 반환").to.deep.equal([
+        { reason: "refund", to: wallet.publicKey.toBase58(), amount: bond },
+      ]);
+      expect((await program.account.handleRecord.fetch(usedRecord)).bondLamports.toNumber()).to
+        .equal(0);
+
+      // operand 가 아닌 record 는 거부
+      expect(
+        await rejectedWith(
+          () =>
+            program.methods
+              .requestUnaryOp({ not: {} }, Array.from(other), null, false)
+              .accounts({ caller: wallet.publicKey })
+              .remainingAccounts([{ pubkey: usedRecord, isSigner: false, isWritable: true }])
+              .rpc(),
+          "OperandRecordMismatch"
+        ),
+        "operand 가 아닌 record 가 사용 처리되었습니다"
+      ).to.be.true;
+    } finally {
+      await program.methods
+        .setRegistrationBond(new anchor.BN(bond), new anchor.BN(2))
+        .accounts(admin)
+        .rpc();
+    }
+
+    try {
+      // 2) 지연 후 반환: close 는 bond_release_slots 가 지나야 가능
+      await register(delayed);
+      const delayedRecord = handleRecordPda(delayed, program.programId);
+      const closeDelayed = () =>
+        program.methods
+          .closeHandleRecord()
+          .accounts({ record: delayedRecord, payer: wallet.publicKey })
+          .rpc();
+      expect(await rejectedWith(closeDelayed, "BondLocked"), "지연 전 close 가 통과했습니다").to
+        .be.true;
+      const created = (
+        await program.account.handleRecord.fetch(delayedRecord)
+      ).createdSlot.toNumber();
+      while ((await provider.connection.getSlot("confirmed")) < created + 2) {
+        await new Promise((resolve) => setTimeout(resolve, 200));
+      }
+      const closed = await lamportsMoved(await closeDelayed());
+      expect(closed[0], "close 시 bond 반환").to.deep.equal({
+        reason: "refund",
+        to: wallet.publicKey.toBase58(),
+        amount: bond,
+      });
+      expect(closed[1].reason).to.equal("rentReclaim");
+
+      // 3) 쓰이지 않은 spam handle: 지연이 지난 뒤 수거하면 bond 는 treasury 로.
+      //    operand 로 쓰인 record 는 수거할 수 없다
+      const collect = (handle: Uint8Array) =>
+        program.methods
+          .collectUnusedRecord()
+          .accounts({
+            admin: wallet.publicKey,
+            record: handleRecordPda(handle, program.programId),
+            payer: wallet.publicKey,
+            treasury,
+          })
+          .rpc();
+      await register(spam);
+      await register(usedSpam);
+      await program.methods
+        .requestUnaryOp({ not: {} }, Array.from(usedSpam), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .remainingAccounts([
+          {
+            pubkey: handleRecordPda(usedSpam, program.programId),
+            isSigner: false,
+            isWritable: true,
+          },
+        ])
+        .rpc();
+      expect(await rejectedWith(() => collect(spam), "RecordTooRecent"), "지연 전 수거").to.be
+        .true;
+      const spamCreated = (
+        await program.account.handleRecord.fetch(handleRecordPda(spam, program.programId))
+      ).createdSlot.toNumber();
+      while ((await provider.connection.getSlot("confirmed")) < spamCreated + 2) {
+        await new Promise((resolve) => setTimeout(resolve, 200));
+      }
+      expect(await rejectedWith(() => collect(usedSpam), "RecordUsed"), "사용된 record 수거").to
+        .be.true;
+      const collected = await lamportsMoved(await collect(spam));
+      expect(collected[0]).to.deep.equal({
+        reason: "slash",
+        to: treasury.toBase58(),
+        amount: bond,
+      });
+      expect(await provider.connection.getBalance(treasury, "confirmed")).to.equal(bond);
+    } finally {
+      await program.methods
+        .setRegistrationBond(new anchor.BN(0), new anchor.BN(1))
+        .accounts(admin)
+        .rpc();
+      await program.methods.setTreasury(wallet.publicKey).accounts(admin).rpc();
+    }
+  });
+
+  it("Rate limit: per-slot and windowed caps, reconfiguration applies from the next window", async () => {
+    // throttle 은 서명한 caller 기준 (서명 없는 caller 는 아무 주소나 될 수 있다)
+    const callerKeypair = anchor.web3.Keypair.generate();
+    const caller = callerKeypair.publicKey;
+    const x = new Uint8Array(32).fill(206);
+    const activityPda = (key: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("caller_activity"), key.toBuffer()],
+        program.programId
+      )[0];
+    const activity = activityPda(caller);
+    for (const key of [caller, wallet.publicKey]) {
+      await program.methods
+        .initCallerActivity()
+        .accounts({ caller: key, payer: wallet.publicKey })
+        .rpc();
+    }
+
+    const request = () =>
+      program.methods
+        .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+        .accounts({ caller, activity })
+        .signers([callerKeypair]);
+    const rejectedWith = async (send: () => Promise<unknown>, code: string) => {
+      try {
+        await send();
+      } catch (err) {
+        // sendAndConfirm 실패는 error code 를 logs 에만 남긴다
+        const logs = ((err as { logs?: string[] }).logs ?? []).join("\n");
+        return String(err).includes(code) || logs.includes(code);
+      }
+      return false;
+    };
+    const setRateLimit = (windowSlots: number, maxRequests: number) =>
+      program.methods
+        .setRateLimit(new anchor.BN(windowSlots), maxRequests)
+        .accounts({ admin: wallet.publicKey })
+        .rpc();
+
+    expect(await rejectedWith(() => setRateLimit(0, 1), "InvalidThrottleWindow")).to.be.true;
+
+    try {
+      // 1) window_slots = 1: slot 당 1 회 (같은 transaction 의 두 번째 요청은 거부)
+      await setRateLimit(1, 1);
+      const twoInOneSlot = async () =>
+        provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            await request().instruction(),
+            await request().instruction()
+          ),
+          [callerKeypair]
+        );
+      expect(await rejectedWith(twoInOneSlot, "RateLimited"), "같은 slot 의 두 번째 요청").to.be
+        .true;
+      await request().rpc();
+
+      // 2) 20 slot window 에 1 회: window 중간에 cap 도달
+      await setRateLimit(20, 1);
+      await request().rpc();
+      expect(await rejectedWith(() => request().rpc(), "RateLimited"), "window 중간 cap").to.be
+        .true;
+      expect(
+        await rejectedWith(
+          () =>
+            program.methods
+              .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+              .accounts({ caller })
+              .signers([callerKeypair])
+              .rpc(),
+          "MissingActivityAccount"
+        ),
+        "activity 없이 제한이 우회되었습니다"
+      ).to.be.true;
+      // 다른 key 의 activity 로 바꿔 끼우거나, 서명 없이 caller 를 내세워 새 window 를 열 수 없다
+      expect(
+        await rejectedWith(
+          () =>
+            program.methods
+              .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+              .accounts({ caller, activity: activityPda(wallet.publicKey) })
+              .signers([callerKeypair])
+              .rpc(),
+          "ActivityAccountMismatch"
+        ),
+        "다른 key 의 activity 로 제한이 우회되었습니다"
+      ).to.be.true;
+      const unsigned = anchor.web3.Keypair.generate().publicKey;
+      await program.methods
+        .initCallerActivity()
+        .accounts({ caller: unsigned, payer: wallet.publicKey })
+        .rpc();
+      expect(
+        await rejectedWith(
+          () =>
+            program.methods
+              .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+              .accounts({ caller: unsigned, activity: activityPda(unsigned) })
+              .rpc(),
+          "UnattributedRequest"
+        ),
+        "서명 없는 caller 로 새 window 가 열렸습니다"
+      ).to.be.true;
+
+      // register_input_handle 도 같은 window 를 쓴다
+      expect(
+        await rejectedWith(
+          () =>
+            program.methods
+              .registerInputHandle(Array.from(new Uint8Array(32).fill(207)), Array(32).fill(0))
+              .accounts({ caller, activity })
+              .signers([callerKeypair])
+              .rpc(),
+          "RateLimited"
+        ),
+        "register_input_handle 이 제한을 우회했습니다"
+      ).to.be.true;
+
+      // 3) 재설정은 진행 중인 window 에 적용되지 않는다
+      await setRateLimit(20, 3);
+      expect(
+        await rejectedWith(() => request().rpc(), "RateLimited"),
+        "재설정이 현재 window 에 적용되었습니다"
+      ).to.be.true;
+
+      // 4) window 가 지나면 새 설정 (3 회) 으로 다시 시작
+      const state = await program.account.callerActivity.fetch(activity);
+      const windowEnd = state.windowStartSlot.toNumber() + state.windowSlots.toNumber();
+      while ((await provider.connection.getSlot("confirmed")) < windowEnd) {
+        await new Promise((resolve) => setTimeout(resolve, 200));
+      }
+      for (let i = 0; i < 3; i++) {
+        await request().rpc();
+      }
+      expect(await rejectedWith(() => request().rpc(), "RateLimited"), "새 window 의 cap").to.be
+        .true;
+      const rolled = await program.account.callerActivity.fetch(activity);
+      expect(rolled.maxRequests).to.equal(3);
+      expect(rolled.count.toNumber()).to.equal(3);
+    } finally {
+      await setRateLimit(1, 0);
+    }
+  });
+
+  it("Strictness profiles: same scenario under Permissionless, Standard and Strict", async () => {
+    const c = new Uint8Array(32).fill(207);
+    const x = new Uint8Array(32).fill(208);
+    const y = new Uint8Array(32).fill(209);
+    const admin = { admin: wallet.publicKey };
+    const outcome = async (attempt: () => Promise<unknown>) => {
+      try {
+        await attempt();
+        return "ok";
+      } catch (err) {
+        const code = [
+          "SuspiciousSelect",
+          "TrivialSelfOp",
+          "OpNotSupported",
+          "MissingActivityAccount",
+          "MissingBondAccounts",
+          "CallerNotSigned",
+        ].find((name) => String(err).includes(name));
+        return code ?? String(err);
+      }
+    };
+    let registered = 250;
+    const steps = {
+      selectCondAsBranch: () =>
+        program.methods
+          .requestTernaryOp({ select: {} }, Array.from(c), Array.from(c), Array.from(y), null, false)
+          .accounts({ caller: wallet.publicKey })
+          .rpc(),
+      subSelf: () =>
+        program.methods
+          .requestBinaryOp({ sub: {} }, Array.from(x), Array.from(x), null, false)
+          .accounts({ caller: wallet.publicKey })
+          .rpc(),
+      template: () =>
+        program.methods
+          .requestBinaryOp({ gateTemplete: {} }, Array.from(x), Array.from(y), null, false)
+          .accounts({ caller: wallet.publicKey })
+          .rpc(),
+      // activity account 없이 보내는 요청 (throttle 이 꺼져 있어야 통과)
+      unthrottled: () =>
+        program.methods
+          .requestUnaryOp({ not: {} }, Array.from(x), null, false)
+          .accounts({ caller: wallet.publicKey })
+          .rpc(),
+      // 서명하지 않은 caller 의 요청
+      unsignedCaller: () =>
+        program.methods
+          .requestUnaryOp({ not: {} }, Array.from(y), null, false)
+          .accounts({ caller: anchor.web3.Keypair.generate().publicKey })
+          .rpc(),
+      // bond 계정 없이 등록 (빈 allowlist 는 모든 프로필에서 미설정으로 본다)
+      registerWithoutBond: () =>
+        program.methods
+          .registerInputHandle(
+            Array.from(new Uint8Array(32).fill(++registered)),
+            Array.from(new Uint8Array(32))
+          )
+          .accounts({ caller: wallet.publicKey })
+          .rpc(),
+    };
+    // 개별 플래그와 bond / throttle / allowlist 는 모두 가장 느슨하게 두고 프로필만 바꾼다
+    const expected = {
+      permissionless: {
+        selectCondAsBranch: "ok",
+        subSelf: "ok",
+        template: "ok",
+        unthrottled: "ok",
+        unsignedCaller: "ok",
+        registerWithoutBond: "ok",
+      },
+      standard: {
+        selectCondAsBranch: "SuspiciousSelect",
+        subSelf: "ok",
+        template: "ok",
+        unthrottled: "ok",
+        unsignedCaller: "ok",
+        registerWithoutBond: "MissingBondAccounts",
+      },
+      strict: {
+        selectCondAsBranch: "SuspiciousSelect",
+        subSelf: "TrivialSelfOp",
+        template: "OpNotSupported",
+        unthrottled: "MissingActivityAccount",
+        unsignedCaller: "CallerNotSigned",
+        registerWithoutBond: "MissingBondAccounts",
+      },
+    };
+
+    const [configPda] = PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId);
+    const initial = await program.account.config.fetch(configPda);
+    expect(initial.strictness, "기본 프로필이 Permissionless 가 아닙니다").to.deep.equal({
+      permissionless: {},
+    });
+
+    await program.methods.setStrictSelect(false).accounts(admin).rpc();
+    await program.methods.setTemplatesEnabled(true).accounts(admin).rpc();
+    try {
+      let previous = "permissionless";
+      for (const [profile, outcomes] of Object.entries(expected)) {
+        const tx = await program.methods.setStrictness({ [profile]: {} }).accounts(admin).rpc();
+        const changed = await getEvent(program, provider, tx, "StrictnessChanged");
+        const data = changed.data as { oldProfile: object; newProfile: object };
+        expect(data.oldProfile).to.deep.equal({ [previous]: {} });
+        expect(data.newProfile).to.deep.equal({ [profile]: {} });
+        previous = profile;
+
+        for (const [step, want] of Object.entries(outcomes)) {
+          const got = await outcome(steps[step as keyof typeof steps]);
+          expect(got, `${profile} 프로필의 ${step} 결과가 다릅니다`).to.equal(want);
+        }
+      }
+    } finally {
+      await program.methods.setStrictness({ permissionless: {} }).accounts(admin).rpc();
+      await program.methods.setTemplatesEnabled(false).accounts(admin).rpc();
+      await program.methods.setStrictSelect(true).accounts(admin).rpc();
+    }
+  });
+
+  it("Vector ops: one handle per element pair, lengths checked, binary path deprecated", async () => {
+    const lhs = [210, 211, 212].map((seed) => new Uint8Array(32).fill(seed));
+    const rhs = [213, 214, 215].map((seed) => new Uint8Array(32).fill(seed));
+    const xorOp = enumDiscriminantFromIdl(idl, "Fhe16VectorOp", { xor: {} });
+    const toArrays = (handles: Uint8Array[]) => handles.map((h) => Array.from(h));
+    const vector = (l: Uint8Array[], r: Uint8Array[]) =>
+      program.methods
+        .requestVectorOp({ xor: {} }, toArrays(l), toArrays(r), null, false)
+        .accounts({ caller: wallet.publicKey });
+
+    const tx = await vector(lhs, rhs).rpc();
+    const event = await getEvent(program, provider, tx, "Fhe16VectorOpRequested");
+    expect(event.data.len).to.equal(3);
+    const results = (event.data.results as number[][]).map((h) => Buffer.from(h));
+    const expected = deriveVectorHandles(xorOp, lhs, rhs, program.programId);
+    expect(results, "원소별 handle 이 derivation 과 다릅니다").to.deep.equal(
+      expected.map((h) => Buffer.from(h))
+    );
+    // 같은 원소 쌍이라도 길이가 다르면 다른 handle
+    const [single] = deriveVectorHandles(xorOp, [lhs[0]], [rhs[0]], program.programId);
+    expect(Buffer.from(single).equals(results[0]), "길이가 handle 에 반영되지 않았습니다").to.be
+      .false;
+
+    const rejectedWith = async (attempt: () => Promise<unknown>, name: string) => {
+      try {
+        await attempt();
+      } catch (err) {
+        return String(err).includes(name);
+      }
+      return false;
+    };
+    expect(
+      await rejectedWith(() => vector(lhs, rhs.slice(0, 2)).rpc(), "InvalidVectorLength"),
+      "길이가 다른 vector 가 거부되지 않았습니다"
+    ).to.be.true;
+    expect(
+      await rejectedWith(() => vector([], []).rpc(), "InvalidVectorLength"),
+      "빈 vector 가 거부되지 않았습니다"
+    ).to.be.true;
+    const nine = Array.from({ length: 9 }, () => lhs[0]);
+    expect(
+      await rejectedWith(() => vector(nine, nine).rpc(), "InvalidVectorLength"),
+      "MAX_VECTOR_LEN 초과가 거부되지 않았습니다"
+    ).to.be.true;
+
+    expect(
+      await rejectedWith(
+        () =>
+          program.methods
+            .requestBinaryOp({ xorVec: {} }, Array.from(lhs[0]), Array.from(rhs[0]), null, false)
+            .accounts({ caller: wallet.publicKey })
+            .rpc(),
+        "UseVectorOp"
+      ),
+      "binary 경로의 XorVec 이 거부되지 않았습니다"
+    ).to.be.true;
+  });
+
+  it("Range check: AND(GE(x, lo), LE(x, hi)) with all three handles emitted", async () => {
+    const [x, lo, hi] = [216, 217, 218].map((seed) => new Uint8Array(32).fill(seed));
+    const binOp = (op: Record<string, object>) => enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", op);
+    const ge = deriveBinaryHandle(binOp({ ge: {} }), x, lo, program.programId);
+    const le = deriveBinaryHandle(binOp({ le: {} }), x, hi, program.programId);
+    const and = deriveBinaryHandle(binOp({ and: {} }), ge, le, program.programId);
+
+    const tx = await program.methods
+      .requestRangeCheck(Array.from(x), Array.from(lo), Array.from(hi), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(program.programId, program.coder);
+    const steps = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].filter(
+      (e) => e.name === "Fhe16BinaryOpRequested" || e.name === "fhe16BinaryOpRequested"
+    );
+    expect(steps.map((e) => Buffer.from(safeGetUint8Array(e.data, "result_handle")))).to.deep.equal(
+      [ge, le, and].map((h) => Buffer.from(h))
+    );
+    // 최종 handle 은 두 비교 handle 의 AND
+    const operand = (name: string) => Buffer.from(safeGetUint8Array(steps[2].data, name));
+    expect(operand("lhs_handle")).to.deep.equal(Buffer.from(ge));
+    expect(operand("rhs_handle")).to.deep.equal(Buffer.from(le));
+  });
+
+  it("Dot product: encrypted and scalar weights derive one pinned handle", async () => {
+    const weights = [219, 222, 223].map((seed) => new Uint8Array(32).fill(seed));
+    const values = [224, 228, 229].map((seed) => new Uint8Array(32).fill(seed));
+    const scalars = [3, 0x0102, 65535];
+    const toArrays = (handles: Uint8Array[]) => handles.map((h) => Array.from(h));
+
+    const tx = await program.methods
+      .requestDotProduct(toArrays(weights), toArrays(values), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    const event = await getEvent(program, provider, tx, "Fhe16DotProductRequested");
+    const encrypted = deriveDotProductHandle(weights, values, program.programId);
+    expect(Buffer.from(safeGetUint8Array(event.data, "result_handle"))).to.deep.equal(
+      Buffer.from(encrypted)
+    );
+
+    const scalarTx = await program.methods
+      .requestScalarDotProduct(scalars, toArrays(values), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    const scalarEvent = await getEvent(program, provider, scalarTx, "Fhe16DotProductRequested");
+    const scalar = deriveDotProductHandle(scalars, values, program.programId);
+    expect(Buffer.from(safeGetUint8Array(scalarEvent.data, "result_handle"))).to.deep.equal(
+      Buffer.from(scalar)
+    );
+    expect(Buffer.from(scalar).equals(Buffer.from(encrypted))).to.be.false;
+
+    const rejectedWith = async (attempt: () => Promise<unknown>, name: string) => {
+      try {
+        await attempt();
+      } catch (err) {
+        return String(err).includes(name);
+      }
+      return false;
+    };
+    const mismatched = () =>
+      program.methods
+        .requestDotProduct(toArrays(weights), toArrays(values.slice(0, 2)), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    expect(await rejectedWith(mismatched, "InvalidDotProductLength"), "길이 불일치").to.be.true;
+    const scalarMismatched = () =>
+      program.methods
+        .requestScalarDotProduct([1, 2], toArrays(values), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    expect(await rejectedWith(scalarMismatched, "InvalidDotProductLength"), "scalar 길이 불일치").to
+      .be.true;
+    const single = () =>
+      program.methods
+        .requestScalarDotProduct([1], toArrays(values.slice(0, 1)), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    expect(await rejectedWith(single, "InvalidDotProductLength"), "원소 1 개").to.be.true;
+  });
+
+  // anchor build -- --features cu-profile 로 빌드한 뒤 CU_PROFILE=1 로 실행
+  it("CU profiling: CuUsed reports a positive derivation cost", async function () {
+    if (!process.env.CU_PROFILE) {
+      this.skip();
+    }
+    const [x, y, z] = [230, 231, 232].map((seed) => Array.from(new Uint8Array(32).fill(seed)));
+    const caller = { caller: wallet.publicKey };
+    const requests: [string, () => Promise<string>][] = [
+      [
+        "request_unary_op",
+        () => program.methods.requestUnaryOp({ not: {} }, x, null, false).accounts(caller).rpc(),
+      ],
+      [
+        "request_fold",
+        () =>
+          program.methods.requestFold({ add: {} }, [x, y, z], null, false).accounts(caller).rpc(),
+      ],
+      [
+        "request_range_check",
+        () => program.methods.requestRangeCheck(x, y, z, null, false).accounts(caller).rpc(),
+      ],
+      [
+        "request_dot_product",
+        () => program.methods.requestDotProduct([x, y], [y, z], null, false).accounts(caller).rpc(),
+      ],
+    ];
+    for (const [instruction, send] of requests) {
+      const event = await getEvent(program, provider, await send(), "CuUsed");
+      expect(event.data.instruction).to.equal(instruction);
+      const units = (event.data.units as anchor.BN).toNumber();
+      expect(units, `${instruction} 의 CU 사용량이 0 입니다`).to.be.greaterThan(0);
+      expect(units, `${instruction} 의 CU 사용량이 한도를 넘었습니다`).to.be.lessThan(200_000);
+    }
+  });
+
+  it("Bootstrap constants: zero, one, true and false records, idempotent", async () => {
+    const expected = {
+      zero: constantHandle(0, program.programId),
+      one: constantHandle(1, program.programId),
+      trueHandle: booleanConstantHandle(true, program.programId),
+      falseHandle: booleanConstantHandle(false, program.programId),
+    };
+    const bootstrap = async () => {
+      const tx = await program.methods
+        .bootstrapConstants()
+        .accounts({ payer: wallet.publicKey })
+        .rpc();
+      return (await getEvent(program, provider, tx, "ConstantsBootstrapped")).data;
+    };
+
+    const first = await bootstrap();
+    for (const [name, handle] of Object.entries(expected)) {
+      expect(Buffer.from(first[name] as number[]), `${name} handle 이 다릅니다`).to.deep.equal(
+        Buffer.from(handle)
+      );
+      const [recordPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("handle_record"), Buffer.from(handle)],
+        program.programId
+      );
+      const record = await program.account.handleRecord.fetch(recordPda);
+      expect(Buffer.from(record.handle)).to.deep.equal(Buffer.from(handle));
+      expect(record.owner.toBase58()).to.equal(program.programId.toBase58());
+    }
+    // true / false 는 0 / 1 과 다른 handle
+    expect(Buffer.from(expected.trueHandle).equals(Buffer.from(expected.one))).to.be.false;
+
+    // 재실행은 아무 record 도 만들지 않는다
+    const second = await bootstrap();
+    expect(second.created, "재실행이 record 를 다시 만들었습니다").to.equal(0);
+  });
+
+  it("Strict mode: ternary ops with three identical operands rejected", async () => {
+    const [x, y, z] = [231, 232, 233].map((seed) => new Uint8Array(32).fill(seed));
+    const ternary = (op: Record<string, object>, a: Uint8Array, b: Uint8Array, c: Uint8Array) =>
+      program.methods
+        .requestTernaryOp(op, Array.from(a), Array.from(b), Array.from(c), null, false)
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    const degenerate = async (call: () => Promise<unknown>) => {
+      try {
+        await call();
+      } catch (err) {
+        return String(err).includes("DegenerateTernary");
+      }
+      return false;
+    };
+
+    // strict mode 밖에서는 허용
+    await ternary({ maj3: {} }, x, x, x);
+    await program.methods.setStrictMode(true).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      expect(await degenerate(() => ternary({ maj3: {} }, x, x, x)), "MAJ3(x, x, x)").to.be.true;
+      expect(await degenerate(() => ternary({ select: {} }, x, x, x)), "SELECT(x, x, x)").to.be
+        .true;
+      await ternary({ maj3: {} }, x, y, z);
+      await ternary({ maj3: {} }, x, x, y);
+    } finally {
+      await program.methods.setStrictMode(false).accounts({ admin: wallet.publicKey }).rpc();
+    }
+  });
+});
+
+describe("lending-demo", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const lendingProgram = anchor.workspace.LendingDemo as Program<LendingDemo>;
+  const hostProgram = anchor.workspace.HostPrograms as Program<HostPrograms>;
+  const wallet = provider.wallet as anchor.Wallet;
+
+  // lending-demo 의 op enum 은 host-programs 와 같은 discriminant 를 사용
+  const LENDING_BIN_OPS = {
+    And: 0,
+    Add: 3,
+    Sub: 4,
+    Eq: 6,
+    Ge: 9,
+    SMulL: 20,
+  };
+  const LENDING_TER_OPS = {
+    Select: 4,
+  };
+  // KYC 통과를 뜻하는 암호화된 상수 (demo config 에 기록)
+  const EXPECTED_ATTESTATION = new Uint8Array(32).fill(0x4b);
+
+  it("Initialize Lending Demo", async () => {
+    await lendingProgram.methods.initialize().rpc();
+    await lendingProgram.methods
+      .initializeDemoConfig(wallet.publicKey, Array.from(EXPECTED_ATTESTATION))
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+  });
+
+  it("Deposit: SOL + Amount -> Final Handle Verification", async () => {
+    const solBalance = new Uint8Array(32);
+    solBalance.fill(10);
+    const depositAmount = new Uint8Array(32);
+    depositAmount.fill(5);
+
+    const expectedFinalHandle = deriveBinaryHandle(
+      LENDING_BIN_OPS.Add,
+      solBalance,
+      depositAmount,
+      hostProgram.programId 
+    );
+
+    const tx = await lendingProgram.methods
+      .deposit(Array.from(solBalance), Array.from(depositAmount), null)
+      .accounts({
+        caller: wallet.publicKey,
+      })
+      .rpc();
+
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, { 
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    
+    if (!txInfo) {
+      throw new Error(`Transaction ${tx} not found`);
+    }
+    
+    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
+    let eventFound = false;
+    const allEvents: string[] = [];
+    
+    for (const event of eventParser.parseLogs(txInfo.meta?.logMessages ?? [])) {
+      allEvents.push(event.name);
+      const camelCaseName = "depositCompleted";
+      if (event.name === "DepositCompleted" || event.name === camelCaseName) {
+        eventFound = true;
+        const finalHandle = safeGetUint8Array(event.data, "final_handle");
+        
+        expect(Buffer.from(finalHandle)).to.deep.equal(
+          Buffer.from(expectedFinalHandle), 
+          "Deposit 결과 핸들이 예상값과 다릅니다."
+        );
+        break;
+      }
+    }
+    
+    if (!eventFound) {
+      void console.log(`Available events: ${allEvents.join(", ")}`);
+    }
+    
+    expect(eventFound, `DepositCompleted event not found. Available events: ${allEvents.join(", ")}`).to.be.true;
+  });
+
+  it("Withdraw: Chained Operations (GE -> SUB -> SELECT)", async () => {
+    const usdcBalance = new Uint8Array(32);
+    usdcBalance.fill(100);
+    const withdrawAmount = new Uint8Array(32);
+    withdrawAmount.fill(30);
+
+    const expectedGeHandle = deriveBinaryHandle(
+      LENDING_BIN_OPS.Ge,
+      usdcBalance,
+      withdrawAmount,
+      hostProgram.programId
+    );
+
+    const expectedSubHandle = deriveBinaryHandle(
+      LENDING_BIN_OPS.Sub,
+      usdcBalance,
+      withdrawAmount,
+      hostProgram.programId
+    );
+
+    const expectedFinalHandle = deriveTernaryHandle(
+      LENDING_TER_OPS.Select,
+      expectedGeHandle,
+      expectedSubHandle,
+      usdcBalance,
+      hostProgram.programId
+    );
+
+    const tx = await lendingProgram.methods
+      .withdraw(Array.from(usdcBalance), Array.from(withdrawAmount))
+      .accounts({
+        caller: wallet.publicKey,
+      })
+      .rpc();
+
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, { 
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+
+    if (!txInfo) {
+      throw new Error(`Transaction ${tx} not found`);
+    }
+
+    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
+    let eventFound = false;
+    const allEvents: string[] = [];
     
     for (const event of eventParser.parseLogs(txInfo.meta?.logMessages ?? [])) {
       allEvents.push(event.name);
@@ -631,17 +3759,1134 @@ describe("lending-demo", () => {
         const subResult = safeGetUint8Array(event.data, "sub_result_handle");
         const finalResult = safeGetUint8Array(event.data, "final_handle");
 
-        expect(Buffer.from(geResult)).to.deep.equal(Buffer.from(expectedGeHandle), "GE Handle Mismatch");
-        expect(Buffer.from(subResult)).to.deep.equal(Buffer.from(expectedSubHandle), "SUB Handle Mismatch");
-        expect(Buffer.from(finalResult)).to.deep.equal(Buffer.from(expectedFinalHandle), "SELECT (Final) Handle Mismatch");
-        break;
+        expect(Buffer.from(geResult)).to.deep.equal(Buffer.from(expectedGeHandle), "GE Handle Mismatch");
+        expect(Buffer.from(subResult)).to.deep.equal(Buffer.from(expectedSubHandle), "SUB Handle Mismatch");
+        expect(Buffer.from(finalResult)).to.deep.equal(Buffer.from(expectedFinalHandle), "SELECT (Final) Handle Mismatch");
+        break;
+      }
+    }
+    
+    if (!eventFound) {
+      void console.log(`Available events: ${allEvents.join(", ")}`);
+    }
+    
+    expect(eventFound, `WithdrawCompleted event not found. Available events: ${allEvents.join(", ")}`).to.be.true;
+  });
+
+  it("Repay: GE -> SUB -> SELECT clamps debt at zero", async () => {
+    const debt = new Uint8Array(32).fill(60);
+    const repayAmount = new Uint8Array(32).fill(80);
+    // 과상환 시 debt 는 host 의 공용 0 handle 로 clamp
+    const zeroHandle = constantHandle(0, hostProgram.programId);
+
+    const expectedGeHandle = deriveBinaryHandle(
+      LENDING_BIN_OPS.Ge,
+      debt,
+      repayAmount,
+      hostProgram.programId
+    );
+    const expectedSubHandle = deriveBinaryHandle(
+      LENDING_BIN_OPS.Sub,
+      debt,
+      repayAmount,
+      hostProgram.programId
+    );
+    const expectedFinalHandle = deriveTernaryHandle(
+      LENDING_TER_OPS.Select,
+      expectedGeHandle,
+      expectedSubHandle,
+      zeroHandle,
+      hostProgram.programId
+    );
+
+    const tx = await lendingProgram.methods
+      .repay(Array.from(debt), Array.from(repayAmount))
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
+    const event = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].find(
+      (e) => e.name === "RepayCompleted" || e.name === "repayCompleted"
+    );
+    expect(event, "RepayCompleted event not found").to.not.be.undefined;
+
+    expect(Buffer.from(safeGetUint8Array(event!.data, "ge_result_handle"))).to.deep.equal(
+      Buffer.from(expectedGeHandle)
+    );
+    expect(Buffer.from(safeGetUint8Array(event!.data, "sub_result_handle"))).to.deep.equal(
+      Buffer.from(expectedSubHandle)
+    );
+    expect(Buffer.from(safeGetUint8Array(event!.data, "final_handle"))).to.deep.equal(
+      Buffer.from(expectedFinalHandle),
+      "SELECT(GE, SUB, 0) 결과와 다릅니다"
+    );
+  });
+
+  it("Deposit with referrer: encrypted total chains ADD, each referred deposit counted, guards", async () => {
+    const referrer = Keypair.generate().publicKey;
+    const [referralStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from("referral"), referrer.toBuffer()],
+      lendingProgram.programId
+    );
+    const [referee] = PublicKey.findProgramAddressSync(
+      [Buffer.from("referral"), referrer.toBuffer(), wallet.publicKey.toBuffer()],
+      lendingProgram.programId
+    );
+    await lendingProgram.methods
+      .initReferralStats()
+      .accounts({ referrer, payer: wallet.publicKey })
+      .rpc();
+    const solBalance = new Uint8Array(32).fill(20);
+    const amounts = [new Uint8Array(32).fill(21), new Uint8Array(32).fill(22)];
+    const referredDeposit = (caller: PublicKey, amount: Uint8Array, refereeAccount: PublicKey | null) =>
+      lendingProgram.methods
+        .deposit(Array.from(solBalance), Array.from(amount), referrer)
+        .accounts({ caller, referralStats, referee: refereeAccount })
+        .rpc();
+    const rejectedWith = async (attempt: () => Promise<unknown>, name: string) => {
+      try {
+        await attempt();
+      } catch (err) {
+        return String(err).includes(name);
       }
+      return false;
+    };
+
+    expect(
+      await rejectedWith(() => referredDeposit(wallet.publicKey, amounts[0], null), "NotReferred"),
+      "join_referral 없이 추천 deposit 이 허용되었습니다"
+    ).to.be.true;
+
+    await lendingProgram.methods
+      .joinReferral()
+      .accounts({ referralStats, depositor: wallet.publicKey })
+      .rpc();
+    // join 만으로는 count 가 오르지 않는다
+    expect(
+      (await lendingProgram.account.referralStats.fetch(referralStats)).count.toNumber(),
+      "join 이 deposit 으로 count 되었습니다"
+    ).to.equal(0);
+    // 같은 depositor 는 두 번 join 할 수 없음 (Referee PDA 가 이미 존재).
+    // compute budget ix 는 첫 tx 와 signature 가 겹치지 않게 하기 위함
+    expect(
+      await rejectedWith(
+        () =>
+          lendingProgram.methods
+            .joinReferral()
+            .accounts({ referralStats, depositor: wallet.publicKey })
+            .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+            .rpc(),
+        "already in use"
+      ),
+      "같은 depositor 가 두 번 join 되었습니다"
+    ).to.be.true;
+
+    // 누적 시작값은 host 의 공용 0 handle
+    let expectedTotal = constantHandle(0, hostProgram.programId);
+    for (const amount of amounts) {
+      await referredDeposit(wallet.publicKey, amount, referee);
+      expectedTotal = deriveBinaryHandle(
+        LENDING_BIN_OPS.Add,
+        expectedTotal,
+        amount,
+        hostProgram.programId
+      );
     }
-    
-    if (!eventFound) {
-      void console.log(`Available events: ${allEvents.join(", ")}`);
+
+    const stats = await lendingProgram.account.referralStats.fetch(referralStats);
+    expect(stats.count.toNumber(), "추천 deposit 두 번이 count 되지 않았습니다").to.equal(2);
+    expect(Buffer.from(stats.totalHandle)).to.deep.equal(
+      Buffer.from(expectedTotal),
+      "ADD(ADD(0, a1), a2) 누적 핸들과 다릅니다"
+    );
+
+    // 서명 없이 남의 이름으로 추천 금액을 쌓을 수 없음
+    expect(
+      await rejectedWith(
+        () => referredDeposit(Keypair.generate().publicKey, amounts[0], null),
+        "ReferralRequiresSigner"
+      ),
+      "서명 없는 추천 deposit 이 허용되었습니다"
+    ).to.be.true;
+
+    let rejected = false;
+    try {
+      await lendingProgram.methods
+        .deposit(Array.from(solBalance), Array.from(amounts[0]), wallet.publicKey)
+        .accounts({ caller: wallet.publicKey, referralStats })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("SelfReferral");
     }
-    
-    expect(eventFound, `WithdrawCompleted event not found. Available events: ${allEvents.join(", ")}`).to.be.true;
+    expect(rejected, "자기 추천이 거부되지 않았습니다").to.be.true;
+  });
+
+  it("KYC mode: withdraw folds EQ(attestation, expected) into the SELECT condition", async () => {
+    const usdcBalance = new Uint8Array(32).fill(110);
+    const withdrawAmount = new Uint8Array(32).fill(40);
+    const attestationHandle = new Uint8Array(32).fill(0x4c);
+    const [attestation] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attestation"), wallet.publicKey.toBuffer()],
+      lendingProgram.programId
+    );
+    const withdraw = (withAttestation: boolean) =>
+      lendingProgram.methods
+        .withdraw(Array.from(usdcBalance), Array.from(withdrawAmount))
+        .accounts({ caller: wallet.publicKey, attestation: withAttestation ? attestation : null })
+        .rpc();
+
+    await lendingProgram.methods.setKycRequired(true).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      let rejected = false;
+      try {
+        await withdraw(false);
+      } catch (err) {
+        rejected = String(err).includes("MissingAttestation");
+      }
+      expect(rejected, "attestation 없는 withdraw 가 거부되지 않았습니다").to.be.true;
+
+      await lendingProgram.methods
+        .attestUser(wallet.publicKey, Array.from(attestationHandle))
+        .accounts({ attestor: wallet.publicKey })
+        .rpc();
+
+      const geHandle = deriveBinaryHandle(
+        LENDING_BIN_OPS.Ge,
+        usdcBalance,
+        withdrawAmount,
+        hostProgram.programId
+      );
+      const subHandle = deriveBinaryHandle(
+        LENDING_BIN_OPS.Sub,
+        usdcBalance,
+        withdrawAmount,
+        hostProgram.programId
+      );
+      const eqHandle = deriveBinaryHandle(
+        LENDING_BIN_OPS.Eq,
+        attestationHandle,
+        EXPECTED_ATTESTATION,
+        hostProgram.programId
+      );
+      const andHandle = deriveBinaryHandle(
+        LENDING_BIN_OPS.And,
+        geHandle,
+        eqHandle,
+        hostProgram.programId
+      );
+      const expectedFinalHandle = deriveTernaryHandle(
+        LENDING_TER_OPS.Select,
+        andHandle,
+        subHandle,
+        usdcBalance,
+        hostProgram.programId
+      );
+
+      const tx = await withdraw(true);
+      await provider.connection.confirmTransaction(tx, "confirmed");
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
+      const event = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].find(
+        (e) => e.name === "WithdrawCompleted" || e.name === "withdrawCompleted"
+      );
+      expect(event, "WithdrawCompleted event not found").to.not.be.undefined;
+      expect(Buffer.from(safeGetUint8Array(event!.data, "kyc_result_handle"))).to.deep.equal(
+        Buffer.from(eqHandle)
+      );
+      expect(Buffer.from(safeGetUint8Array(event!.data, "final_handle"))).to.deep.equal(
+        Buffer.from(expectedFinalHandle),
+        "SELECT(AND(GE, EQ), SUB, balance) 결과와 다릅니다"
+      );
+    } finally {
+      await lendingProgram.methods
+        .setKycRequired(false)
+        .accounts({ admin: wallet.publicKey })
+        .rpc();
+    }
+
+    // flag off: attestation 없이도 기존 GE -> SUB -> SELECT 경로
+    await withdraw(false);
+  });
+
+  it("Pause mask: paused withdraw fails while repay still succeeds", async () => {
+    const balance = new Uint8Array(32).fill(120);
+    const amount = new Uint8Array(32).fill(50);
+    const PAUSE_WITHDRAW = 1 << 1;
+
+    await lendingProgram.methods
+      .setPausedMask(PAUSE_WITHDRAW)
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+    try {
+      let rejected = false;
+      try {
+        await lendingProgram.methods
+          .withdraw(Array.from(balance), Array.from(amount))
+          .accounts({ caller: wallet.publicKey, attestation: null })
+          .rpc();
+      } catch (err) {
+        rejected = String(err).includes("InstructionPaused");
+      }
+      expect(rejected, "pause 된 withdraw 가 거부되지 않았습니다").to.be.true;
+
+      await lendingProgram.methods
+        .repay(Array.from(balance), Array.from(amount))
+        .accounts({ caller: wallet.publicKey })
+        .rpc();
+    } finally {
+      await lendingProgram.methods.setPausedMask(0).accounts({ admin: wallet.publicKey }).rpc();
+    }
+
+    await lendingProgram.methods
+      .withdraw(Array.from(balance), Array.from(amount))
+      .accounts({ caller: wallet.publicKey, attestation: null })
+      .rpc();
+  });
+
+  it("Position migration: version bump blocks ops until handles are migrated", async () => {
+    const amount = new Uint8Array(32).fill(132);
+    const newBalance = new Uint8Array(32).fill(133);
+    const newDebt = new Uint8Array(32).fill(134);
+    const proof = new Uint8Array(32).fill(135);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), wallet.publicKey.toBuffer()],
+      lendingProgram.programId
+    );
+    const depositToPosition = () =>
+      lendingProgram.methods
+        .depositToPosition(Array.from(amount))
+        .accounts({ owner: wallet.publicKey })
+        .rpc();
+    const migrate = () =>
+      lendingProgram.methods
+        .migratePosition(Array.from(newBalance), Array.from(newDebt), Array.from(proof))
+        .accounts({ position, migrationAuthority: wallet.publicKey })
+        .rpc();
+
+    await lendingProgram.methods.openPosition().accounts({ owner: wallet.publicKey }).rpc();
+    const opened = await lendingProgram.account.userPosition.fetch(position);
+    expect(Buffer.from(opened.balanceHandle), "position 이 공용 0 handle 로 시작하지 않았습니다").to.deep.equal(
+      Buffer.from(constantHandle(0, hostProgram.programId))
+    );
+
+    await lendingProgram.methods
+      .setDerivationVersion(2)
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+    // 새 version 은 host 의 현재 salt 로 derive
+    const [hostConfig] = PublicKey.findProgramAddressSync([Buffer.from("config")], hostProgram.programId);
+    const demoConfig = await lendingProgram.account.demoConfig.fetch(
+      PublicKey.findProgramAddressSync([Buffer.from("demo_config")], lendingProgram.programId)[0]
+    );
+    expect(demoConfig.domainSalt, "version 의 salt 가 host salt 와 다릅니다").to.deep.equal(
+      (await hostProgram.account.config.fetch(hostConfig)).domainSalt
+    );
+
+    let mismatch = false;
+    try {
+      await depositToPosition();
+    } catch (err) {
+      mismatch = String(err).includes("DerivationVersionMismatch");
+    }
+    expect(mismatch, "이전 version position 연산이 거부되지 않았습니다").to.be.true;
+
+    await migrate();
+    await depositToPosition();
+    const stored = await lendingProgram.account.userPosition.fetch(position);
+    expect(stored.derivationVersion).to.equal(2);
+    expect(Buffer.from(stored.balanceHandle)).to.deep.equal(
+      Buffer.from(deriveBinaryHandle(LENDING_BIN_OPS.Add, newBalance, amount, hostProgram.programId)),
+      "deposit 이 migration 된 balance handle 을 사용하지 않았습니다"
+    );
+
+    let alreadyMigrated = false;
+    try {
+      await migrate();
+    } catch (err) {
+      alreadyMigrated = String(err).includes("AlreadyMigrated");
+    }
+    expect(alreadyMigrated, "같은 version 으로 재-migration 되었습니다").to.be.true;
+  });
+
+  it("Compute LTV: emitted handle is GE(debt, SMulL(collateral, factor)) over the position", async () => {
+    const ltvFactor = new Uint8Array(32).fill(205);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), wallet.publicKey.toBuffer()],
+      lendingProgram.programId
+    );
+    const stored = await lendingProgram.account.userPosition.fetch(position);
+    const collateral = Uint8Array.from(stored.balanceHandle);
+    const debt = Uint8Array.from(stored.debtHandle);
+    const scaled = deriveBinaryHandle(
+      LENDING_BIN_OPS.SMulL,
+      collateral,
+      ltvFactor,
+      hostProgram.programId
+    );
+    const expected = deriveBinaryHandle(LENDING_BIN_OPS.Ge, debt, scaled, hostProgram.programId);
+
+    const computeLtv = lendingProgram.methods
+      .computeLtv()
+      .accounts({ caller: wallet.publicKey, position });
+
+    // admin 이 ltv_factor 를 설정하기 전에는 거부
+    let notSet = false;
+    try {
+      await computeLtv.rpc();
+    } catch (err) {
+      notSet = String(err).includes("RiskFactorNotSet");
+    }
+    expect(notSet, "ltv_factor 없이 LTV 가 계산되었습니다").to.be.true;
+    await lendingProgram.methods
+      .setLtvFactor(Array.from(ltvFactor))
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+
+    const tx = await computeLtv.rpc();
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
+    const event = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].find(
+      (e) => e.name === "LtvComputed" || e.name === "ltvComputed"
+    );
+    expect(event, "LtvComputed event not found").to.not.be.undefined;
+    expect(Buffer.from(safeGetUint8Array(event!.data, "scaled_collateral_handle"))).to.deep.equal(
+      Buffer.from(scaled),
+      "scaled collateral handle 이 SMulL(collateral, factor) 와 다릅니다"
+    );
+    expect(Buffer.from(safeGetUint8Array(event!.data, "ltv_handle"))).to.deep.equal(
+      Buffer.from(expected),
+      "LTV handle 이 저장된 balance/debt 로 계산한 값과 다릅니다"
+    );
+
+    const returned = await computeLtv.view();
+    expect(Buffer.from(returned), "반환된 LTV handle 이 event 와 다릅니다").to.deep.equal(
+      Buffer.from(expected)
+    );
+  });
+
+  it("Liquidation signal: event carries the GE health comparison handle", async () => {
+    const liquidationFactor = new Uint8Array(32).fill(207);
+    const [position] = PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), wallet.publicKey.toBuffer()],
+      lendingProgram.programId
+    );
+    const stored = await lendingProgram.account.userPosition.fetch(position);
+    const scaled = deriveBinaryHandle(
+      LENDING_BIN_OPS.SMulL,
+      Uint8Array.from(stored.balanceHandle),
+      liquidationFactor,
+      hostProgram.programId
+    );
+    const health = deriveBinaryHandle(
+      LENDING_BIN_OPS.Ge,
+      Uint8Array.from(stored.debtHandle),
+      scaled,
+      hostProgram.programId
+    );
+
+    const checkLiquidatable = (keeper: Keypair) =>
+      lendingProgram.methods
+        .checkLiquidatable()
+        .accounts({ liquidationKeeper: keeper.publicKey, position })
+        .signers([keeper])
+        .rpc();
+    const keeper = Keypair.generate();
+    await lendingProgram.methods
+      .setLiquidationKeeper(keeper.publicKey)
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+
+    // admin 이 liquidation_factor 를 설정하기 전에는 거부
+    let notSet = false;
+    try {
+      await checkLiquidatable(keeper);
+    } catch (err) {
+      notSet = String(err).includes("RiskFactorNotSet");
+    }
+    expect(notSet, "liquidation_factor 없이 청산 신호가 나갔습니다").to.be.true;
+    await lendingProgram.methods
+      .setLiquidationFactor(Array.from(liquidationFactor))
+      .accounts({ admin: wallet.publicKey })
+      .rpc();
+
+    // keeper 가 아닌 서명자는 신호를 낼 수 없다
+    let notKeeper = false;
+    try {
+      await checkLiquidatable(Keypair.generate());
+    } catch (err) {
+      notKeeper = String(err).includes("ConstraintHasOne");
+    }
+    expect(notKeeper, "keeper 가 아닌 주소가 청산 신호를 냈습니다").to.be.true;
+
+    const tx = await checkLiquidatable(keeper);
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
+    const signal = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].find(
+      (e) => e.name === "LiquidationSignal" || e.name === "liquidationSignal"
+    );
+    expect(signal, "LiquidationSignal event not found").to.not.be.undefined;
+    expect((signal!.data.owner as PublicKey).toBase58()).to.equal(wallet.publicKey.toBase58());
+    expect(Buffer.from(safeGetUint8Array(signal!.data, "health_handle"))).to.deep.equal(
+      Buffer.from(health),
+      "health handle 이 GE(debt, SMulL(collateral, factor)) 와 다릅니다"
+    );
+  });
+
+  it("Pipeline tracing: every withdraw event, op requests included, shares one trace id", async () => {
+    const balance = new Uint8Array(32).fill(234);
+    const amount = new Uint8Array(32).fill(235);
+
+    const tx = await lendingProgram.methods
+      .withdraw(Array.from(balance), Array.from(amount))
+      .accounts({ caller: wallet.publicKey, attestation: null })
+      .rpc();
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const logs = txInfo?.meta?.logMessages ?? [];
+    const demoEvents = [
+      ...new EventParser(lendingProgram.programId, lendingProgram.coder).parseLogs(logs),
+    ];
+    const started = demoEvents.find(
+      (e) => e.name === "PipelineStarted" || e.name === "pipelineStarted"
+    );
+    const completed = demoEvents.find(
+      (e) => e.name === "WithdrawCompleted" || e.name === "withdrawCompleted"
+    );
+    expect(started, "PipelineStarted event not found").to.not.be.undefined;
+    expect(completed, "WithdrawCompleted event not found").to.not.be.undefined;
+    expect(demoEvents[0].name.toLowerCase()).to.equal(
+      "pipelinestarted",
+      "PipelineStarted 가 첫 이벤트가 아닙니다"
+    );
+
+    const traceId = Buffer.from(safeGetUint8Array(started!.data, "trace_id"));
+    expect(traceId.length).to.equal(16);
+    for (const e of demoEvents) {
+      expect(Buffer.from(safeGetUint8Array(e.data, "trace_id"))).to.deep.equal(
+        traceId,
+        `${e.name} 의 trace_id 가 PipelineStarted 와 다릅니다`
+      );
+    }
+
+    // demo 의 연산 요청은 host 로의 CPI 이며, host 가 요청마다 event 를 남긴다.
+    // 요청마다 뒤따르는 PipelineOp 가 trace_id 와 host 결과 handle 을 잇는다
+    const opRequests = [
+      ...new EventParser(hostProgram.programId, hostProgram.coder).parseLogs(logs),
+    ].filter((e) => /^fhe16(binary|ternary)oprequested$/.test(e.name.toLowerCase()));
+    const pipelineOps = demoEvents.filter((e) => e.name.toLowerCase() === "pipelineop");
+    const opCount = started!.data.op_count ?? started!.data.opCount;
+    expect(opRequests.length).to.equal(opCount, "op_count 와 host 요청 event 수가 다릅니다");
+    expect(pipelineOps.length).to.equal(opCount, "op 요청마다 PipelineOp 가 없습니다");
+    pipelineOps.forEach((op, i) => {
+      expect(Buffer.from(safeGetUint8Array(op.data, "result_handle"))).to.deep.equal(
+        Buffer.from(safeGetUint8Array(opRequests[i].data, "result_handle")),
+        `${i} 번째 PipelineOp 가 host 요청 event 와 다릅니다`
+      );
+    });
+    expect(
+      Buffer.from(safeGetUint8Array(pipelineOps[opCount - 1].data, "result_handle"))
+    ).to.deep.equal(
+      Buffer.from(safeGetUint8Array(completed!.data, "final_handle")),
+      "마지막 PipelineOp 가 final handle 이 아닙니다"
+    );
+  });
+
+  it("Origin: host events from the demo's CPIs name lending-demo as origin_program", async () => {
+    const balance = new Uint8Array(32).fill(236);
+    const amount = new Uint8Array(32).fill(237);
+    const deposit = (instructions: PublicKey | null) =>
+      lendingProgram.methods
+        .deposit(Array.from(balance), Array.from(amount), null)
+        .accounts({ caller: wallet.publicKey, instructions })
+        .rpc();
+    const originOf = async (tx: string) => {
+      const event = await getEvent(hostProgram, provider, tx, "Fhe16BinaryOpRequested");
+      return ((event.data.originProgram ?? event.data.origin_program) as PublicKey).toBase58();
+    };
+
+    // instructions sysvar 를 넘기면 host 는 top-level program (demo) 을 origin 으로 기록
+    expect(
+      await originOf(await deposit(SYSVAR_INSTRUCTIONS_PUBKEY)),
+      "CPI 요청의 origin_program 이 lending-demo 가 아닙니다"
+    ).to.equal(lendingProgram.programId.toBase58());
+    // sysvar 없는 CPI 는 origin 을 알 수 없어 default
+    expect(await originOf(await deposit(null)), "sysvar 없는 CPI 의 origin_program").to.equal(
+      PublicKey.default.toBase58()
+    );
+  });
+
+  it("Handle derivation: lending-demo matches host-programs for the same ops", async () => {
+    const balance = new Uint8Array(32).fill(140);
+    const amount = new Uint8Array(32).fill(141);
+    const zeroHandle = constantHandle(0, hostProgram.programId);
+
+    const repayTx = await lendingProgram.methods
+      .repay(Array.from(balance), Array.from(amount))
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    await provider.connection.confirmTransaction(repayTx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(repayTx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
+    const repayEvent = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].find(
+      (e) => e.name === "RepayCompleted" || e.name === "repayCompleted"
+    );
+    expect(repayEvent, "RepayCompleted event not found").to.not.be.undefined;
+    const demoGe = safeGetUint8Array(repayEvent!.data, "ge_result_handle");
+    const demoSub = safeGetUint8Array(repayEvent!.data, "sub_result_handle");
+    const demoFinal = safeGetUint8Array(repayEvent!.data, "final_handle");
+
+    // 같은 op 을 host-programs 에 직접 요청
+    const geTx = await hostProgram.methods
+      .requestBinaryOp({ ge: {} }, Array.from(balance), Array.from(amount), null, false)
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    const hostGe = await getEvent(hostProgram, provider, geTx, "Fhe16BinaryOpRequested");
+    expect(Buffer.from(safeGetUint8Array(hostGe.data, "result_handle"))).to.deep.equal(
+      Buffer.from(demoGe),
+      "GE: lending-demo 와 host-programs 의 handle 이 다릅니다"
+    );
+
+    const selectTx = await hostProgram.methods
+      .requestTernaryOp(
+        { select: {} },
+        Array.from(demoGe),
+        Array.from(demoSub),
+        Array.from(zeroHandle),
+        null,
+        false
+      )
+      .accounts({ caller: wallet.publicKey })
+      .rpc();
+    const hostSelect = await getEvent(hostProgram, provider, selectTx, "Fhe16TernaryOpRequested");
+    expect(Buffer.from(safeGetUint8Array(hostSelect.data, "result_handle"))).to.deep.equal(
+      Buffer.from(demoFinal),
+      "SELECT: lending-demo 와 host-programs 의 handle 이 다릅니다"
+    );
+  });
+
+  it("Conformance: demo-planned handles agree with host and client derivation", async () => {
+    const caller = { caller: wallet.publicKey };
+    const eventParser = new EventParser(lendingProgram.programId, lendingProgram.coder);
+    const demoEvent = async (tx: string, name: string) => {
+      await provider.connection.confirmTransaction(tx, "confirmed");
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const event = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].find(
+        (e) => e.name.toLowerCase() === name.toLowerCase()
+      );
+      expect(event, `${name} event not found`).to.not.be.undefined;
+      return event!.data;
+    };
+    const hostResult = async (tx: string, name: string) =>
+      Buffer.from(
+        safeGetUint8Array((await getEvent(hostProgram, provider, tx, name)).data, "result_handle")
+      );
+    // 세 layer 가 모두 같아야 하며, 다르면 어느 layer 가 어긋났는지 출력한다
+    const agree = (label: string, demo: Uint8Array, host: Buffer, client: Uint8Array) => {
+      expect(host, `${label}: host program 이 client 계산과 다릅니다`).to.deep.equal(
+        Buffer.from(client)
+      );
+      expect(Buffer.from(demo), `${label}: lending-demo 가 client 계산과 다릅니다`).to.deep.equal(
+        Buffer.from(client)
+      );
+    };
+
+    // demo 가 쓰는 op (ADD, SUB, GE, SELECT) x operand fixture, 기본 derivation option
+    const fixtures = [
+      [new Uint8Array(32).fill(120), new Uint8Array(32).fill(121)],
+      [new Uint8Array(32).fill(122), new Uint8Array(32).fill(122)],
+    ];
+    for (const [balance, amount] of fixtures) {
+      const deposit = await demoEvent(
+        await lendingProgram.methods
+          .deposit(Array.from(balance), Array.from(amount), null)
+          .accounts(caller)
+          .rpc(),
+        "DepositCompleted"
+      );
+      const addHost = await hostResult(
+        await hostProgram.methods
+          .requestBinaryOp({ add: {} }, Array.from(balance), Array.from(amount), null, false)
+          .accounts(caller)
+          .rpc(),
+        "Fhe16BinaryOpRequested"
+      );
+      agree(
+        "ADD",
+        safeGetUint8Array(deposit, "final_handle"),
+        addHost,
+        deriveBinaryHandle(LENDING_BIN_OPS.Add, balance, amount, hostProgram.programId)
+      );
+
+      const withdraw = await demoEvent(
+        await lendingProgram.methods
+          .withdraw(Array.from(balance), Array.from(amount))
+          .accounts({ caller: wallet.publicKey, attestation: null })
+          .rpc(),
+        "WithdrawCompleted"
+      );
+      const geHost = await hostResult(
+        await hostProgram.methods
+          .requestBinaryOp({ ge: {} }, Array.from(balance), Array.from(amount), null, false)
+          .accounts(caller)
+          .rpc(),
+        "Fhe16BinaryOpRequested"
+      );
+      const subHost = await hostResult(
+        await hostProgram.methods
+          .requestBinaryOp({ sub: {} }, Array.from(balance), Array.from(amount), null, false)
+          .accounts(caller)
+          .rpc(),
+        "Fhe16BinaryOpRequested"
+      );
+      const selectHost = await hostResult(
+        await hostProgram.methods
+          .requestTernaryOp(
+            { select: {} },
+            Array.from(geHost),
+            Array.from(subHost),
+            Array.from(balance),
+            null,
+            false
+          )
+          .accounts(caller)
+          .rpc(),
+        "Fhe16TernaryOpRequested"
+      );
+      const geClient = deriveBinaryHandle(
+        LENDING_BIN_OPS.Ge,
+        balance,
+        amount,
+        hostProgram.programId
+      );
+      const subClient = deriveBinaryHandle(
+        LENDING_BIN_OPS.Sub,
+        balance,
+        amount,
+        hostProgram.programId
+      );
+      agree("GE", safeGetUint8Array(withdraw, "ge_result_handle"), geHost, geClient);
+      agree("SUB", safeGetUint8Array(withdraw, "sub_result_handle"), subHost, subClient);
+      agree(
+        "SELECT",
+        safeGetUint8Array(withdraw, "final_handle"),
+        selectHost,
+        deriveTernaryHandle(
+          LENDING_TER_OPS.Select,
+          geClient,
+          subClient,
+          balance,
+          hostProgram.programId
+        )
+      );
+    }
+  });
+
+  it("Wrong host program account fails with WrongHostProgram", async () => {
+    const balance = new Uint8Array(32).fill(125);
+    const amount = new Uint8Array(32).fill(126);
+
+    let rejected = false;
+    try {
+      await lendingProgram.methods
+        .withdraw(Array.from(balance), Array.from(amount))
+        .accountsPartial({
+          caller: wallet.publicKey,
+          hostPrograms: Keypair.generate().publicKey,
+          attestation: null,
+        })
+        .rpc();
+    } catch (err) {
+      rejected = String(err).includes("WrongHostProgram");
+    }
+    expect(rejected, "잘못된 host program 이 WrongHostProgram 으로 거부되지 않았습니다").to.be.true;
+  });
+});
+
+describe("voting-demo", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const votingProgram = anchor.workspace.VotingDemo as Program<VotingDemo>;
+  const hostProgram = anchor.workspace.HostPrograms as Program<HostPrograms>;
+  const wallet = provider.wallet as anchor.Wallet;
+
+  const [hostConfig] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    hostProgram.programId
+  );
+  const [hostWorkCursor] = PublicKey.findProgramAddressSync(
+    [Buffer.from("work_cursor")],
+    hostProgram.programId
+  );
+  const addOp = enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { add: {} });
+
+  // SELECT(AND(GE(ballot, 0), LE(ballot, 1)), ballot, 0): 0 / 1 밖의 ballot 은 0 으로 센다
+  const counted = (ballot: Uint8Array) => {
+    const pid = hostProgram.programId;
+    const [zero, one] = [0, 1].map((v) => constantHandle(v, pid));
+    const op = (name: string) => enumDiscriminantFromIdl(idl, "Fhe16BinaryOp", { [name]: {} });
+    const ge = deriveBinaryHandle(op("ge"), ballot, zero, pid);
+    const le = deriveBinaryHandle(op("le"), ballot, one, pid);
+    const inRange = deriveBinaryHandle(op("and"), ge, le, pid);
+    const selectOp = enumDiscriminantFromIdl(idl, "Fhe16TernaryOp", { select: {} });
+    return deriveTernaryHandle(selectOp, inRange, ballot, zero, pid);
+  };
+
+  // wallet 에서 lamports 를 보내 새 투표자를 만든다
+  const fundedVoter = async () => {
+    const voter = Keypair.generate();
+    const tx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: wallet.publicKey,
+        toPubkey: voter.publicKey,
+        lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+      })
+    );
+    await provider.sendAndConfirm(tx);
+    return voter;
+  };
+
+  const castVote = (proposal: PublicKey, voter: Keypair | null, ballot: Uint8Array) => {
+    const builder = votingProgram.methods
+      .castVote(Array.from(ballot), null)
+      .accounts({
+        proposal,
+        voter: voter ? voter.publicKey : wallet.publicKey,
+        hostConfig,
+        hostWorkCursor,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })]);
+    return voter ? builder.signers([voter]).rpc() : builder.rpc();
+  };
+
+  it("Vote, tally and finalize after the deadline; double votes are rejected", async () => {
+    const tag = new Uint8Array(16).fill(185);
+    const [proposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), wallet.publicKey.toBuffer(), Buffer.from(tag)],
+      votingProgram.programId
+    );
+    const deadline = Math.floor(Date.now() / 1000) + 5;
+    await votingProgram.methods
+      .createProposal(Array.from(tag), new anchor.BN(deadline))
+      .accounts({ creator: wallet.publicKey })
+      .rpc();
+
+    const yes = new Uint8Array(32).fill(186);
+    const no = new Uint8Array(32).fill(187);
+    const second = await fundedVoter();
+    await castVote(proposal, null, yes);
+    await castVote(proposal, second, no);
+
+    // ballot PDA 가 이미 있으므로 init 단계에서 실패
+    let rejected = false;
+    try {
+      await castVote(proposal, null, no);
+    } catch (err) {
+      rejected = String(err).includes("already in use");
+    }
+    expect(rejected, "같은 투표자의 두 번째 ballot 이 받아들여졌습니다").to.be.true;
+
+    // tally = ADD(ADD(0, counted(yes)), counted(no)): host 가 돌려준 handle 과 같은 chain
+    const zero = constantHandle(0, hostProgram.programId);
+    const afterYes = deriveBinaryHandle(addOp, zero, counted(yes), hostProgram.programId);
+    const expectedTally = deriveBinaryHandle(addOp, afterYes, counted(no), hostProgram.programId);
+    const stored = await votingProgram.account.proposal.fetch(proposal);
+    expect(Buffer.from(stored.tallyHandle)).to.deep.equal(Buffer.from(expectedTally));
+    expect(stored.voteCount.toNumber()).to.equal(2);
+
+    let stillOpen = false;
+    try {
+      await votingProgram.methods.finalizeProposal().accounts({ proposal }).rpc();
+    } catch (err) {
+      stillOpen = String(err).includes("VotingStillOpen");
+    }
+    expect(stillOpen, "마감 전에 finalize 되었습니다").to.be.true;
+
+    await new Promise((resolve) => setTimeout(resolve, 7000));
+
+    let closed = false;
+    try {
+      await castVote(proposal, await fundedVoter(), yes);
+    } catch (err) {
+      closed = String(err).includes("VotingClosed");
+    }
+    expect(closed, "마감 후 투표가 받아들여졌습니다").to.be.true;
+
+    const tx = await votingProgram.methods.finalizeProposal().accounts({ proposal }).rpc();
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const eventParser = new EventParser(votingProgram.programId, votingProgram.coder);
+    const event = [...eventParser.parseLogs(txInfo?.meta?.logMessages ?? [])].find(
+      (e) => e.name === "TallyFinalized" || e.name === "tallyFinalized"
+    );
+    expect(event, "TallyFinalized event 가 없습니다").to.not.be.undefined;
+    expect(Buffer.from(safeGetUint8Array(event!.data, "tally_handle"))).to.deep.equal(
+      Buffer.from(expectedTally)
+    );
+    const data = event!.data as any;
+    expect((data.voteCount ?? data.vote_count).toNumber()).to.equal(2);
+  });
+
+  it("Registration origin: CPI through the demo vs a direct user call", async () => {
+    const tag = new Uint8Array(16).fill(238);
+    const [proposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), wallet.publicKey.toBuffer(), Buffer.from(tag)],
+      votingProgram.programId
+    );
+    await votingProgram.methods
+      .createProposal(Array.from(tag), new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+      .accounts({ creator: wallet.publicKey })
+      .rpc();
+    const originOf = (event: { data: Record<string, unknown> }) =>
+      ((event.data.originProgram ?? event.data.origin_program) as PublicKey).toBase58();
+
+    // demo 를 거친 등록 → origin 은 demo program (Cpi)
+    const ballot = new Uint8Array(32).fill(239);
+    const voteTx = await votingProgram.methods
+      .castVote(Array.from(ballot), null)
+      .accounts({
+        proposal,
+        voter: wallet.publicKey,
+        hostConfig,
+        hostWorkCursor,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+      .rpc();
+    const registered = await getEvent(hostProgram, provider, voteTx, "InputHandleRegistered");
+    expect(originOf(registered), "CPI 등록의 origin").to.equal(votingProgram.programId.toBase58());
+    const tally = await getEvent(hostProgram, provider, voteTx, "Fhe16BinaryOpRequested");
+    expect(originOf(tally), "CPI 요청의 origin").to.equal(votingProgram.programId.toBase58());
+
+    // 직접 등록 → origin 은 host 자신 (User)
+    const directTx = await hostProgram.methods
+      .registerInputHandle(
+        Array.from(new Uint8Array(32).fill(240)),
+        Array.from(new Uint8Array(32))
+      )
+      .accounts({ caller: wallet.publicKey, instructions: SYSVAR_INSTRUCTIONS_PUBKEY })
+      .rpc();
+    const direct = await getEvent(hostProgram, provider, directTx, "InputHandleRegistered");
+    expect(originOf(direct), "직접 등록의 origin").to.equal(hostProgram.programId.toBase58());
+  });
+
+  it("Registration origin: one transaction mixing a direct call and the demo's CPIs", async () => {
+    const tag = new Uint8Array(16).fill(248);
+    const [proposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), wallet.publicKey.toBuffer(), Buffer.from(tag)],
+      votingProgram.programId
+    );
+    await votingProgram.methods
+      .createProposal(Array.from(tag), new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+      .accounts({ creator: wallet.publicKey })
+      .rpc();
+    // Rust 의 RegistrationOrigin::from_origin_program 과 같은 분류
+    const registrationOrigin = (event: { data: Record<string, unknown> }) => {
+      const origin = (event.data.originProgram ?? event.data.origin_program) as PublicKey;
+      return origin.equals(hostProgram.programId)
+        ? "user"
+        : `cpi:${origin.toBase58()}`;
+    };
+
+    const directHandle = new Uint8Array(32).fill(249);
+    const ballot = new Uint8Array(32).fill(250);
+    const direct = await hostProgram.methods
+      .registerInputHandle(Array.from(directHandle), Array.from(new Uint8Array(32)))
+      .accounts({ caller: wallet.publicKey, instructions: SYSVAR_INSTRUCTIONS_PUBKEY })
+      .instruction();
+    const tx = await votingProgram.methods
+      .castVote(Array.from(ballot), null)
+      .accounts({
+        proposal,
+        voter: wallet.publicKey,
+        hostConfig,
+        hostWorkCursor,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 }), direct])
+      .rpc();
+    await provider.connection.confirmTransaction(tx, "confirmed");
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const hostEvents = [
+      ...new EventParser(hostProgram.programId, hostProgram.coder).parseLogs(
+        txInfo?.meta?.logMessages ?? []
+      ),
+    ];
+    const viaDemo = `cpi:${votingProgram.programId.toBase58()}`;
+
+    const registrations = hostEvents
+      .filter((e) => e.name.toLowerCase() === "inputhandleregistered")
+      .map((e) => ({
+        handle: Buffer.from(safeGetUint8Array(e.data, "handle")).toString("hex"),
+        origin: registrationOrigin(e),
+      }));
+    expect(registrations, "등록 event 의 origin").to.deep.equal([
+      { handle: Buffer.from(directHandle).toString("hex"), origin: "user" },
+      { handle: Buffer.from(ballot).toString("hex"), origin: viaDemo },
+    ]);
+    // range check, SELECT, ADD 는 모두 demo 를 거친 요청
+    const requests = hostEvents.filter((e) => /requested$/.test(e.name.toLowerCase()));
+    expect(requests, "demo 요청 event 가 없습니다").to.not.be.empty;
+    for (const e of requests) {
+      expect(registrationOrigin(e), `${e.name} 의 origin`).to.equal(viaDemo);
+    }
+  });
+
+  it("Vote nonce: the voter's host nonce is forwarded and advances", async () => {
+    const tag = new Uint8Array(16).fill(246);
+    const [proposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), wallet.publicKey.toBuffer(), Buffer.from(tag)],
+      votingProgram.programId
+    );
+    await votingProgram.methods
+      .createProposal(Array.from(tag), new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+      .accounts({ creator: wallet.publicKey })
+      .rpc();
+    const voter = await fundedVoter();
+    const [hostNonceAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("caller_nonce"), voter.publicKey.toBuffer()],
+      hostProgram.programId
+    );
+    await hostProgram.methods
+      .initCallerNonce()
+      .accounts({ caller: voter.publicKey, payer: wallet.publicKey })
+      .rpc();
+
+    await votingProgram.methods
+      .castVote(Array.from(new Uint8Array(32).fill(247)), new anchor.BN(7))
+      .accounts({
+        proposal,
+        voter: voter.publicKey,
+        hostConfig,
+        hostWorkCursor,
+        hostNonceAccount,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+      .signers([voter])
+      .rpc();
+    const nonce = await hostProgram.account.callerNonce.fetch(hostNonceAccount);
+    expect(nonce.lastNonce.toNumber(), "vote 의 nonce 가 host 에 전달되지 않았습니다").to.equal(7);
+  });
+
+  it("Vote bond: the voter posts the host registration bond for the ballot", async () => {
+    const bond = 1_000_000;
+    const tag = new Uint8Array(16).fill(243);
+    const [proposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), wallet.publicKey.toBuffer(), Buffer.from(tag)],
+      votingProgram.programId
+    );
+    await votingProgram.methods
+      .createProposal(Array.from(tag), new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+      .accounts({ creator: wallet.publicKey })
+      .rpc();
+    const ballot = new Uint8Array(32).fill(242);
+    const ballotRecord = handleRecordPda(ballot, hostProgram.programId);
+    const admin = { admin: wallet.publicKey };
+
+    await hostProgram.methods
+      .setRegistrationBond(new anchor.BN(bond), new anchor.BN(1_000_000))
+      .accounts(admin)
+      .rpc();
+    try {
+      // record 없이는 host 가 bond 를 받을 곳이 없다
+      let rejected = false;
+      try {
+        await castVote(proposal, null, ballot);
+      } catch (err) {
+        rejected = (String(err) + ((err as { logs?: string[] }).logs ?? []).join("\n")).includes(
+          "MissingBondAccounts"
+        );
+      }
+      expect(rejected, "ballot record 없이 bond 등록이 통과했습니다").to.be.true;
+
+      await votingProgram.methods
+        .castVote(Array.from(ballot), null)
+        .accounts({
+          proposal,
+          voter: wallet.publicKey,
+          hostConfig,
+          hostWorkCursor,
+          hostBallotRecord: ballotRecord,
+        })
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+        .rpc();
+      const record = await hostProgram.account.handleRecord.fetch(ballotRecord);
+      expect(record.bondLamports.toNumber(), "voter 의 bond 가 예치되지 않았습니다").to.equal(bond);
+      expect(record.payer.toBase58()).to.equal(wallet.publicKey.toBase58());
+    } finally {
+      await hostProgram.methods
+        .setRegistrationBond(new anchor.BN(0), new anchor.BN(1))
+        .accounts(admin)
+        .rpc();
+    }
+  });
+
+  it("CPI depth guard: a vote through the demo sits exactly at the boundary", async () => {
+    const tag = new Uint8Array(16).fill(244);
+    const [proposal] = PublicKey.findProgramAddressSync(
+      [Buffer.from("proposal"), wallet.publicKey.toBuffer(), Buffer.from(tag)],
+      votingProgram.programId
+    );
+    await votingProgram.methods
+      .createProposal(Array.from(tag), new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+      .accounts({ creator: wallet.publicKey })
+      .rpc();
+    const errorText = (err: unknown) =>
+      String(err) + ((err as { logs?: string[] }).logs ?? []).join("\n");
+
+    // user -> voting-demo -> host 의 stack height 는 2
+    await hostProgram.methods.setMaxCpiDepth(1).accounts({ admin: wallet.publicKey }).rpc();
+    try {
+      let rejected = false;
+      try {
+        await castVote(proposal, null, new Uint8Array(32).fill(245));
+      } catch (err) {
+        rejected = errorText(err).includes("CpiDepthExceeded");
+      }
+      expect(rejected, "max 1 에서 CPI 요청이 거부되지 않았습니다").to.be.true;
+    } finally {
+      await hostProgram.methods.setMaxCpiDepth(2).accounts({ admin: wallet.publicKey }).rpc();
+    }
+    await castVote(proposal, null, new Uint8Array(32).fill(245));
+
+    // runtime 한계를 넘는 값은 설정 자체가 거부된다
+    let tooLarge = false;
+    try {
+      await hostProgram.methods.setMaxCpiDepth(6).accounts({ admin: wallet.publicKey }).rpc();
+    } catch (err) {
+      tooLarge = String(err).includes("MaxCpiDepthOutOfRange");
+    }
+    expect(tooLarge, "stack 한계를 넘는 max_cpi_depth 가 설정되었습니다").to.be.true;
   });
 });
@@ -12,7 +12,7 @@ import {
 } from '@solana/web3.js'
 import { ACTIONS_CORS_HEADERS, BLOCKCHAIN_IDS, ActionGetResponse, ActionPostRequest, ActionPostResponse } from '@solana/actions'
 import { createLogger } from '@/lib/logger'
-import { buildRegisterInputHandleData, validatePublicKey } from '@/lib/solana/host-programs'
+import { buildRegisterInputHandleData, getConfigPda, getWorkCursorPda, validatePublicKey } from '@/lib/solana/host-programs'
 
 const log = createLogger('API:RegisterInputHandle')
 const connection = new Connection('https://api.devnet.solana.com', 'confirmed')
@@ -150,6 +150,8 @@ export async function POST(req: NextRequest) {
       programId: PROGRAM_ID,
       keys: [
         { pubkey: caller, isSigner: false, isWritable: false },
+        { pubkey: getConfigPda(PROGRAM_ID), isSigner: false, isWritable: false },
+        { pubkey: getWorkCursorPda(PROGRAM_ID), isSigner: false, isWritable: true },
       ],
       data,
     })
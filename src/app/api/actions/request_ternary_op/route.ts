@@ -12,7 +12,7 @@ import {
 } from '@solana/web3.js'
 import { ACTIONS_CORS_HEADERS, BLOCKCHAIN_IDS, ActionGetResponse, ActionPostRequest, ActionPostResponse } from '@solana/actions'
 import { createLogger } from '@/lib/logger'
-import { buildRequestTernaryOpData, Fhe16TernaryOp, validatePublicKey } from '@/lib/solana/host-programs'
+import { buildRequestTernaryOpData, getConfigPda, getWorkCursorPda, Fhe16TernaryOp, validatePublicKey } from '@/lib/solana/host-programs'
 
 const log = createLogger('API:RequestTernaryOp')
 const connection = new Connection('https://api.devnet.solana.com', 'confirmed')
@@ -184,6 +184,8 @@ export async function POST(req: NextRequest) {
       programId: PROGRAM_ID,
       keys: [
         { pubkey: caller, isSigner: false, isWritable: false },
+        { pubkey: getConfigPda(PROGRAM_ID), isSigner: false, isWritable: false },
+        { pubkey: getWorkCursorPda(PROGRAM_ID), isSigner: false, isWritable: true },
       ],
       data,
     })
@@ -12,7 +12,7 @@ import {
 } from '@solana/web3.js'
 import { ACTIONS_CORS_HEADERS, BLOCKCHAIN_IDS, ActionGetResponse, ActionPostRequest, ActionPostResponse } from '@solana/actions'
 import { createLogger } from '@/lib/logger'
-import { buildRequestUnaryOpData, Fhe16UnaryOp, validatePublicKey } from '@/lib/solana/host-programs'
+import { buildRequestUnaryOpData, getConfigPda, getWorkCursorPda, Fhe16UnaryOp, validatePublicKey } from '@/lib/solana/host-programs'
 
 const log = createLogger('API:RequestUnaryOp')
 const connection = new Connection('https://api.devnet.solana.com', 'confirmed')
@@ -147,6 +147,8 @@ export async function POST(req: NextRequest) {
       programId: PROGRAM_ID,
       keys: [
         { pubkey: caller, isSigner: false, isWritable: false },
+        { pubkey: getConfigPda(PROGRAM_ID), isSigner: false, isWritable: false },
+        { pubkey: getWorkCursorPda(PROGRAM_ID), isSigner: false, isWritable: true },
       ],
       data,
     })
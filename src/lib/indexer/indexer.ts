@@ -11,12 +11,20 @@ import type {
   IndexerConfig,
   EventHandlers,
   IndexerMode,
+  RequestMeta,
 } from "@/types/indexer";
 import { createDefaultConfig, detectRpcType, getRpcConfig, type RpcType, type RpcConfig } from "./config";
 import { createLogger } from "@/lib/logger";
 
 const log = createLogger("Indexer");
 
+type ParsedEvent = { name: string; data: Record<string, unknown> };
+
+// packed event 의 op_code 는 (family << 8) | op index, 등록이면 null
+const OP_FAMILY_UNARY = 0;
+const OP_FAMILY_BINARY = 1;
+const OP_FAMILY_TERNARY = 2;
+
 // 타입 재export (외부에서 사용)
 export type {
   IndexedEvent,
@@ -24,6 +32,7 @@ export type {
   Fhe16UnaryOpRequestedEvent,
   Fhe16BinaryOpRequestedEvent,
   Fhe16TernaryOpRequestedEvent,
+  RequestMeta,
   IndexerConfig,
   EventHandlers,
   IndexerMode,
@@ -35,6 +44,7 @@ export class HostProgramsIndexer {
   private connection: Connection;
   private programId: PublicKey;
   private eventParser: EventParser;
+  private idl: Idl;
   private subscriptionId: number | null = null;
   private config: Required<IndexerConfig>;
   private handlers: EventHandlers = {};
@@ -78,6 +88,7 @@ export class HostProgramsIndexer {
     });
 
     this.programId = new PublicKey(this.config.programId);
+    this.idl = idl;
     
     // Anchor EventParser 사용
     const coder = new BorshCoder(idl);
@@ -633,16 +644,45 @@ export class HostProgramsIndexer {
 
       caller = this.extractCaller(finalTx);
 
+      // RequestMeta 는 다음 op event 에, packed event 의 caller/origin 은
+      // 생략된 다음 packed event 에 넘어간다
+      let pendingMeta: RequestMeta | null = null;
+      let lastPacked: { caller: unknown; originProgram: unknown } | null = null;
+
       // 각 이벤트 처리
-      for (const event of events) {
+      for (const parsed of events) {
         if (process.env.NODE_ENV === "development") {
           log.debug("Processing event", {
-            event_name: event.name,
-            data_keys: Object.keys(event.data || {}),
+            event_name: parsed.name,
+            data_keys: Object.keys(parsed.data || {}),
             signature,
           });
         }
 
+        let event: ParsedEvent = parsed;
+        const eventName = this.normalizeEventName(parsed.name);
+        if (eventName === "RequestMeta") {
+          pendingMeta = this.toRequestMeta(parsed.data);
+          continue;
+        }
+        if (eventName === "Fhe16PackedOpRequested") {
+          const packedCaller = this.getFieldValue(parsed.data, "caller");
+          const packedOrigin = this.getFieldValue(
+            parsed.data,
+            "origin_program",
+            "originProgram"
+          );
+          if (packedCaller !== undefined && packedOrigin !== undefined) {
+            lastPacked = { caller: packedCaller, originProgram: packedOrigin };
+          }
+          const unpacked = lastPacked && this.unpackEvent(parsed.data, lastPacked);
+          if (!unpacked) {
+            log.warn("Packed event could not be unpacked", { signature });
+            continue;
+          }
+          event = unpacked;
+        }
+
         const indexedEvent = this.createIndexedEvent(
           event,
           signature,
@@ -652,6 +692,10 @@ export class HostProgramsIndexer {
         );
 
         if (indexedEvent) {
+          if (pendingMeta) {
+            indexedEvent.meta = pendingMeta;
+            pendingMeta = null;
+          }
           await this.dispatchEvent(indexedEvent);
         } else {
           log.warn("Event conversion failed", { event_name: event.name, signature });
@@ -686,6 +730,91 @@ export class HostProgramsIndexer {
     return accountKeys?.[0]?.toString() || "unknown";
   }
 
+  /**
+   * RequestMeta event 를 다음 op event 에 붙일 형태로 변환
+   */
+  private toRequestMeta(data: Record<string, unknown>): RequestMeta {
+    const num = (value: unknown) => Number(value?.toString() ?? 0);
+    return {
+      seq: num(this.getFieldValue(data, "seq")),
+      timestamp: num(this.getFieldValue(data, "timestamp")),
+      stackHeight: num(this.getFieldValue(data, "stack_height", "stackHeight")),
+    };
+  }
+
+  /**
+   * IDL enum 의 variant index 를 EventParser 가 decode 하는 형태 ({ add: {} }) 로 변환
+   */
+  private opVariant(typeName: string, index: number): Record<string, object> | null {
+    const typeDef = this.idl.types?.find((t) => t.name === typeName)?.type;
+    const variant = typeDef?.kind === "enum" ? typeDef.variants[index] : undefined;
+    if (!variant) return null;
+    return { [variant.name.charAt(0).toLowerCase() + variant.name.slice(1)]: {} };
+  }
+
+  /**
+   * Fhe16PackedOpRequested 를 그것이 대신하는 arity 별 event 로 변환
+   * (caller/origin 이 생략된 경우 앞선 packed event 의 것을 사용)
+   */
+  private unpackEvent(
+    data: Record<string, unknown>,
+    inherited: { caller: unknown; originProgram: unknown }
+  ): ParsedEvent | null {
+    const request = this.getFieldValue(data, "request");
+    if (!(request instanceof Uint8Array)) return null;
+    const opCode = this.getFieldValue(data, "op_code", "opCode") as Record<number, number> | null;
+    const handleAt = (offset: number) => Array.from(request.slice(offset, offset + 32));
+    const base = {
+      caller: inherited.caller,
+      origin_program: inherited.originProgram,
+      result_handle: this.getFieldValue(data, "result_handle", "resultHandle"),
+    };
+
+    if (opCode == null) {
+      if (request.length !== 64) return null;
+      return {
+        name: "InputHandleRegistered",
+        data: { caller: base.caller, handle: handleAt(0), client_tag: handleAt(32) },
+      };
+    }
+    const index = opCode[0] & 0xff;
+    switch (opCode[0] >> 8) {
+      case OP_FAMILY_UNARY: {
+        const op = this.opVariant("Fhe16UnaryOp", index);
+        if (!op || request.length !== 32) return null;
+        return {
+          name: "Fhe16UnaryOpRequested",
+          data: { ...base, op, input_handle: handleAt(0) },
+        };
+      }
+      case OP_FAMILY_BINARY: {
+        const op = this.opVariant("Fhe16BinaryOp", index);
+        if (!op || request.length !== 64) return null;
+        return {
+          name: "Fhe16BinaryOpRequested",
+          data: { ...base, op, lhs_handle: handleAt(0), rhs_handle: handleAt(32) },
+        };
+      }
+      case OP_FAMILY_TERNARY: {
+        const op = this.opVariant("Fhe16TernaryOp", index);
+        if (!op || request.length !== 96) return null;
+        return {
+          name: "Fhe16TernaryOpRequested",
+          data: {
+            ...base,
+            op,
+            a_handle: handleAt(0),
+            b_handle: handleAt(32),
+            c_handle: handleAt(64),
+          },
+        };
+      }
+      default:
+        // pad / truncate / concat 는 아직 인덱싱하지 않음
+        return null;
+    }
+  }
+
   /**
    * 이벤트 이름 정규화 (PascalCase로 통일)
    */
@@ -737,6 +866,11 @@ export class HostProgramsIndexer {
       // caller는 snake_case 우선, 없으면 fallback
       caller:
         this.getFieldValue(event.data, "caller")?.toString() || caller,
+      originProgram: this.getFieldValue(
+        event.data,
+        "origin_program",
+        "originProgram"
+      )?.toString(),
     };
 
     const extractOpName = (op: unknown): string => {
@@ -810,7 +944,8 @@ export class HostProgramsIndexer {
         };
       }
 
-      case "Fhe16BinaryOpRequested": {
+      case "Fhe16BinaryOpRequested":
+      case "Fhe16BinaryOpCategorized": {
         const op = extractOpName(
           this.getFieldValue(event.data, "op")
         );
@@ -825,7 +960,7 @@ export class HostProgramsIndexer {
         );
 
         if (lhsHandle.length === 0 || rhsHandle.length === 0 || resultHandle.length === 0) {
-          log.warn("Fhe16BinaryOpRequested event fields are empty", { signature });
+          log.warn(`${normalizedName} event fields are empty`, { signature });
           return null;
         }
 
@@ -836,6 +971,9 @@ export class HostProgramsIndexer {
           lhsHandle,
           rhsHandle,
           resultHandle,
+          ...(normalizedName === "Fhe16BinaryOpCategorized" && {
+            category: extractOpName(this.getFieldValue(event.data, "category")),
+          }),
         };
       }
 
@@ -9,6 +9,17 @@ import { PublicKey } from '@solana/web3.js';
 const HANDLE_DOMAIN_UNARY = new TextEncoder().encode('FHE16_UNARY_V1');
 const HANDLE_DOMAIN_BINARY = new TextEncoder().encode('FHE16_BINARY_V1');
 const HANDLE_DOMAIN_TERNARY = new TextEncoder().encode('FHE16_TERNARY_V1');
+const HANDLE_DOMAIN_INPUT = new TextEncoder().encode('FHE16_INPUT');
+
+// 현재 암호문 인코딩 scheme (v1 = 기존 sha256(ct) 와 동일)
+export const DEFAULT_INPUT_SCHEME_VERSION = 1;
+
+// Config.domain_salt 기본값 (all-zero 이면 hash 에 포함되지 않음 → 기존 derivation)
+export const NO_DOMAIN_SALT = new Uint8Array(16);
+
+function saltBytes(salt: Uint8Array): Uint8Array {
+  return salt.every((b) => b === 0) ? new Uint8Array(0) : salt;
+}
 
 // Helper: 여러 Uint8Array를 하나로 합침
 function concatBytes(...arrays: Uint8Array[]): Uint8Array {
@@ -35,15 +46,23 @@ export function bytesToHex(bytes: Uint8Array): string {
  * [Core Logic] 입력 핸들 생성 (Input Handle Derivation)
  * 암호화된 데이터 배열로부터 결정적 핸들을 생성
  * 규칙: 신규 암호문의 등록의 경우 항상 SHA256 해시를 사용합니다. Solana의 hashv와 동일한 방식으로 계산됩니다.
+ * 인코딩 scheme 이 바뀌면 version 이 해시에 포함되어 같은 바이트라도 핸들이 달라집니다.
  * @param encryptedData - 암호화된 데이터 배열 (number[])
+ * @param schemeVersion - 암호문 인코딩 scheme version (기본값 1)
  * @returns 32바이트 hex 문자열 핸들
  */
-export function deriveInputHandle(encryptedData: number[]): string {
+export function deriveInputHandle(
+  encryptedData: number[],
+  schemeVersion: number = DEFAULT_INPUT_SCHEME_VERSION
+): string {
   // 암호화된 데이터 배열을 바이트 배열로 변환 (little-endian 32-bit integer)
   const ctBytes = Buffer.from(new Uint32Array(encryptedData).buffer);
 
-  // SHA256 해시 계산 (Solana의 hashv와 동일한 방식)
-  const hash = sha256(ctBytes);
+  // SHA256 해시 계산 (Rust의 derive_input_handle과 동일한 방식)
+  const hash =
+    schemeVersion === DEFAULT_INPUT_SCHEME_VERSION
+      ? sha256(ctBytes)
+      : sha256(concatBytes(HANDLE_DOMAIN_INPUT, new Uint8Array([schemeVersion]), ctBytes));
 
   // 32바이트 해시를 hex 문자열로 변환
   return bytesToHex(hash);
@@ -54,20 +73,23 @@ export function deriveInputHandle(encryptedData: number[]): string {
  * @param op - 연산자 Enum 값 (예: Not=0, Abs=1, Neg=2)
  * @param inputHandle - 입력 핸들 (32 bytes hex)
  * @param programIdString - 솔라나 프로그램 ID
+ * @param domainSalt - Config.domain_salt (rotation 전에는 all-zero)
  */
 export function deriveUnaryHandle(
   op: number,
   inputHandle: string,
-  programIdString: string
+  programIdString: string,
+  domainSalt: Uint8Array = NO_DOMAIN_SALT
 ): string {
   try {
     const opByte = new Uint8Array([op]);
     const inputBytes = hexToBytes(inputHandle);
     const programIdBytes = new PublicKey(programIdString).toBuffer();
 
-    // Hash Payload 구조: [DOMAIN, PROGRAM_ID, OP, INPUT]
+    // Hash Payload 구조: [DOMAIN, SALT?, PROGRAM_ID, OP, INPUT]
     const payload = concatBytes(
       HANDLE_DOMAIN_UNARY,
+      saltBytes(domainSalt),
       programIdBytes,
       opByte,
       inputBytes
@@ -87,12 +109,14 @@ export function deriveUnaryHandle(
  * @param lhsHandle - 좌변 핸들 (32 bytes hex)
  * @param rhsHandle - 우변 핸들 (32 bytes hex)
  * @param programIdString - 솔라나 프로그램 ID
+ * @param domainSalt - Config.domain_salt (rotation 전에는 all-zero)
  */
 export function deriveBinaryHandle(
   op: number,
   lhsHandle: string,
   rhsHandle: string,
-  programIdString: string
+  programIdString: string,
+  domainSalt: Uint8Array = NO_DOMAIN_SALT
 ): string {
   try {
     const opByte = new Uint8Array([op]);
@@ -100,9 +124,10 @@ export function deriveBinaryHandle(
     const rhsBytes = hexToBytes(rhsHandle);
     const programIdBytes = new PublicKey(programIdString).toBuffer();
 
-    // Hash Payload 구조: [DOMAIN, PROGRAM_ID, OP, LHS, RHS]
+    // Hash Payload 구조: [DOMAIN, SALT?, PROGRAM_ID, OP, LHS, RHS]
     const payload = concatBytes(
       HANDLE_DOMAIN_BINARY,
+      saltBytes(domainSalt),
       programIdBytes,
       opByte,
       lhsBytes,
@@ -124,13 +149,15 @@ export function deriveBinaryHandle(
  * @param bHandle - 두 번째 핸들 (32 bytes hex)
  * @param cHandle - 세 번째 핸들 (32 bytes hex)
  * @param programIdString - 솔라나 프로그램 ID
+ * @param domainSalt - Config.domain_salt (rotation 전에는 all-zero)
  */
 export function deriveTernaryHandle(
   op: number,
   aHandle: string,
   bHandle: string,
   cHandle: string,
-  programIdString: string
+  programIdString: string,
+  domainSalt: Uint8Array = NO_DOMAIN_SALT
 ): string {
   try {
     const opByte = new Uint8Array([op]);
@@ -139,9 +166,10 @@ export function deriveTernaryHandle(
     const cBytes = hexToBytes(cHandle);
     const programIdBytes = new PublicKey(programIdString).toBuffer();
 
-    // Hash Payload 구조: [DOMAIN, PROGRAM_ID, OP, A, B, C]
+    // Hash Payload 구조: [DOMAIN, SALT?, PROGRAM_ID, OP, A, B, C]
     const payload = concatBytes(
       HANDLE_DOMAIN_TERNARY,
+      saltBytes(domainSalt),
       programIdBytes,
       opByte,
       aBytes,
@@ -35,6 +35,22 @@ export function validatePublicKey(account: string): PublicKey {
 
 export type Handle = Uint8Array | Buffer | number[]
 
+/**
+ * Derive the program Config PDA (seeds: ["config"])
+ */
+export function getConfigPda(programId: PublicKey): PublicKey {
+  const [config] = PublicKey.findProgramAddressSync([Buffer.from('config')], programId)
+  return config
+}
+
+/**
+ * Work cursor PDA (seeds: ["work_cursor"]), bumped by every request instruction
+ */
+export function getWorkCursorPda(programId: PublicKey): PublicKey {
+  const [workCursor] = PublicKey.findProgramAddressSync([Buffer.from('work_cursor')], programId)
+  return workCursor
+}
+
 /**
  * Convert handle to Buffer (32 bytes)
  */
@@ -67,6 +83,26 @@ export function serializeEnum(value: number): Buffer {
   return Buffer.from([value])
 }
 
+/**
+ * Serialize Option<u64> (Borsh: 0 = None, 1 + u64 LE = Some)
+ */
+export function serializeOptionU64(value?: bigint | number | null): Buffer {
+  if (value === undefined || value === null) {
+    return Buffer.from([0])
+  }
+  const buf = Buffer.alloc(9)
+  buf.writeUInt8(1, 0)
+  buf.writeBigUInt64LE(BigInt(value), 1)
+  return buf
+}
+
+/**
+ * Serialize bool (Borsh: 1 byte)
+ */
+export function serializeBool(value: boolean): Buffer {
+  return Buffer.from([value ? 1 : 0])
+}
+
 /**
  * Build initialize instruction data
  */
@@ -94,13 +130,21 @@ export function buildRegisterInputHandleData(
  */
 export function buildRequestUnaryOpData(
   op: number,
-  inputHandle: Handle
+  inputHandle: Handle,
+  callerNonce?: bigint | number | null,
+  dryRun = false
 ): Buffer {
   const discriminator = getInstructionDiscriminator('request_unary_op')
   const opBuf = serializeEnum(op)
   const handleBuf = handleToBuffer(inputHandle)
   
-  return Buffer.concat([discriminator, opBuf, handleBuf])
+  return Buffer.concat([
+    discriminator,
+    opBuf,
+    handleBuf,
+    serializeOptionU64(callerNonce),
+    serializeBool(dryRun),
+  ])
 }
 
 /**
@@ -109,14 +153,23 @@ export function buildRequestUnaryOpData(
 export function buildRequestBinaryOpData(
   op: number,
   lhsHandle: Handle,
-  rhsHandle: Handle
+  rhsHandle: Handle,
+  callerNonce?: bigint | number | null,
+  dryRun = false
 ): Buffer {
   const discriminator = getInstructionDiscriminator('request_binary_op')
   const opBuf = serializeEnum(op)
   const lhsBuf = handleToBuffer(lhsHandle)
   const rhsBuf = handleToBuffer(rhsHandle)
   
-  return Buffer.concat([discriminator, opBuf, lhsBuf, rhsBuf])
+  return Buffer.concat([
+    discriminator,
+    opBuf,
+    lhsBuf,
+    rhsBuf,
+    serializeOptionU64(callerNonce),
+    serializeBool(dryRun),
+  ])
 }
 
 /**
@@ -126,7 +179,9 @@ export function buildRequestTernaryOpData(
   op: number,
   aHandle: Handle,
   bHandle: Handle,
-  cHandle: Handle
+  cHandle: Handle,
+  callerNonce?: bigint | number | null,
+  dryRun = false
 ): Buffer {
   const discriminator = getInstructionDiscriminator('request_ternary_op')
   const opBuf = serializeEnum(op)
@@ -134,7 +189,15 @@ export function buildRequestTernaryOpData(
   const bBuf = handleToBuffer(bHandle)
   const cBuf = handleToBuffer(cHandle)
   
-  return Buffer.concat([discriminator, opBuf, aBuf, bBuf, cBuf])
+  return Buffer.concat([
+    discriminator,
+    opBuf,
+    aBuf,
+    bBuf,
+    cBuf,
+    serializeOptionU64(callerNonce),
+    serializeBool(dryRun),
+  ])
 }
 
 /**
@@ -170,9 +233,9 @@ export const Fhe16BinaryOp = {
   SMulL: 20,
   AddPowTwo: 21,
   SubPowTwo: 22,
-  GateTemplete: 23,
-  PrefixTemplete: 24,
-  AddPowTwoTemplete: 25,
+  GateTemplate: 23,
+  PrefixTemplate: 24,
+  AddPowTwoTemplate: 25,
   OrXor: 26,
   AndXor: 27,
 } as const
@@ -7,6 +7,17 @@ export interface BaseEvent {
   slot: number;
   blockTime: number | null;
   caller: string;
+  // top-level instruction 의 program (CPI 로 호출된 경우 dapp program)
+  originProgram?: string;
+  // enriched / categorized format 에서 op event 앞에 오는 RequestMeta
+  meta?: RequestMeta;
+}
+
+export interface RequestMeta {
+  // caller work cursor 의 sequence 번호
+  seq: number;
+  timestamp: number;
+  stackHeight: number;
 }
 
 export interface InputHandleRegisteredEvent extends BaseEvent {
@@ -28,6 +39,8 @@ export interface Fhe16BinaryOpRequestedEvent extends BaseEvent {
   lhsHandle: number[];
   rhsHandle: number[];
   resultHandle: number[];
+  // categorized format (Fhe16BinaryOpCategorized) 에서만 채워짐
+  category?: string;
 }
 
 export interface Fhe16TernaryOpRequestedEvent extends BaseEvent {